@@ -0,0 +1,109 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Baggage Attributes
+//!
+//! Attaches selected W3C baggage entries as attributes to metric recordings.
+//!
+//! When a request carries baggage such as `tenant=acme`, the entries listed in
+//! `MetricsConfig::baggage_keys` can be added to the attributes of the measurements recorded
+//! while handling that request. This enables per-tenant breakdowns without passing the values
+//! through the application code.
+//!
+//! The OpenTelemetry API does not allow intercepting measurements, so the entries are added
+//! explicitly by wrapping the attributes with [`with_baggage`] at the recording site. The
+//! allowlist is configured when the provider is installed; until then, and when the list is
+//! empty, no baggage is attached.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use metrics::baggage::with_baggage;
+//! use opentelemetry::{KeyValue, global};
+//!
+//! let counter = global::meter("orders").u64_counter("orders.created").build();
+//!
+//! // with `baggage_keys = ["tenant"]` and the baggage `tenant=acme` on the current context,
+//! // records the attributes `region=eu` and `tenant=acme`
+//! counter.add(1, &with_baggage(&[KeyValue::new("region", "eu")]));
+//! ```
+
+use opentelemetry::{Context, KeyValue, Value, baggage::BaggageExt};
+use std::sync::RwLock;
+
+static BAGGAGE_KEYS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Sets the baggage keys attached to recordings.
+pub(crate) fn configure(keys: &[String]) {
+    *BAGGAGE_KEYS.write().unwrap_or_else(|e| e.into_inner()) = keys.to_vec();
+}
+
+/// Returns the given attributes followed by the allowed baggage entries of the current context.
+///
+/// # Parameters
+///
+/// * `attributes` - The attributes of the recording
+///
+/// # Returns
+///
+/// The attributes, extended with one attribute per allowed baggage entry present in the
+/// current context
+pub fn with_baggage(attributes: &[KeyValue]) -> Vec<KeyValue> {
+    let mut attributes = attributes.to_vec();
+
+    let keys = BAGGAGE_KEYS.read().unwrap_or_else(|e| e.into_inner());
+    if keys.is_empty() {
+        return attributes;
+    }
+
+    let cx = Context::current();
+    let baggage = cx.baggage();
+    for key in keys.iter() {
+        if let Some(value) = baggage.get(key) {
+            attributes.push(KeyValue::new(key.clone(), Value::String(value.clone())));
+        }
+    }
+
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn allowed_baggage_entries_are_appended() {
+        let _lock = test_util::global_lock();
+        configure(&["tenant".to_string()]);
+        let _cx = Context::current_with_baggage(vec![
+            KeyValue::new("tenant", "acme"),
+            KeyValue::new("user", "alice"),
+        ])
+        .attach();
+
+        let attributes = with_baggage(&[KeyValue::new("region", "eu")]);
+        configure(&[]);
+
+        assert_eq!(
+            attributes,
+            vec![
+                KeyValue::new("region", "eu"),
+                KeyValue::new("tenant", "acme")
+            ]
+        );
+    }
+
+    #[test]
+    fn no_baggage_is_attached_without_keys() {
+        let _lock = test_util::global_lock();
+        configure(&[]);
+        let _cx = Context::current_with_baggage(vec![KeyValue::new("tenant", "acme")]).attach();
+
+        assert_eq!(
+            with_baggage(&[KeyValue::new("region", "eu")]),
+            vec![KeyValue::new("region", "eu")]
+        );
+    }
+}
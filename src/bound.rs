@@ -0,0 +1,119 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Bound Instruments
+//!
+//! Provides helpers binding an instrument to a fixed attribute set.
+//!
+//! Hot paths often record on the same counter with the same attributes on every call. Binding
+//! the counter once builds the attribute set a single time, and the returned closure only takes
+//! the value to add.
+//!
+//! The OpenTelemetry SDK does not expose a pre-bound recording path, so the SDK still looks up
+//! the attribute set on every `add`. Binding saves building the `KeyValue` slice at each call
+//! site, not the aggregation lookup.
+//!
+//! ## Counting Events
+//!
+//! The most common recording adds `1` without attributes. The SDK keeps measurements without
+//! attributes in a dedicated slot of every aggregation, so they skip the attribute set lookup
+//! entirely and never allocate. [`inc`] is the shorthand for that path.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use opentelemetry::{KeyValue, global};
+//!
+//! let counter = global::meter("http").u64_counter("http.requests").build();
+//! let add = metrics::bind(counter, &[KeyValue::new("http.method", "GET")]);
+//!
+//! for _ in 0..1_000 {
+//!     add(1);
+//! }
+//!
+//! let processed = global::meter("jobs").u64_counter("jobs.processed").build();
+//! metrics::inc(&processed);
+//! ```
+
+use crate::non_finite::MeasurementValue;
+use opentelemetry::{KeyValue, metrics::Counter};
+
+/// Binds a counter to a fixed attribute set.
+///
+/// # Parameters
+///
+/// * `counter` - The counter to record on
+/// * `attrs` - The attributes attached to every recording
+///
+/// # Returns
+///
+/// A closure adding its argument to the counter with the bound attributes, subject to the
+/// configured [`NonFinitePolicy`](crate::config::NonFinitePolicy)
+pub fn bind<T>(counter: Counter<T>, attrs: &[KeyValue]) -> impl Fn(T) + Send + Sync + 'static
+where
+    T: MeasurementValue + 'static,
+{
+    let attrs = attrs.to_vec();
+
+    move |value| {
+        if let Some(value) = value.guard() {
+            counter.add(value, &attrs)
+        }
+    }
+}
+
+/// Adds `1` to a counter, without attributes.
+///
+/// Recording without attributes is the allocation-free path of the SDK, which makes this
+/// suitable for the hottest loops.
+///
+/// # Parameters
+///
+/// * `counter` - The counter to increment
+pub fn inc(counter: &Counter<u64>) {
+    counter.add(1, &[]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::MetricsConfig, test_util};
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry_sdk::metrics::data::Sum;
+
+    #[test]
+    fn bound_counter_records_with_the_bound_attributes() {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        let counter = provider.meter("test").u64_counter("requests").build();
+        let add = bind(counter, &[KeyValue::new("method", "GET")]);
+
+        add(2);
+        add(3);
+
+        let rm = test_util::collect(&reader);
+        let requests = test_util::metric(&rm, "requests").expect("counter");
+        let sum = requests.data.as_any().downcast_ref::<Sum<u64>>().unwrap();
+        assert_eq!(sum.data_points.len(), 1);
+        assert_eq!(sum.data_points[0].value, 5);
+        assert_eq!(
+            sum.data_points[0].attributes,
+            vec![KeyValue::new("method", "GET")]
+        );
+    }
+
+    #[test]
+    fn inc_adds_one_without_attributes() {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        let counter = provider.meter("test").u64_counter("requests").build();
+
+        inc(&counter);
+        inc(&counter);
+
+        let rm = test_util::collect(&reader);
+        let requests = test_util::metric(&rm, "requests").expect("counter");
+        let sum = requests.data.as_any().downcast_ref::<Sum<u64>>().unwrap();
+        assert_eq!(sum.data_points[0].value, 2);
+        assert!(sum.data_points[0].attributes.is_empty());
+    }
+}
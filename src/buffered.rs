@@ -0,0 +1,220 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Buffered Recording
+//!
+//! Provides recorders that hand measurements to a background task through a bounded channel.
+//!
+//! Recording on an instrument updates the same aggregation state the collection reads, so under
+//! extreme load the hot path can contend with collection and export. A [`BufferedRecorder`]
+//! only pushes the measurement into a bounded channel; a background task owns the instrument
+//! and records what it receives.
+//!
+//! ## Tradeoffs
+//!
+//! - Recording is eventually consistent: a measurement reaches the aggregation once the
+//!   background task processes it, so a collection may miss the most recent measurements
+//! - Recording never blocks. When the channel is full the measurement is dropped, and the
+//!   `metrics.buffered.dropped` counter is incremented with the name of the instrument
+//! - The background task runs on the tokio runtime the recorder was created in
+//!
+//! Only services where recording itself shows up in profiles should use these recorders.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use metrics::buffered::BufferedRecorder;
+//! use opentelemetry::{KeyValue, global};
+//! use tokio::runtime::Builder;
+//!
+//! let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+//! runtime.block_on(async {
+//!     let counter = global::meter("ingest").u64_counter("ingest.events").build();
+//!     let recorder = BufferedRecorder::counter("ingest.events", counter, 10_000).unwrap();
+//!
+//!     recorder.record(1, &[KeyValue::new("source", "kafka")]);
+//! });
+//! ```
+
+use crate::{LOG_TARGET, errors::MetricsError, non_finite::MeasurementValue};
+use opentelemetry::{
+    KeyValue, global,
+    metrics::{Counter, Histogram},
+};
+use std::borrow::Cow;
+use tokio::{
+    runtime::Handle,
+    sync::mpsc::{self, Sender},
+};
+use tracing::error;
+
+/// Name of the counter incremented for every dropped measurement.
+const DROPPED_COUNTER: &str = "metrics.buffered.dropped";
+
+/// # BufferedRecorder
+///
+/// Records measurements through a bounded channel drained by a background task.
+#[derive(Debug, Clone)]
+pub struct BufferedRecorder<T> {
+    name: Cow<'static, str>,
+    sender: Sender<(T, Vec<KeyValue>)>,
+    dropped: Counter<u64>,
+}
+
+impl<T: MeasurementValue + Send + 'static> BufferedRecorder<T> {
+    /// Creates a recorder adding to `counter` from a background task.
+    ///
+    /// # Parameters
+    ///
+    /// * `name` - The name of the instrument, attached to the dropped measurements counter
+    /// * `counter` - The counter receiving the measurements
+    /// * `capacity` - The number of measurements the channel holds before dropping
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(BufferedRecorder)` - The recorder
+    /// * `Err(MetricsError::InternalError)` - If called outside of a tokio runtime
+    pub fn counter(
+        name: impl Into<Cow<'static, str>>,
+        counter: Counter<T>,
+        capacity: usize,
+    ) -> Result<Self, MetricsError> {
+        Self::spawn(name.into(), capacity, move |value, attrs| {
+            counter.add(value, attrs)
+        })
+    }
+
+    /// Creates a recorder recording to `histogram` from a background task.
+    ///
+    /// # Parameters
+    ///
+    /// * `name` - The name of the instrument, attached to the dropped measurements counter
+    /// * `histogram` - The histogram receiving the measurements
+    /// * `capacity` - The number of measurements the channel holds before dropping
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(BufferedRecorder)` - The recorder
+    /// * `Err(MetricsError::InternalError)` - If called outside of a tokio runtime
+    pub fn histogram(
+        name: impl Into<Cow<'static, str>>,
+        histogram: Histogram<T>,
+        capacity: usize,
+    ) -> Result<Self, MetricsError> {
+        Self::spawn(name.into(), capacity, move |value, attrs| {
+            histogram.record(value, attrs)
+        })
+    }
+
+    /// Queues a measurement, dropping it when the channel is full.
+    ///
+    /// Non-finite values are handled according to the configured
+    /// [`NonFinitePolicy`](crate::config::NonFinitePolicy) before being queued.
+    ///
+    /// # Parameters
+    ///
+    /// * `value` - The measurement
+    /// * `attrs` - The attributes of the measurement
+    pub fn record(&self, value: T, attrs: &[KeyValue]) {
+        let Some(value) = value.guard() else {
+            return;
+        };
+
+        // a closed channel means the runtime running the task is gone, drop as well
+        if self.sender.try_send((value, attrs.to_vec())).is_err() {
+            self.dropped
+                .add(1, &[KeyValue::new("instrument", self.name.clone())]);
+        }
+    }
+
+    fn spawn<F>(name: Cow<'static, str>, capacity: usize, record: F) -> Result<Self, MetricsError>
+    where
+        F: Fn(T, &[KeyValue]) + Send + 'static,
+    {
+        let Ok(handle) = Handle::try_current() else {
+            error!(
+                target: LOG_TARGET,
+                instrument = %name,
+                "buffered recorder requires a tokio runtime"
+            );
+            return Err(MetricsError::InternalError);
+        };
+
+        let (sender, mut receiver) = mpsc::channel::<(T, Vec<KeyValue>)>(capacity.max(1));
+
+        handle.spawn(async move {
+            while let Some((value, attrs)) = receiver.recv().await {
+                record(value, &attrs);
+            }
+        });
+
+        Ok(Self {
+            name,
+            sender,
+            dropped: global::meter("metrics")
+                .u64_counter(DROPPED_COUNTER)
+                .build(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::MetricsConfig, test_util};
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry_sdk::metrics::data::Sum;
+
+    fn total(reader: &crate::exporters::reader::SharedReader) -> u64 {
+        let rm = test_util::collect(reader);
+        test_util::metric(&rm, "events")
+            .and_then(|m| m.data.as_any().downcast_ref::<Sum<u64>>())
+            .map(|sum| sum.data_points.iter().map(|dp| dp.value).sum())
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn measurements_are_recorded_by_the_background_task() {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        let counter = provider.meter("test").u64_counter("events").build();
+
+        test_util::block_on(async {
+            let recorder = BufferedRecorder::counter("events", counter, 10).unwrap();
+            recorder.record(1, &[]);
+            recorder.record(2, &[]);
+
+            // nothing is recorded until the background task runs
+            assert_eq!(total(&reader), 0);
+            tokio::task::yield_now().await;
+        });
+
+        assert_eq!(total(&reader), 3);
+    }
+
+    #[test]
+    fn measurements_are_dropped_when_the_channel_is_full() {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        let counter = provider.meter("test").u64_counter("events").build();
+
+        test_util::block_on(async {
+            let recorder = BufferedRecorder::counter("events", counter, 1).unwrap();
+            recorder.record(1, &[]);
+            recorder.record(2, &[]);
+            tokio::task::yield_now().await;
+        });
+
+        assert_eq!(total(&reader), 1);
+    }
+
+    #[test]
+    fn recorders_require_a_runtime() {
+        let (provider, _reader) = test_util::provider(&MetricsConfig::default());
+        let counter = provider.meter("test").u64_counter("events").build();
+
+        assert_eq!(
+            BufferedRecorder::counter("events", counter, 1).unwrap_err(),
+            MetricsError::InternalError
+        );
+    }
+}
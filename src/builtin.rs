@@ -0,0 +1,106 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Built-in Instruments
+//!
+//! Registers the instruments the crate itself can report on the installed provider.
+//!
+//! These instruments describe the process rather than the application domain, and are
+//! registered on demand by the install functions of the [`provider`](crate::provider) module.
+
+use opentelemetry::{KeyValue, metrics::MeterProvider};
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use std::time::Instant;
+
+/// Name of the meter owning the built-in instruments.
+const METER_NAME: &str = "metrics";
+
+/// Registers the `build_info` gauge, always reporting `1` with the build attributes.
+///
+/// # Parameters
+///
+/// * `provider` - The installed meter provider
+/// * `version` - Value of the `version` attribute
+/// * `commit` - Value of the `commit` attribute
+pub(crate) fn register_build_info(provider: &SdkMeterProvider, version: &str, commit: &str) {
+    let attributes = [
+        KeyValue::new("version", version.to_string()),
+        KeyValue::new("commit", commit.to_string()),
+        KeyValue::new("rustc", env!("RUSTC_VERSION")),
+    ];
+
+    provider
+        .meter(METER_NAME)
+        .u64_observable_gauge("build_info")
+        .with_description("Build information of the running binary, always 1")
+        .with_callback(move |observer| observer.observe(1, &attributes))
+        .build();
+}
+
+/// Registers the `process_uptime_seconds` gauge, reporting the seconds elapsed since now.
+///
+/// # Parameters
+///
+/// * `provider` - The installed meter provider
+pub(crate) fn register_uptime(provider: &SdkMeterProvider) {
+    let installed_at = Instant::now();
+
+    provider
+        .meter(METER_NAME)
+        .f64_observable_gauge("process_uptime_seconds")
+        .with_description("Seconds elapsed since the metrics provider was installed")
+        .with_unit("s")
+        .with_callback(move |observer| observer.observe(installed_at.elapsed().as_secs_f64(), &[]))
+        .build();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::MetricsConfig, test_util};
+    use opentelemetry_sdk::metrics::data::Gauge;
+
+    #[test]
+    fn build_info_reports_the_build_attributes() {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        register_build_info(&provider, "1.2.3", "abc123");
+
+        let rm = test_util::collect(&reader);
+        let metric = test_util::metric(&rm, "build_info").expect("build_info");
+        let gauge = metric.data.as_any().downcast_ref::<Gauge<u64>>().unwrap();
+        let point = &gauge.data_points[0];
+
+        assert_eq!(point.value, 1);
+        assert!(
+            point
+                .attributes
+                .contains(&KeyValue::new("version", "1.2.3"))
+        );
+        assert!(
+            point
+                .attributes
+                .contains(&KeyValue::new("commit", "abc123"))
+        );
+        let rustc = point
+            .attributes
+            .iter()
+            .find(|kv| kv.key.as_str() == "rustc")
+            .expect("rustc attribute");
+        assert!(rustc.value.as_str().starts_with("rustc "));
+    }
+
+    #[test]
+    fn uptime_grows_from_registration() {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        register_uptime(&provider);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+
+        let rm = test_util::collect(&reader);
+        let metric = test_util::metric(&rm, "process_uptime_seconds").expect("uptime");
+        let gauge = metric.data.as_any().downcast_ref::<Gauge<f64>>().unwrap();
+
+        assert_eq!(metric.unit, "s");
+        assert!(gauge.data_points[0].value >= 0.01);
+    }
+}
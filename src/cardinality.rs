@@ -0,0 +1,131 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Cardinality
+//!
+//! Reports how many distinct series each instrument currently has.
+//!
+//! Every distinct attribute set recorded on an instrument is a series, which the backends store
+//! and bill separately. [`series_counts`] counts the data points of collected metrics, one per
+//! series, so a pre-production audit can spot the instruments whose cardinality grows before
+//! it becomes a cost problem. It works on the `ResourceMetrics` of any manual reader or of the
+//! in-memory exporter, and the Prometheus registry exposes the same counts for the metrics it
+//! serves through `PrometheusRegistry::series_counts`.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use metrics::cardinality::series_counts;
+//! use opentelemetry_sdk::metrics::InMemoryMetricExporter;
+//!
+//! let exporter = InMemoryMetricExporter::default();
+//! // ... install a provider exporting to `exporter`, record and flush ...
+//!
+//! for rm in exporter.get_finished_metrics().unwrap() {
+//!     for (name, count) in series_counts(&rm) {
+//!         println!("{name}: {count} series");
+//!     }
+//! }
+//! ```
+
+use opentelemetry_sdk::metrics::data::{
+    Aggregation, ExponentialHistogram, Gauge, Histogram, ResourceMetrics, Sum,
+};
+use std::collections::BTreeMap;
+
+/// Counts the series of every metric in the collected metrics.
+///
+/// Metrics sharing a name across scopes are counted together, the way backends merge them.
+///
+/// # Parameters
+///
+/// * `rm` - The collected metrics
+///
+/// # Returns
+///
+/// The number of series of each metric, by metric name
+pub fn series_counts(rm: &ResourceMetrics) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+
+    for scope_metrics in &rm.scope_metrics {
+        for metric in &scope_metrics.metrics {
+            *counts.entry(metric.name.to_string()).or_insert(0) +=
+                data_points(metric.data.as_ref());
+        }
+    }
+
+    counts
+}
+
+/// Returns the number of data points of a metric.
+fn data_points(data: &dyn Aggregation) -> usize {
+    let data = data.as_any();
+
+    macro_rules! count {
+        ($($ty:ty),*) => {
+            $(
+                if let Some(agg) = data.downcast_ref::<$ty>() {
+                    return agg.data_points.len();
+                }
+            )*
+        };
+    }
+
+    count!(
+        Sum<u64>,
+        Sum<i64>,
+        Sum<f64>,
+        Gauge<u64>,
+        Gauge<i64>,
+        Gauge<f64>,
+        Histogram<u64>,
+        Histogram<f64>,
+        ExponentialHistogram<u64>,
+        ExponentialHistogram<f64>
+    );
+
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::MetricsConfig, test_util};
+    use opentelemetry::{KeyValue, metrics::MeterProvider};
+
+    #[test]
+    fn series_are_counted_per_attribute_set() {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        let meter = provider.meter("http");
+        let requests = meter.u64_counter("requests").build();
+        for method in ["GET", "POST", "PUT"] {
+            requests.add(1, &[KeyValue::new("method", method)]);
+        }
+        requests.add(1, &[KeyValue::new("method", "GET")]);
+        let latency = meter.f64_histogram("latency").build();
+        latency.record(1.0, &[]);
+        latency.record(2.0, &[KeyValue::new("route", "/")]);
+
+        let counts = series_counts(&test_util::collect(&reader));
+
+        assert_eq!(counts.get("requests"), Some(&3));
+        assert_eq!(counts.get("latency"), Some(&2));
+    }
+
+    #[test]
+    fn metrics_sharing_a_name_across_scopes_are_counted_together() {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        for scope in ["first", "second"] {
+            provider
+                .meter(scope)
+                .u64_counter("requests")
+                .build()
+                .add(1, &[KeyValue::new("scope", scope)]);
+        }
+
+        let counts = series_counts(&test_util::collect(&reader));
+
+        assert_eq!(counts, BTreeMap::from([("requests".to_string(), 2)]));
+    }
+}
@@ -0,0 +1,1393 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Metrics Configuration
+//!
+//! Defines the configuration consumed by the metrics exporters.
+//!
+//! The exporters do not read the Ruskit configuration system directly. Instead they receive a
+//! [`MetricsConfig`], which holds only the metrics-relevant settings. A `MetricsConfig` can be
+//! obtained in two ways:
+//!
+//! - [`MetricsConfig::load`] reads the ambient Ruskit configuration (`AppConfigs` and `OTLPConfigs`)
+//! - [`MetricsConfig::from_file`] deserializes a standalone TOML or YAML file
+//!
+//! The file based path decouples metrics from the ambient configuration environment, which is
+//! convenient for simple binaries and standalone tools.
+//!
+//! ## File Format
+//!
+//! Durations are expressed in seconds. Every field is optional and falls back to the
+//! [`Default`] value when omitted. The default export interval and timeout honor the
+//! `OTEL_METRIC_EXPORT_INTERVAL` and `OTEL_METRIC_EXPORT_TIMEOUT` variables of the
+//! OpenTelemetry specification, which are expressed in milliseconds.
+//!
+//! ```toml
+//! exporter = "otlp"
+//! fallback = ["otlp", "stdout", "noop"]
+//! service_name = "my-service"
+//! namespace = "payments"
+//! environment = "production"
+//! environment_key = "deployment.environment.name"
+//! endpoint = "http://localhost:4317"
+//! secondary_endpoints = ["http://collector-b:4317"]
+//! statsd_address = "127.0.0.1:8125"
+//! tls = true
+//! require_connection = false
+//! mirror_stdout = false
+//! mirror_prometheus = false
+//! scrape_read_timeout = 10
+//! scrape_write_timeout = 10
+//! scrape_max_connections = 64
+//! pipe_path = "/var/run/otel/metrics.fifo"
+//! user_agent = "my-service/1.2.0"
+//! authority = "collector.mesh.local"
+//! header_access_key = "x-api-key"
+//! access_key = "secret"
+//! header_value_file = "/var/run/secrets/otel/access-key"
+//! header_refresh_interval = 300
+//! service_type = "api"
+//! exporter_timeout = 30
+//! exporter_interval = 60
+//! align_to_wall_clock = false
+//! with_detectors = true
+//! resource_merge_policy = "config_over_env"
+//! non_finite = "reject"
+//! with_uptime = true
+//! disabled_instruments = ["histogram"]
+//! max_concurrent_exports = 1
+//! full_resource_every = 0
+//! collector_compat = false
+//! baggage_keys = ["tenant"]
+//! breaker_threshold = 5
+//! breaker_cooldown = 30
+//! reset_on_failure = true
+//! round_decimals = 2
+//! exponential_max_size = 160
+//! exponential_max_scale = 8
+//! gauges_on_change = false
+//! strict_validation = false
+//! self_metrics_prefix = "ruskit_metrics_"
+//! redacted_attributes = ["user.email"]
+//! redaction = "mask"
+//!
+//! [global_attributes]
+//! region = "us-east-1"
+//!
+//! [prometheus_summaries]
+//! "http.server.duration" = [0.5, 0.9, 0.99]
+//!
+//! [resource_keys]
+//! prometheus = ["service.name", "service.namespace"]
+//!
+//! [resource_exclude]
+//! prometheus = ["library.language", "environment"]
+//!
+//! [metric_aliases]
+//! "http.server.duration" = ["http.server.request.duration"]
+//!
+//! [resource_rename]
+//! environment = "env"
+//! "service.name" = "svc"
+//! ```
+
+#[cfg(feature = "otlp")]
+use crate::exporters::snapshot::OtlpSnapshot;
+use crate::{
+    LOG_TARGET,
+    errors::{ExportError, MetricsError},
+    exporters::checkpoint::Checkpoint,
+};
+use configs::{app::AppConfigs, otlp::OTLPConfigs};
+use opentelemetry::KeyValue;
+use opentelemetry_sdk::{
+    Resource,
+    metrics::{Aggregation, Instrument, InstrumentKind},
+    resource::ResourceDetector,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    collections::{BTreeMap, HashMap},
+    env, fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::{debug, error};
+
+/// Maximum duration of a single export when neither the configuration nor the environment
+/// sets one.
+const DEFAULT_EXPORTER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Interval between two periodic exports when neither the configuration nor the environment
+/// sets one.
+const DEFAULT_EXPORTER_INTERVAL: Duration = Duration::from_secs(60);
+
+/// # MetricsConfig
+///
+/// The metrics-relevant subset of the application configuration.
+///
+/// ## Fields
+///
+/// * `exporter` - The exporter requested at runtime, see [`ExporterKind`]. Installing fails
+///    when the requested exporter was not compiled in
+/// * `fallback` - Exporters tried in order at install, replacing `exporter` when not empty.
+///    An exporter is skipped when it was not compiled in or fails to install, including an
+///    unreachable OTLP collector, and every fallback is logged
+/// * `service_name` - Value of the `service.name` resource attribute. When empty, the
+///    `OTEL_SERVICE_NAME` variable or `unknown_service` is used instead
+/// * `namespace` - Value of the `service.namespace` resource attribute
+/// * `environment` - Value of the environment resource attribute
+/// * `environment_key` - Key of the environment resource attribute, `environment` by default.
+///    Set it to `deployment.environment.name` to follow the OpenTelemetry semantic conventions
+/// * `endpoint` - Address of the OpenTelemetry collector used by the OTLP exporter
+/// * `secondary_endpoints` - Addresses of additional collectors, each receiving the full metric
+///    stream alongside `endpoint`
+/// * `statsd_address` - Address of the StatsD server used by the StatsD exporter,
+///    `127.0.0.1:8125` by default
+/// * `mirror_stdout` - Whether the OTLP exporter also writes the metrics to standard output.
+///    Requires the `stdout` feature
+/// * `mirror_prometheus` - Whether the OTLP exporter also registers the Prometheus registry.
+///    Requires the `prometheus` feature
+/// * `scrape_read_timeout` - Maximum duration the scrape server started by `prom::serve` waits
+///    for the request of a client, in seconds, `10` by default. `0` disables the timeout
+/// * `scrape_write_timeout` - Maximum duration the scrape server takes to send a whole
+///    response, in seconds, `10` by default. `0` disables the timeout
+/// * `scrape_max_connections` - Maximum number of connections the scrape server serves at the
+///    same time, `64` by default. Connections beyond the limit are closed without a response.
+///    `0` disables the limit
+/// * `pipe_path` - Named pipe or file the stdout exporter writes OTLP JSON lines to instead of
+///    standard output, for a sidecar forwarding them. Requires the `stdout-pipe` feature
+/// * `tls` - Whether the OTLP exports use TLS. When unset, TLS is used for `https://` endpoints
+///    only
+/// * `require_connection` - Whether installing the OTLP exporter fails when a collector cannot
+///    be reached. Exporters connect lazily when unset, the default
+/// * `user_agent` - Value prepended to the `user-agent` header of the OTLP exports, typically
+///    the service name and version. The default user agent is sent when empty
+/// * `authority` - Authority of the OTLP export requests and name the TLS certificate of the
+///    collector is verified against, when it differs from the endpoint host such as behind a
+///    mesh proxy. The endpoint host is used when empty
+/// * `header_access_key` - Name of the metadata header carrying `access_key` on every OTLP
+///    export. No header is sent when either value is empty
+/// * `access_key` - Access key authenticating the OTLP exports with the collector
+/// * `header_value_file` - File holding the value of the `header_access_key` header, used
+///    instead of `access_key` for secrets mounted as files. Installing fails when it cannot be
+///    read
+/// * `header_refresh_interval` - Interval after which `header_value_file` is read again, in
+///    seconds, so rotated secrets are picked up. `0`, the default, reads it only at install
+/// * `service_type` - Value of the `service.type` resource attribute, omitted when empty
+/// * `exporter_timeout` - Maximum duration of a single export, in seconds. Defaults to the
+///    `OTEL_METRIC_EXPORT_TIMEOUT` variable, in milliseconds, or 30 seconds
+/// * `exporter_interval` - Interval between two periodic exports, in seconds. Defaults to the
+///    `OTEL_METRIC_EXPORT_INTERVAL` variable, in milliseconds, or 60 seconds
+/// * `align_to_wall_clock` - Whether the first periodic export is delayed so every export lands
+///    on a multiple of `exporter_interval` since the Unix epoch, lining up the export windows of
+///    all the services using the same interval
+/// * `with_detectors` - Whether the standard resource detectors (host, operating system,
+///    process and Kubernetes) run at install and contribute resource attributes
+/// * `resource_merge_policy` - Which source wins when several provide the same resource
+///    attribute, see [`ResourceMergePolicy`]
+/// * `non_finite` - How the helper instruments handle `NaN` and infinite values, see
+///    [`NonFinitePolicy`]
+/// * `histogram_min_max` - Whether the exported explicit bucket histograms carry the minimum
+///    and maximum value of every collection window, `true` by default
+/// * `disabled_instruments` - Instrument classes whose data is dropped instead of exported, see
+///    [`InstrumentClass`]
+/// * `with_uptime` - Whether a `process_uptime_seconds` gauge reporting the seconds since
+///    install is registered
+/// * `max_concurrent_exports` - Maximum number of OTLP exports in flight at the same time
+/// * `full_resource_every` - Every how many OTLP exports the full resource is sent, the other
+///    exports only carrying the attributes identifying the service. Saves bandwidth on
+///    constrained links, at the risk of data without the full resource if the full export is
+///    lost. `0`, the default, and `1` send it with every export
+/// * `collector_compat` - Whether the OTLP exports are restricted to what older collectors
+///    accept: explicit bucket histograms and the full resource on every export. See the
+///    `otlp_grpc` module for the exact changes
+/// * `breaker_threshold` - Number of consecutive failed OTLP exports opening the circuit
+///    breaker, `0` disables the breaker
+/// * `breaker_cooldown` - Duration the circuit breaker stays open before probing, in seconds
+/// * `reset_on_failure` - Whether the data of a failed delta export is discarded, `true` by
+///    default. When `false`, the StatsD packets that could not be sent are sent again with the
+///    next export. Cumulative exporters are not affected, see the `statsd` module
+/// * `baggage_keys` - Baggage entries attached as attributes by
+///    [`with_baggage`](crate::baggage::with_baggage)
+/// * `exponential_max_size` - Maximum number of buckets of the exponential histograms chosen by
+///    the `aggregation_selector`. Unset by default, keeping the selected value
+/// * `exponential_max_scale` - Maximum scale of the exponential histograms chosen by the
+///    `aggregation_selector`, for backends rejecting high resolutions. Clamped to -10 to 20
+/// * `round_decimals` - Number of decimals the floating-point sum and gauge values are rounded
+///    to before export. Unset by default, exporting the values unchanged
+/// * `gauges_on_change` - Whether gauge series are only pushed when their value changed since
+///    the previous export. Backends treating a missing series as a reset may misreport them, so
+///    it is disabled by default. Prometheus scrapes always contain every series
+/// * `strict_validation` - Whether [`validate_instruments`](crate::validate_instruments) fails
+///    on invalid instruments instead of only logging them, for CI gates
+/// * `self_metrics_prefix` - Prefix of the metrics the crate reports about its own exports with
+///    the `self-metrics` feature, `ruskit_metrics_` by default. May be empty
+/// * `redacted_attributes` - Data point attribute keys whose values are redacted before export,
+///    for values that must not leave the process such as user emails used as attributes
+/// * `redaction` - How the values of `redacted_attributes` are redacted, see [`Redaction`]
+/// * `global_attributes` - Attributes added to every exported data point. Unlike resource
+///    attributes, they are metric attributes and can be used by query layers filtering on data
+///    point attributes only
+/// * `prometheus_summaries` - Histograms the Prometheus exporter renders as summaries, with the
+///    quantiles to report for each of them
+/// * `resource_keys` - Per exporter, the only resource attributes passed to it. Exporters not
+///    listed receive the full resource, which lets a mirror expose a smaller resource than the
+///    collector receives
+/// * `resource_exclude` - Per exporter, resource attributes removed from the resource passed to
+///    it, for example to keep noisy attributes out of the Prometheus `target_info` labels
+/// * `metric_aliases` - Additional names each instrument is exported under, from the instrument
+///    name to its aliases, for renaming metrics without breaking dashboards
+/// * `resource_rename` - Resource attribute keys renamed before export, from the original key
+///    to the exported one. Keys missing from the map are exported unchanged
+/// * `detectors` - Additional resource detectors run at install. This field can only be set
+///    from code
+/// * `name_transform` - Optional hook applied to every instrument name before export. This
+///    field can only be set from code and is never read from a configuration file
+/// * `aggregation_selector` - Optional hook choosing the default aggregation of each instrument
+///    kind, for example exponential histograms for every histogram. This field can only be set
+///    from code
+/// * `instrument_catalog` - Optional handle collecting the kind, unit and description of every
+///    instrument registered on the provider, see [`InstrumentCatalog`]. This field can only be
+///    set from code
+/// * `export_health` - Optional handle publishing the export health, such as the circuit
+///    breaker state. This field can only be set from code
+/// * `otlp_snapshot` - Optional handle encoding the current metrics to OTLP protobuf bytes on
+///    demand, see [`OtlpSnapshot`](crate::exporters::snapshot::OtlpSnapshot). Requires the
+///    `otlp` feature and can only be set from code
+/// * `checkpoint` - Optional handle collecting the measurements recorded since the previous
+///    checkpoint, see [`Checkpoint`]. This field can only be set from code
+/// * `on_export_error` - Optional hook called with a typed [`ExportError`] every time an export
+///    to the collector fails. This field can only be set from code
+/// * `external_producer` - Optional [`ExternalProducer`] whose points are merged into every
+///    export. This field can only be set from code
+///
+/// ## Example
+///
+/// ```
+/// use metrics::config::MetricsConfig;
+/// use std::time::Duration;
+///
+/// let cfg = MetricsConfig {
+///     service_name: "my-service".to_string(),
+///     exporter_interval: Duration::from_secs(10),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    pub exporter: Option<ExporterKind>,
+    pub fallback: Vec<ExporterKind>,
+    pub service_name: String,
+    pub namespace: String,
+    pub environment: String,
+    pub environment_key: String,
+    pub endpoint: String,
+    pub secondary_endpoints: Vec<String>,
+    pub statsd_address: String,
+    pub mirror_stdout: bool,
+    pub mirror_prometheus: bool,
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub scrape_read_timeout: Duration,
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub scrape_write_timeout: Duration,
+    pub scrape_max_connections: usize,
+    pub pipe_path: Option<PathBuf>,
+    pub tls: Option<bool>,
+    pub require_connection: bool,
+    pub user_agent: String,
+    pub authority: String,
+    pub header_access_key: String,
+    pub access_key: String,
+    pub header_value_file: Option<PathBuf>,
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub header_refresh_interval: Duration,
+    pub service_type: String,
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub exporter_timeout: Duration,
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub exporter_interval: Duration,
+    pub align_to_wall_clock: bool,
+    pub with_detectors: bool,
+    pub resource_merge_policy: ResourceMergePolicy,
+    pub non_finite: NonFinitePolicy,
+    pub histogram_min_max: bool,
+    pub disabled_instruments: Vec<InstrumentClass>,
+    pub with_uptime: bool,
+    pub max_concurrent_exports: usize,
+    pub full_resource_every: u32,
+    pub collector_compat: bool,
+    pub breaker_threshold: u32,
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub breaker_cooldown: Duration,
+    pub reset_on_failure: bool,
+    pub baggage_keys: Vec<String>,
+    pub exponential_max_size: Option<u32>,
+    pub exponential_max_scale: Option<i8>,
+    pub round_decimals: Option<u32>,
+    pub gauges_on_change: bool,
+    pub strict_validation: bool,
+    pub self_metrics_prefix: String,
+    pub redacted_attributes: Vec<String>,
+    pub redaction: Redaction,
+    pub global_attributes: BTreeMap<String, String>,
+    pub prometheus_summaries: BTreeMap<String, Vec<f64>>,
+    pub resource_keys: BTreeMap<ExporterKind, Vec<String>>,
+    pub resource_exclude: BTreeMap<ExporterKind, Vec<String>>,
+    pub metric_aliases: BTreeMap<String, Vec<String>>,
+    pub resource_rename: BTreeMap<String, String>,
+    #[serde(skip)]
+    pub detectors: Vec<Detector>,
+    #[serde(skip)]
+    pub name_transform: Option<NameTransform>,
+    #[serde(skip)]
+    pub aggregation_selector: Option<AggregationSelector>,
+    #[serde(skip)]
+    pub instrument_catalog: Option<InstrumentCatalog>,
+    #[serde(skip)]
+    pub export_health: Option<ExportHealth>,
+    #[cfg(feature = "otlp")]
+    #[serde(skip)]
+    pub otlp_snapshot: Option<OtlpSnapshot>,
+    #[serde(skip)]
+    pub checkpoint: Option<Checkpoint>,
+    #[serde(skip)]
+    pub on_export_error: Option<ExportErrorHook>,
+    #[serde(skip)]
+    pub external_producer: Option<ExternalProducer>,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            exporter: None,
+            fallback: Vec::new(),
+            service_name: String::new(),
+            namespace: String::new(),
+            environment: String::new(),
+            environment_key: "environment".to_string(),
+            endpoint: "http://localhost:4317".to_string(),
+            secondary_endpoints: Vec::new(),
+            statsd_address: "127.0.0.1:8125".to_string(),
+            mirror_stdout: false,
+            mirror_prometheus: false,
+            scrape_read_timeout: Duration::from_secs(10),
+            scrape_write_timeout: Duration::from_secs(10),
+            scrape_max_connections: 64,
+            pipe_path: None,
+            tls: None,
+            require_connection: false,
+            user_agent: String::new(),
+            authority: String::new(),
+            header_access_key: String::new(),
+            access_key: String::new(),
+            header_value_file: None,
+            header_refresh_interval: Duration::ZERO,
+            service_type: String::new(),
+            exporter_timeout: env_millis("OTEL_METRIC_EXPORT_TIMEOUT")
+                .unwrap_or(DEFAULT_EXPORTER_TIMEOUT),
+            exporter_interval: env_millis("OTEL_METRIC_EXPORT_INTERVAL")
+                .unwrap_or(DEFAULT_EXPORTER_INTERVAL),
+            align_to_wall_clock: false,
+            with_detectors: false,
+            resource_merge_policy: ResourceMergePolicy::default(),
+            non_finite: NonFinitePolicy::default(),
+            histogram_min_max: true,
+            disabled_instruments: Vec::new(),
+            with_uptime: false,
+            max_concurrent_exports: 1,
+            full_resource_every: 0,
+            collector_compat: false,
+            breaker_threshold: 0,
+            breaker_cooldown: Duration::from_secs(30),
+            reset_on_failure: true,
+            baggage_keys: Vec::new(),
+            exponential_max_size: None,
+            exponential_max_scale: None,
+            round_decimals: None,
+            gauges_on_change: false,
+            strict_validation: false,
+            self_metrics_prefix: "ruskit_metrics_".to_string(),
+            redacted_attributes: Vec::new(),
+            redaction: Redaction::default(),
+            global_attributes: BTreeMap::new(),
+            prometheus_summaries: BTreeMap::new(),
+            resource_keys: BTreeMap::new(),
+            resource_exclude: BTreeMap::new(),
+            metric_aliases: BTreeMap::new(),
+            resource_rename: BTreeMap::new(),
+            detectors: Vec::new(),
+            name_transform: None,
+            aggregation_selector: None,
+            instrument_catalog: None,
+            export_health: None,
+            #[cfg(feature = "otlp")]
+            otlp_snapshot: None,
+            checkpoint: None,
+            on_export_error: None,
+            external_producer: None,
+        }
+    }
+}
+
+impl MetricsConfig {
+    /// Loads the configuration from the ambient Ruskit configuration system.
+    ///
+    /// The requested exporter is read from the `METRIC_EXPORTER` environment variable, and is
+    /// left unset when the variable is missing or holds an unknown value.
+    ///
+    /// The collector credentials and the service type are read from `METRIC_HEADER_ACCESS_KEY`,
+    /// `METRIC_ACCESS_KEY` and `METRIC_SERVICE_TYPE`. Each one falls back to its `TRACE_`
+    /// counterpart when unset, so deployments sharing a single collector with the traces keep
+    /// working without duplicating the credentials.
+    ///
+    /// The export timeout and interval come from `OTLPConfigs` when set there. A zero duration
+    /// counts as unset and falls back to the `OTEL_METRIC_EXPORT_TIMEOUT` and
+    /// `OTEL_METRIC_EXPORT_INTERVAL` variables, then to the defaults.
+    ///
+    /// # Returns
+    ///
+    /// A `MetricsConfig` populated from `AppConfigs` and `OTLPConfigs`
+    pub fn load() -> Self {
+        let app_cfgs = AppConfigs::new();
+        let otlp_cfgs = OTLPConfigs::new();
+
+        let exporter = env::var("METRIC_EXPORTER")
+            .ok()
+            .and_then(|value| value.parse().ok());
+
+        Self {
+            exporter,
+            service_name: app_cfgs.name.to_string(),
+            namespace: format!("{}", app_cfgs.namespace),
+            environment: format!("{}", app_cfgs.env),
+            endpoint: otlp_cfgs.endpoint.clone(),
+            header_access_key: metric_or_trace_var("HEADER_ACCESS_KEY"),
+            access_key: metric_or_trace_var("ACCESS_KEY"),
+            service_type: metric_or_trace_var("SERVICE_TYPE"),
+            exporter_timeout: configured_or_env(
+                otlp_cfgs.exporter_timeout,
+                "OTEL_METRIC_EXPORT_TIMEOUT",
+                DEFAULT_EXPORTER_TIMEOUT,
+            ),
+            exporter_interval: configured_or_env(
+                otlp_cfgs.exporter_interval,
+                "OTEL_METRIC_EXPORT_INTERVAL",
+                DEFAULT_EXPORTER_INTERVAL,
+            ),
+            ..Default::default()
+        }
+    }
+
+    /// Reads the configuration from a TOML or YAML file.
+    ///
+    /// The format is selected by the file extension: `.toml` for TOML and `.yaml` or `.yml`
+    /// for YAML.
+    ///
+    /// # Parameters
+    ///
+    /// * `path` - Path of the configuration file
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MetricsConfig)` - The deserialized configuration
+    /// * `Err(MetricsError::InvalidConfigError)` - If the file could not be read, has an unsupported
+    ///   extension or could not be deserialized
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, MetricsError> {
+        let path = path.as_ref();
+
+        let content = match fs::read_to_string(path) {
+            Ok(c) => Ok(c),
+            Err(err) => {
+                error!(
+                    target: LOG_TARGET,
+                    error = err.to_string(),
+                    path = %path.display(),
+                    "failure to read metrics config file"
+                );
+                Err(MetricsError::InvalidConfigError(format!(
+                    "unable to read {}",
+                    path.display()
+                )))
+            }
+        }?;
+
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default();
+
+        let parsed = match extension {
+            "toml" => toml::from_str::<Self>(&content).map_err(|e| e.to_string()),
+            "yaml" | "yml" => serde_yaml::from_str::<Self>(&content).map_err(|e| e.to_string()),
+            _ => {
+                return Err(MetricsError::InvalidConfigError(format!(
+                    "unsupported config file extension: {}",
+                    path.display()
+                )));
+            }
+        };
+
+        match parsed {
+            Ok(cfg) => Ok(cfg),
+            Err(err) => {
+                error!(
+                    target: LOG_TARGET,
+                    error = %err,
+                    path = %path.display(),
+                    "failure to parse metrics config file"
+                );
+                Err(MetricsError::InvalidConfigError(format!(
+                    "unable to parse {}",
+                    path.display()
+                )))
+            }
+        }
+    }
+
+    /// Returns whether the OTLP exports to `endpoint` use TLS.
+    ///
+    /// `tls` takes precedence, otherwise TLS is used for `https://` endpoints.
+    pub(crate) fn uses_tls(&self, endpoint: &str) -> bool {
+        self.tls.unwrap_or_else(|| endpoint.starts_with("https://"))
+    }
+}
+
+/// Returns the configured duration, or the environment variable in milliseconds when it is
+/// zero, or the default.
+fn configured_or_env(configured: Duration, name: &str, default: Duration) -> Duration {
+    if !configured.is_zero() {
+        return configured;
+    }
+
+    env_millis(name).unwrap_or(default)
+}
+
+/// Reads a duration in milliseconds from an environment variable, ignoring invalid values.
+fn env_millis(name: &str) -> Option<Duration> {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_millis)
+}
+
+/// Reads `METRIC_<name>`, falling back to `TRACE_<name>` when it is unset or empty.
+fn metric_or_trace_var(name: &str) -> String {
+    [format!("METRIC_{name}"), format!("TRACE_{name}")]
+        .iter()
+        .filter_map(|key| env::var(key).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default()
+}
+
+/// # ExporterKind
+///
+/// The exporters that can be requested from the configuration, or targeted by its per-exporter
+/// settings such as `resource_keys`.
+///
+/// ## Variants
+///
+/// * `Otlp` - The OTLP exporter, requires the `otlp` feature
+/// * `Prometheus` - The Prometheus exporter, requires the `prometheus` feature
+/// * `Statsd` - The StatsD exporter, requires the `statsd` feature
+/// * `Stdout` - The stdout exporter, requires the `stdout` feature
+/// * `CustomPush` - The custom push exporter, requires the `custom-push` feature. It needs the
+///   serializer of the application, so it is installed with `custom_push::install` and can not
+///   be requested from the configuration
+/// * `None` - The no-op exporter, always available
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExporterKind {
+    Otlp,
+    Prometheus,
+    Statsd,
+    Stdout,
+    #[serde(rename = "custom_push")]
+    CustomPush,
+    #[serde(alias = "noop")]
+    None,
+}
+
+impl ExporterKind {
+    /// Returns the Cargo feature the exporter requires, if any.
+    pub const fn feature(&self) -> Option<&'static str> {
+        match self {
+            ExporterKind::Otlp => Some("otlp"),
+            ExporterKind::Prometheus => Some("prometheus"),
+            ExporterKind::Statsd => Some("statsd"),
+            ExporterKind::Stdout => Some("stdout"),
+            ExporterKind::CustomPush => Some("custom-push"),
+            ExporterKind::None => None,
+        }
+    }
+
+    /// Returns whether the exporter was compiled into this build.
+    pub const fn is_compiled(&self) -> bool {
+        match self {
+            ExporterKind::Otlp => cfg!(feature = "otlp"),
+            ExporterKind::Prometheus => cfg!(feature = "prometheus"),
+            ExporterKind::Statsd => cfg!(feature = "statsd"),
+            ExporterKind::Stdout => cfg!(feature = "stdout"),
+            ExporterKind::CustomPush => cfg!(feature = "custom-push"),
+            ExporterKind::None => true,
+        }
+    }
+}
+
+impl FromStr for ExporterKind {
+    type Err = MetricsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "otlp" | "otlpgrpc" | "otlp_grpc" => Ok(ExporterKind::Otlp),
+            "prometheus" => Ok(ExporterKind::Prometheus),
+            "statsd" => Ok(ExporterKind::Statsd),
+            "stdout" => Ok(ExporterKind::Stdout),
+            "none" | "noop" => Ok(ExporterKind::None),
+            _ => Err(MetricsError::InvalidConfigError(format!(
+                "unknown metric exporter: {s}"
+            ))),
+        }
+    }
+}
+
+/// # InstrumentClass
+///
+/// A class of instruments, covering both the synchronous and the observable kinds.
+///
+/// ## Variants
+///
+/// * `Counter` - Counters and observable counters
+/// * `UpDownCounter` - Up-down counters and observable up-down counters
+/// * `Histogram` - Histograms
+/// * `Gauge` - Gauges and observable gauges
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstrumentClass {
+    Counter,
+    UpDownCounter,
+    Histogram,
+    Gauge,
+}
+
+impl InstrumentClass {
+    /// Returns whether the instrument kind belongs to this class.
+    pub fn matches(&self, kind: InstrumentKind) -> bool {
+        match self {
+            InstrumentClass::Counter => matches!(
+                kind,
+                InstrumentKind::Counter | InstrumentKind::ObservableCounter
+            ),
+            InstrumentClass::UpDownCounter => matches!(
+                kind,
+                InstrumentKind::UpDownCounter | InstrumentKind::ObservableUpDownCounter
+            ),
+            InstrumentClass::Histogram => kind == InstrumentKind::Histogram,
+            InstrumentClass::Gauge => matches!(
+                kind,
+                InstrumentKind::Gauge | InstrumentKind::ObservableGauge
+            ),
+        }
+    }
+}
+
+/// # ResourceMergePolicy
+///
+/// The precedence of the resource attribute sources when they provide the same key.
+///
+/// ## Variants
+///
+/// * `ConfigOverEnv` - The configuration wins over the custom detectors, which win over the
+///   standard detectors, which win over the `OTEL_RESOURCE_ATTRIBUTES` and `OTEL_SERVICE_NAME`
+///   variables. This is the default
+/// * `EnvOverConfig` - The reverse order: the environment variables win over the standard
+///   detectors, then the custom detectors, then the configuration
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceMergePolicy {
+    #[default]
+    ConfigOverEnv,
+    EnvOverConfig,
+}
+
+/// # NonFinitePolicy
+///
+/// How the helper instruments handle `NaN` and infinite values before recording them.
+///
+/// Some backends reject a batch containing a non-finite value and others silently drop it, so a
+/// single bad measurement can cost a whole export. The policy is applied at recording time by
+/// the helpers of this crate, see [`non_finite`](crate::non_finite).
+///
+/// ## Variants
+///
+/// * `Pass` - Record the value as is. This is the default
+/// * `Reject` - Log a warning and skip the recording
+/// * `Zero` - Record `0` instead
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NonFinitePolicy {
+    #[default]
+    Pass,
+    Reject,
+    Zero,
+}
+
+/// # Redaction
+///
+/// How the values of the attributes listed in `MetricsConfig::redacted_attributes` are
+/// redacted before export.
+///
+/// ## Variants
+///
+/// * `Mask` - Replace the value with `[redacted]`. This is the default. Series differing only
+///   by a redacted value are merged into a single data point: sums are added, histograms
+///   merged and gauges keep the last value
+/// * `Hash` - Replace the value with a 64-bit FNV-1a hash, in hexadecimal, which keeps the
+///   series apart and stays stable across restarts. The hash is not salted, so values from a
+///   small set, such as known emails, can be recovered by hashing the candidates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Redaction {
+    #[default]
+    Mask,
+    Hash,
+}
+
+/// # Detector
+///
+/// A custom resource detector run when the provider is installed.
+///
+/// Detected attributes are merged into the resource. With the default
+/// [`ResourceMergePolicy`], the attributes from the configuration (service name, namespace,
+/// environment) take precedence over detected ones.
+///
+/// ## Example
+///
+/// ```
+/// use metrics::config::{Detector, MetricsConfig};
+/// use opentelemetry::KeyValue;
+/// use opentelemetry_sdk::{Resource, resource::ResourceDetector};
+///
+/// struct RegionDetector;
+///
+/// impl ResourceDetector for RegionDetector {
+///     fn detect(&self) -> Resource {
+///         Resource::builder_empty()
+///             .with_attribute(KeyValue::new("cloud.region", "us-east-1"))
+///             .build()
+///     }
+/// }
+///
+/// let cfg = MetricsConfig {
+///     detectors: vec![Detector::new(RegionDetector)],
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone)]
+pub struct Detector(Arc<dyn ResourceDetector + Send + Sync>);
+
+impl Detector {
+    /// Creates a new detector wrapping the given resource detector.
+    pub fn new<D>(detector: D) -> Self
+    where
+        D: ResourceDetector + Send + Sync + 'static,
+    {
+        Self(Arc::new(detector))
+    }
+
+    /// Runs the detector.
+    pub fn detect(&self) -> Resource {
+        self.0.detect()
+    }
+}
+
+impl fmt::Debug for Detector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Detector")
+    }
+}
+
+impl PartialEq for Detector {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// # NameTransform
+///
+/// A hook that rewrites instrument names before they are exported.
+///
+/// Different backends follow different naming conventions (dots versus underscores, mandatory
+/// prefixes, ...). A `NameTransform` gives full control over the exported name without touching
+/// the instrumentation code. The transform is applied through a view, so it affects every
+/// exporter attached to the provider. The Prometheus exporter sanitizes names to its character
+/// set by default; a configured transform replaces that sanitization, and can call
+/// `exporters::prom::sanitize` to keep the names valid.
+///
+/// ## Example
+///
+/// ```
+/// use metrics::config::{MetricsConfig, NameTransform};
+///
+/// let cfg = MetricsConfig {
+///     name_transform: Some(NameTransform::new(|name| format!("myapp.{name}"))),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone)]
+pub struct NameTransform(Arc<dyn Fn(&str) -> String + Send + Sync>);
+
+impl NameTransform {
+    /// Creates a new name transform from the given function.
+    pub fn new<F>(transform: F) -> Self
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        Self(Arc::new(transform))
+    }
+
+    /// Applies the transform to an instrument name.
+    pub fn apply(&self, name: &str) -> String {
+        (self.0)(name)
+    }
+}
+
+impl fmt::Debug for NameTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("NameTransform")
+    }
+}
+
+impl PartialEq for NameTransform {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// # AggregationSelector
+///
+/// A hook selecting the default aggregation used for each instrument kind.
+///
+/// The selector is applied through the same view as the [`NameTransform`], so it affects every
+/// exporter attached to the provider. Returning `None` keeps the SDK default aggregation for
+/// that kind. Gauges always keep the last value aggregation and are not passed to the selector.
+///
+/// ## Example
+///
+/// ```
+/// use metrics::config::{AggregationSelector, MetricsConfig};
+/// use opentelemetry_sdk::metrics::{Aggregation, InstrumentKind};
+///
+/// let cfg = MetricsConfig {
+///     aggregation_selector: Some(AggregationSelector::new(|kind| match kind {
+///         InstrumentKind::Histogram => Some(Aggregation::Base2ExponentialHistogram {
+///             max_size: 160,
+///             max_scale: 20,
+///             record_min_max: true,
+///         }),
+///         _ => None,
+///     })),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone)]
+pub struct AggregationSelector(Arc<dyn Fn(InstrumentKind) -> Option<Aggregation> + Send + Sync>);
+
+impl AggregationSelector {
+    /// Creates a new aggregation selector from the given function.
+    pub fn new<F>(selector: F) -> Self
+    where
+        F: Fn(InstrumentKind) -> Option<Aggregation> + Send + Sync + 'static,
+    {
+        Self(Arc::new(selector))
+    }
+
+    /// Selects the aggregation of an instrument kind.
+    pub fn select(&self, kind: InstrumentKind) -> Option<Aggregation> {
+        (self.0)(kind)
+    }
+}
+
+impl fmt::Debug for AggregationSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AggregationSelector")
+    }
+}
+
+impl PartialEq for AggregationSelector {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// # ExportErrorHook
+///
+/// A callback invoked with the classified error of every failed export.
+///
+/// The hook runs on the exporter's thread right after the failure, so it should return quickly.
+/// Only the OTLP exporter reports export errors; the stdout and no-op exporters never call it.
+///
+/// ## Example
+///
+/// ```
+/// use metrics::{config::{ExportErrorHook, MetricsConfig}, errors::ExportError};
+///
+/// let cfg = MetricsConfig {
+///     on_export_error: Some(ExportErrorHook::new(|err| {
+///         if *err == ExportError::ConnectionRefused {
+///             eprintln!("metrics collector unreachable");
+///         }
+///     })),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone)]
+pub struct ExportErrorHook(Arc<dyn Fn(&ExportError) + Send + Sync>);
+
+impl ExportErrorHook {
+    /// Creates a new export error hook from the given function.
+    pub fn new<F>(hook: F) -> Self
+    where
+        F: Fn(&ExportError) + Send + Sync + 'static,
+    {
+        Self(Arc::new(hook))
+    }
+
+    /// Calls the hook with the given error.
+    pub fn call(&self, err: &ExportError) {
+        (self.0)(err)
+    }
+}
+
+impl fmt::Debug for ExportErrorHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ExportErrorHook")
+    }
+}
+
+impl PartialEq for ExportErrorHook {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// # ExternalPoint
+///
+/// A data point produced outside of OpenTelemetry, merged into the exports by an
+/// [`ExternalProducer`].
+///
+/// ## Fields
+///
+/// * `name` - Name of the metric
+/// * `attributes` - Attributes of the series
+/// * `value` - Value of the point. Counters report the total since the process started
+/// * `kind` - How the value is exported
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalPoint {
+    pub name: String,
+    pub attributes: Vec<KeyValue>,
+    pub value: f64,
+    pub kind: ExternalKind,
+}
+
+/// # ExternalKind
+///
+/// How an [`ExternalPoint`] is exported.
+///
+/// ## Variants
+///
+/// * `Counter` - A monotonic cumulative sum
+/// * `Gauge` - The last value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalKind {
+    Counter,
+    Gauge,
+}
+
+/// # ExternalProducer
+///
+/// A callback producing data points that are merged into every export.
+///
+/// Subsystems keeping their own counters, such as a legacy counter map, can be exported with
+/// the OpenTelemetry instruments without porting them. The producer is called once per export
+/// of every exporter, and its points are exported under the `external` instrumentation scope,
+/// with the configured transforms applied. Points sharing a name and kind form one metric.
+///
+/// ## Example
+///
+/// ```
+/// use metrics::config::{ExternalKind, ExternalPoint, ExternalProducer, MetricsConfig};
+///
+/// let cfg = MetricsConfig {
+///     external_producer: Some(ExternalProducer::new(|| {
+///         vec![ExternalPoint {
+///             name: "legacy.jobs".to_string(),
+///             attributes: Vec::new(),
+///             value: 42.0,
+///             kind: ExternalKind::Counter,
+///         }]
+///     })),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone)]
+pub struct ExternalProducer(Arc<dyn Fn() -> Vec<ExternalPoint> + Send + Sync>);
+
+impl ExternalProducer {
+    /// Creates a new external producer from the given function.
+    pub fn new<F>(producer: F) -> Self
+    where
+        F: Fn() -> Vec<ExternalPoint> + Send + Sync + 'static,
+    {
+        Self(Arc::new(producer))
+    }
+
+    /// Calls the producer.
+    pub fn produce(&self) -> Vec<ExternalPoint> {
+        (self.0)()
+    }
+}
+
+impl fmt::Debug for ExternalProducer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ExternalProducer")
+    }
+}
+
+impl PartialEq for ExternalProducer {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// # BreakerState
+///
+/// State of the export circuit breaker of a collector endpoint.
+///
+/// ## Variants
+///
+/// * `Closed` - Exports are sent normally
+/// * `Open` - Exports are skipped until the cooldown elapses
+/// * `HalfOpen` - The cooldown elapsed and the next export probes the collector
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+/// # ExportHealth
+///
+/// A shared handle exposing the health of the exports.
+///
+/// The application keeps a clone of the handle and passes another one in the configuration.
+/// The exporters update it as exports succeed or fail.
+///
+/// ## Example
+///
+/// ```
+/// use metrics::config::{BreakerState, ExportHealth, MetricsConfig};
+///
+/// let health = ExportHealth::default();
+/// let cfg = MetricsConfig {
+///     breaker_threshold: 5,
+///     export_health: Some(health.clone()),
+///     ..Default::default()
+/// };
+///
+/// let degraded = health
+///     .breaker_states()
+///     .iter()
+///     .any(|(_, state)| *state != BreakerState::Closed);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ExportHealth(Arc<Mutex<HashMap<String, BreakerState>>>);
+
+impl ExportHealth {
+    /// Returns the circuit breaker state of the given endpoint, if it has a breaker.
+    pub fn breaker_state(&self, endpoint: &str) -> Option<BreakerState> {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(endpoint)
+            .copied()
+    }
+
+    /// Returns the circuit breaker state of every endpoint.
+    pub fn breaker_states(&self) -> Vec<(String, BreakerState)> {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(endpoint, state)| (endpoint.clone(), *state))
+            .collect()
+    }
+
+    pub(crate) fn set_breaker_state(&self, endpoint: &str, state: BreakerState) {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(endpoint.to_string(), state);
+    }
+}
+
+impl PartialEq for ExportHealth {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// # InstrumentCatalog
+///
+/// A shared handle collecting the metadata of every instrument registered on the provider.
+///
+/// Instruments are added to the catalog as they are created, and each addition is logged with
+/// its metadata as structured fields. The application keeps a clone of the handle and reads the
+/// full catalog with [`InstrumentCatalog::entries`], for example to publish it to a central
+/// metrics registry at startup.
+///
+/// ## Example
+///
+/// ```
+/// use metrics::config::{InstrumentCatalog, MetricsConfig};
+///
+/// let catalog = InstrumentCatalog::default();
+/// let cfg = MetricsConfig {
+///     instrument_catalog: Some(catalog.clone()),
+///     ..Default::default()
+/// };
+///
+/// // after the install and the creation of the instruments
+/// for entry in catalog.entries() {
+///     println!("{} {:?} {}", entry.name, entry.kind, entry.unit);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentCatalog(Arc<Mutex<BTreeMap<(String, String), InstrumentInfo>>>);
+
+/// # InstrumentInfo
+///
+/// The metadata of an instrument registered on the provider.
+///
+/// ## Fields
+///
+/// * `scope` - Name of the instrumentation scope that created the instrument
+/// * `name` - Name of the instrument, before any name transform
+/// * `kind` - Kind of the instrument
+/// * `unit` - Unit of the instrument, empty when not set
+/// * `description` - Description of the instrument, empty when not set
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstrumentInfo {
+    pub scope: String,
+    pub name: String,
+    pub kind: Option<InstrumentKind>,
+    pub unit: String,
+    pub description: String,
+}
+
+impl InstrumentCatalog {
+    /// Returns the registered instruments, ordered by scope and name.
+    pub fn entries(&self) -> Vec<InstrumentInfo> {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn register(&self, instrument: &Instrument) {
+        let info = InstrumentInfo {
+            scope: instrument.scope.name().to_string(),
+            name: instrument.name.to_string(),
+            kind: instrument.kind,
+            unit: instrument.unit.to_string(),
+            description: instrument.description.to_string(),
+        };
+
+        let mut entries = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let key = (info.scope.clone(), info.name.clone());
+        if entries.contains_key(&key) {
+            return;
+        }
+
+        debug!(
+            target: LOG_TARGET,
+            scope = %info.scope,
+            name = %info.name,
+            kind = ?info.kind,
+            unit = %info.unit,
+            description = %info.description,
+            "metrics::catalog instrument registered"
+        );
+        entries.insert(key, info);
+    }
+}
+
+impl PartialEq for InstrumentCatalog {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// # EffectiveConfig
+///
+/// The settings in effect for a configuration, once defaults, environment variables and
+/// feature selection are resolved.
+///
+/// Returned by [`effective_config`](crate::provider::effective_config). It can be serialized,
+/// for example to log it as JSON or expose it on a debug endpoint.
+///
+/// ## Fields
+///
+/// * `exporter` - The exporter installed for the configuration
+/// * `endpoints` - The destinations of the exporter, empty for exporters without one
+/// * `interval` - Interval between two periodic exports, serialized in seconds
+/// * `timeout` - Maximum duration of a single export, serialized in seconds
+/// * `temporality` - The temporality of the exported sums and histograms, `cumulative` or
+///    `delta`, or `None` for the no-op exporter
+/// * `compression` - The compression of the exports, if any
+/// * `resource` - The resource attributes attached to the exported metrics
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EffectiveConfig {
+    pub exporter: ExporterKind,
+    pub endpoints: Vec<EffectiveEndpoint>,
+    #[serde(serialize_with = "serialize_seconds")]
+    pub interval: Duration,
+    #[serde(serialize_with = "serialize_seconds")]
+    pub timeout: Duration,
+    pub temporality: Option<String>,
+    pub compression: Option<String>,
+    pub resource: BTreeMap<String, String>,
+}
+
+/// # EffectiveEndpoint
+///
+/// A destination of the exporter in an [`EffectiveConfig`].
+///
+/// ## Fields
+///
+/// * `address` - The address the metrics are sent to
+/// * `tls` - Whether the exports to this address use TLS
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EffectiveEndpoint {
+    pub address: String,
+    pub tls: bool,
+}
+
+/// Serializes a `Duration` as a number of seconds.
+fn serialize_seconds<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+/// Deserializes a whole number of seconds into a `Duration`.
+fn deserialize_seconds<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    u64::deserialize(deserializer).map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempFile;
+
+    #[test]
+    fn from_file_reads_toml() {
+        let file = TempFile::new(
+            "metrics.toml",
+            "service_name = \"checkout\"\nexporter = \"none\"\nexporter_interval = 5\n",
+        );
+
+        let cfg = MetricsConfig::from_file(file.path()).unwrap();
+
+        assert_eq!(cfg.service_name, "checkout");
+        assert_eq!(cfg.exporter, Some(ExporterKind::None));
+        assert_eq!(cfg.exporter_interval, Duration::from_secs(5));
+        assert_eq!(cfg.environment_key, "environment");
+    }
+
+    #[test]
+    fn from_file_reads_yaml() {
+        let file = TempFile::new(
+            "metrics.yaml",
+            "service_name: checkout\nexporter_timeout: 3\n",
+        );
+
+        let cfg = MetricsConfig::from_file(file.path()).unwrap();
+
+        assert_eq!(cfg.service_name, "checkout");
+        assert_eq!(cfg.exporter_timeout, Duration::from_secs(3));
+    }
+
+    #[test]
+    fn from_file_rejects_unknown_extensions_and_invalid_content() {
+        let json = TempFile::new("metrics.json", "{}");
+        let invalid = TempFile::new("metrics.toml", "exporter_interval = \"soon\"");
+
+        assert!(matches!(
+            MetricsConfig::from_file(json.path()),
+            Err(MetricsError::InvalidConfigError(_))
+        ));
+        assert!(matches!(
+            MetricsConfig::from_file(invalid.path()),
+            Err(MetricsError::InvalidConfigError(_))
+        ));
+        assert!(matches!(
+            MetricsConfig::from_file("/nonexistent/metrics.toml"),
+            Err(MetricsError::InvalidConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn exporter_kind_parses_the_runtime_selection() {
+        assert_eq!("OTLP".parse::<ExporterKind>(), Ok(ExporterKind::Otlp));
+        assert_eq!("otlp_grpc".parse::<ExporterKind>(), Ok(ExporterKind::Otlp));
+        assert_eq!("stdout".parse::<ExporterKind>(), Ok(ExporterKind::Stdout));
+        assert_eq!("noop".parse::<ExporterKind>(), Ok(ExporterKind::None));
+        for unknown in ["zipkin", "custom_push"] {
+            assert!(matches!(
+                unknown.parse::<ExporterKind>(),
+                Err(MetricsError::InvalidConfigError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn tls_follows_the_scheme_unless_configured() {
+        let cfg = MetricsConfig::default();
+        assert!(cfg.uses_tls("https://collector:4317"));
+        assert!(!cfg.uses_tls("http://collector:4317"));
+
+        let cfg = MetricsConfig {
+            tls: Some(true),
+            ..MetricsConfig::default()
+        };
+        assert!(cfg.uses_tls("http://collector:4317"));
+    }
+
+    #[test]
+    fn export_durations_prefer_the_configuration_then_the_environment() {
+        let _lock = crate::test_util::global_lock();
+        let name = "OTEL_METRIC_EXPORT_TIMEOUT";
+        // SAFETY: the tests touching the environment hold the global lock
+        unsafe { env::set_var(name, "2500") };
+
+        let configured = configured_or_env(Duration::from_secs(5), name, DEFAULT_EXPORTER_TIMEOUT);
+        let from_env = configured_or_env(Duration::ZERO, name, DEFAULT_EXPORTER_TIMEOUT);
+        unsafe { env::remove_var(name) };
+        let default = configured_or_env(Duration::ZERO, name, DEFAULT_EXPORTER_TIMEOUT);
+
+        assert_eq!(configured, Duration::from_secs(5));
+        assert_eq!(from_env, Duration::from_millis(2500));
+        assert_eq!(default, DEFAULT_EXPORTER_TIMEOUT);
+    }
+}
@@ -0,0 +1,300 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Deferred Metrics
+//!
+//! Captures metrics recorded before the provider is installed.
+//!
+//! Until [`provider::install`](crate::provider::install) runs, the OpenTelemetry global meter
+//! provider is a no-op and every recording is silently dropped. Components that start before
+//! the metrics installation can call [`defer_until_installed`] first: it installs a temporary
+//! in-memory provider as the global provider, and the install functions replay what it
+//! captured onto the real provider.
+//!
+//! ## Replay
+//!
+//! At install, the buffered data is collected once and recorded on the real provider using
+//! the same instrument names, descriptions, units and scopes:
+//!
+//! - Counters and up-down counters are replayed as a single `add` of their accumulated value
+//! - Gauges are replayed with their last recorded value
+//! - Histograms and exponential histograms cannot be rebuilt from their aggregated buckets and
+//!   are dropped with a warning, like any other aggregation the replay does not know
+//!
+//! ## Limitations
+//!
+//! Instruments are bound to the provider that created them. Instruments created while the
+//! buffer is active keep pointing to the temporary provider, which is shut down after replay,
+//! so their later recordings are lost. Components should create their long-lived instruments
+//! after the installation, and only rely on the buffer for early startup recordings.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use metrics::provider;
+//! use opentelemetry::global;
+//!
+//! let guard = metrics::defer_until_installed();
+//!
+//! // recorded before install, buffered
+//! global::meter("startup").u64_counter("config.loaded").build().add(1, &[]);
+//!
+//! // replays the buffered counter onto the real provider
+//! let provider = provider::install().unwrap();
+//! drop(guard);
+//! ```
+
+use crate::{LOG_TARGET, exporters::reader::SharedReader};
+use opentelemetry::{
+    global,
+    metrics::{Meter, MeterProvider},
+};
+use opentelemetry_sdk::{
+    Resource,
+    metrics::{
+        SdkMeterProvider,
+        data::{ExponentialHistogram, Gauge, Histogram, Metric, ResourceMetrics, Sum},
+        reader::MetricReader,
+    },
+};
+use std::{any::Any, sync::Mutex};
+use tracing::{debug, warn};
+
+static BUFFER: Mutex<Option<Buffer>> = Mutex::new(None);
+
+/// The temporary provider and the reader used to drain it.
+struct Buffer {
+    provider: SdkMeterProvider,
+    reader: SharedReader,
+}
+
+/// # DeferGuard
+///
+/// Keeps the pre-install buffer active.
+///
+/// Dropping the guard before the provider is installed discards the buffered metrics. Dropping
+/// it after the installation has no effect.
+#[derive(Debug)]
+#[must_use = "the buffer is discarded when the guard is dropped"]
+pub struct DeferGuard {
+    _private: (),
+}
+
+impl Drop for DeferGuard {
+    fn drop(&mut self) {
+        if let Some(buffer) = take() {
+            debug!(target: LOG_TARGET, "metrics::deferred buffer discarded before install");
+            let _ = buffer.provider.shutdown();
+        }
+    }
+}
+
+/// Starts buffering metrics recorded before the provider is installed.
+///
+/// Installs a temporary in-memory meter provider as the global provider. The next call to one
+/// of the install functions replays the buffered metrics onto the real provider. Calling this
+/// function again while a buffer is active keeps the existing buffer.
+///
+/// # Returns
+///
+/// A [`DeferGuard`] that must be kept alive until the provider is installed
+pub fn defer_until_installed() -> DeferGuard {
+    let mut slot = BUFFER.lock().unwrap_or_else(|e| e.into_inner());
+
+    if slot.is_none() {
+        let reader = SharedReader::new();
+        let provider = SdkMeterProvider::builder()
+            .with_reader(reader.clone())
+            .build();
+
+        global::set_meter_provider(provider.clone());
+        *slot = Some(Buffer { provider, reader });
+    }
+
+    DeferGuard { _private: () }
+}
+
+/// Replays the buffered metrics, if any, onto the installed provider.
+///
+/// The temporary provider is shut down afterwards, so this only replays once.
+pub(crate) fn replay(provider: &SdkMeterProvider) {
+    let Some(buffer) = take() else {
+        return;
+    };
+
+    let mut rm = ResourceMetrics {
+        resource: Resource::builder_empty().build(),
+        scope_metrics: Vec::new(),
+    };
+
+    if let Err(err) = buffer.reader.collect(&mut rm) {
+        warn!(target: LOG_TARGET, error = %err, "failure to collect the deferred metrics");
+    }
+
+    for scope_metrics in &rm.scope_metrics {
+        let meter = provider.meter_with_scope(scope_metrics.scope.clone());
+        for metric in &scope_metrics.metrics {
+            replay_metric(&meter, metric);
+        }
+    }
+
+    let _ = buffer.provider.shutdown();
+}
+
+fn take() -> Option<Buffer> {
+    BUFFER.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+/// Replays a buffered metric, keeping its name, description and unit.
+fn replay_metric(meter: &Meter, metric: &Metric) {
+    let name = metric.name.clone();
+    let description = metric.description.clone();
+    let unit = metric.unit.clone();
+    let data = metric.data.as_any();
+
+    if let Some(sum) = data.downcast_ref::<Sum<u64>>() {
+        let counter = meter
+            .u64_counter(name)
+            .with_description(description)
+            .with_unit(unit)
+            .build();
+        sum.data_points
+            .iter()
+            .for_each(|p| counter.add(p.value, &p.attributes));
+    } else if let Some(sum) = data.downcast_ref::<Sum<f64>>() {
+        if sum.is_monotonic {
+            let counter = meter
+                .f64_counter(name)
+                .with_description(description)
+                .with_unit(unit)
+                .build();
+            sum.data_points
+                .iter()
+                .for_each(|p| counter.add(p.value, &p.attributes));
+        } else {
+            let counter = meter
+                .f64_up_down_counter(name)
+                .with_description(description)
+                .with_unit(unit)
+                .build();
+            sum.data_points
+                .iter()
+                .for_each(|p| counter.add(p.value, &p.attributes));
+        }
+    } else if let Some(sum) = data.downcast_ref::<Sum<i64>>() {
+        let counter = meter
+            .i64_up_down_counter(name)
+            .with_description(description)
+            .with_unit(unit)
+            .build();
+        sum.data_points
+            .iter()
+            .for_each(|p| counter.add(p.value, &p.attributes));
+    } else if let Some(gauge) = data.downcast_ref::<Gauge<u64>>() {
+        let instrument = meter
+            .u64_gauge(name)
+            .with_description(description)
+            .with_unit(unit)
+            .build();
+        gauge
+            .data_points
+            .iter()
+            .for_each(|p| instrument.record(p.value, &p.attributes));
+    } else if let Some(gauge) = data.downcast_ref::<Gauge<i64>>() {
+        let instrument = meter
+            .i64_gauge(name)
+            .with_description(description)
+            .with_unit(unit)
+            .build();
+        gauge
+            .data_points
+            .iter()
+            .for_each(|p| instrument.record(p.value, &p.attributes));
+    } else if let Some(gauge) = data.downcast_ref::<Gauge<f64>>() {
+        let instrument = meter
+            .f64_gauge(name)
+            .with_description(description)
+            .with_unit(unit)
+            .build();
+        gauge
+            .data_points
+            .iter()
+            .for_each(|p| instrument.record(p.value, &p.attributes));
+    } else {
+        warn!(
+            target: LOG_TARGET,
+            metric = %metric.name,
+            aggregation = aggregation_name(data),
+            "deferred metric dropped, its aggregation can not be replayed"
+        );
+    }
+}
+
+/// Names the aggregations that can not be replayed, for the drop warning.
+fn aggregation_name(data: &dyn Any) -> &'static str {
+    if data.is::<Histogram<u64>>() || data.is::<Histogram<f64>>() {
+        "histogram"
+    } else if data.is::<ExponentialHistogram<u64>>() || data.is::<ExponentialHistogram<f64>>() {
+        "exponential histogram"
+    } else {
+        "unknown"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::MetricsConfig, test_util};
+
+    #[test]
+    fn replay_keeps_values_and_metadata() {
+        let _lock = test_util::global_lock();
+        let guard = defer_until_installed();
+        let meter = global::meter("startup");
+        meter
+            .u64_counter("config.loaded")
+            .with_description("configs loaded")
+            .with_unit("{config}")
+            .build()
+            .add(2, &[]);
+        meter.f64_gauge("config.version").build().record(3.0, &[]);
+        meter
+            .f64_histogram("startup.duration")
+            .build()
+            .record(1.0, &[]);
+
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        replay(&provider);
+        drop(guard);
+        let rm = test_util::collect(&reader);
+
+        let loaded = test_util::metric(&rm, "config.loaded").expect("replayed counter");
+        assert_eq!(loaded.description, "configs loaded");
+        assert_eq!(loaded.unit, "{config}");
+        let sum = loaded.data.as_any().downcast_ref::<Sum<u64>>().unwrap();
+        assert_eq!(sum.data_points[0].value, 2);
+
+        let version = test_util::metric(&rm, "config.version").expect("replayed gauge");
+        let gauge = version.data.as_any().downcast_ref::<Gauge<f64>>().unwrap();
+        assert_eq!(gauge.data_points[0].value, 3.0);
+
+        assert!(test_util::metric(&rm, "startup.duration").is_none());
+    }
+
+    #[test]
+    fn dropping_the_guard_discards_the_buffer() {
+        let _lock = test_util::global_lock();
+        let guard = defer_until_installed();
+        global::meter("startup")
+            .u64_counter("config.loaded")
+            .build()
+            .add(1, &[]);
+        drop(guard);
+
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        replay(&provider);
+
+        assert!(test_util::collect(&reader).scope_metrics.is_empty());
+    }
+}
@@ -16,7 +16,21 @@
 //!
 //! All errors implement the standard `Error` trait, making them compatible with
 //! error handling patterns like `?` operator and conversion to `Box<dyn Error>`.
+//!
+//! ## Export Error Classification
+//!
+//! [`ExportError`] is classified from the `OTelSdkError` returned by the exporters. Only the
+//! `Timeout` and `AlreadyShutdown` variants are stable; every other failure arrives as an
+//! `InternalFailure` string. The OTLP exporter of `opentelemetry-otlp` 0.29 builds that string
+//! from the `Debug` rendering of the tonic `Status`, so connection and serialization failures
+//! are recognized by searching it for the gRPC code names and the usual error messages.
+//!
+//! This matching is best effort: it depends on formatting that neither tonic nor the SDK
+//! guarantee, and a change in either can turn a recognized failure into
+//! `ExportError::Other`. Failures that are not recognized are never dropped, they are reported
+//! as `Other` with the original message, and the tests below pin the renderings relied on.
 
+use opentelemetry_sdk::error::OTelSdkError;
 use thiserror::Error;
 
 /// # MetricsError
@@ -33,6 +47,7 @@ use thiserror::Error;
 /// * `ConversionError` - Failed to convert between OpenTelemetry and exporter-specific data types
 /// * `ExporterProviderError` - Failed to create the specified exporter provider, typically due to
 ///    connection issues or invalid configuration
+/// * `InvalidConfigError` - The provided metrics configuration could not be read or is invalid
 ///
 /// ## Example
 ///
@@ -65,4 +80,146 @@ pub enum MetricsError {
 
     #[error("failure to create the exporter provide")]
     ExporterProviderError,
+
+    #[error("invalid configuration: {0}")]
+    InvalidConfigError(String),
+}
+
+/// # ExportError
+///
+/// Typed classification of a failed metrics export.
+///
+/// The OpenTelemetry SDK reports export failures as free-form strings. This enum classifies
+/// them so applications can react differently depending on the failure, for example alerting
+/// on connection errors while paging on serialization errors. Export errors are delivered to
+/// the hook configured in `MetricsConfig::on_export_error`.
+///
+/// ## Variants
+///
+/// * `Timeout` - The export did not complete within the configured timeout
+/// * `ConnectionRefused` - The collector could not be reached
+/// * `Serialization` - The metrics could not be encoded or the collector rejected the payload
+/// * `Shutdown` - The export was attempted after the exporter was shut down
+/// * `Other` - Any other failure, with the original error message
+///
+/// ## Example
+///
+/// ```
+/// use metrics::errors::ExportError;
+///
+/// fn on_export_error(err: &ExportError) {
+///     match err {
+///         ExportError::ConnectionRefused => println!("collector unreachable"),
+///         ExportError::Serialization => println!("invalid payload"),
+///         _ => {}
+///     }
+/// }
+/// ```
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ExportError {
+    #[error("export timed out")]
+    Timeout,
+
+    #[error("connection to the collector refused")]
+    ConnectionRefused,
+
+    #[error("failure to serialize the exported metrics")]
+    Serialization,
+
+    #[error("exporter already shut down")]
+    Shutdown,
+
+    #[error("export failure: {0}")]
+    Other(String),
+}
+
+/// Classifies an SDK error, see the module documentation for the limits of the matching.
+impl From<&OTelSdkError> for ExportError {
+    fn from(err: &OTelSdkError) -> Self {
+        match err {
+            OTelSdkError::Timeout(_) => ExportError::Timeout,
+            OTelSdkError::AlreadyShutdown => ExportError::Shutdown,
+            OTelSdkError::InternalFailure(msg) => {
+                let lower = msg.to_lowercase();
+
+                if lower.contains("deadlineexceeded") || lower.contains("timed out") {
+                    ExportError::Timeout
+                } else if lower.contains("unavailable")
+                    || lower.contains("connection refused")
+                    || lower.contains("connect error")
+                {
+                    ExportError::ConnectionRefused
+                } else if lower.contains("encode")
+                    || lower.contains("decode")
+                    || lower.contains("serializ")
+                    || lower.contains("invalidargument")
+                {
+                    ExportError::Serialization
+                } else if lower.contains("shut down") {
+                    ExportError::Shutdown
+                } else {
+                    ExportError::Other(msg.clone())
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn classify(err: OTelSdkError) -> ExportError {
+        ExportError::from(&err)
+    }
+
+    #[test]
+    fn stable_variants_are_classified() {
+        assert_eq!(
+            classify(OTelSdkError::Timeout(Duration::from_secs(1))),
+            ExportError::Timeout
+        );
+        assert_eq!(
+            classify(OTelSdkError::AlreadyShutdown),
+            ExportError::Shutdown
+        );
+    }
+
+    #[test]
+    fn grpc_codes_are_classified() {
+        let failure = |msg: &str| OTelSdkError::InternalFailure(msg.to_string());
+
+        assert_eq!(
+            classify(failure(r#"Status { code: DeadlineExceeded, message: "" }"#)),
+            ExportError::Timeout
+        );
+        assert_eq!(
+            classify(failure(r#"Status { code: Unavailable, message: "" }"#)),
+            ExportError::ConnectionRefused
+        );
+        assert_eq!(
+            classify(failure(r#"Status { code: InvalidArgument, message: "" }"#)),
+            ExportError::Serialization
+        );
+    }
+
+    #[test]
+    fn unrecognized_failures_keep_their_message() {
+        assert_eq!(
+            classify(OTelSdkError::InternalFailure("boom".to_string())),
+            ExportError::Other("boom".to_string())
+        );
+    }
+
+    #[cfg(feature = "otlp")]
+    #[test]
+    fn tonic_status_rendering_is_recognized() {
+        let status = tonic::Status::unavailable("collector down");
+
+        assert_eq!(
+            classify(OTelSdkError::InternalFailure(format!("{status:?}"))),
+            ExportError::ConnectionRefused
+        );
+    }
 }
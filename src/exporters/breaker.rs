@@ -0,0 +1,232 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Export Circuit Breaker
+//!
+//! Provides an exporter wrapper that stops exporting while the collector keeps failing.
+//!
+//! After `breaker_threshold` consecutive failed exports the breaker opens: exports are skipped
+//! for `breaker_cooldown`, which avoids spending CPU on connection attempts and flooding the
+//! logs during an outage. Once the cooldown has elapsed the breaker is half-open and the next
+//! export is sent as a probe. A successful probe closes the breaker, a failed one opens it for
+//! another cooldown.
+//!
+//! Skipped exports are reported as successful to the reader, so the data of those cycles is
+//! dropped. With cumulative temporality the next successful export carries the totals again.
+
+use crate::{
+    LOG_TARGET,
+    config::{BreakerState, ExportHealth},
+};
+use opentelemetry_sdk::{
+    error::OTelSdkResult,
+    metrics::{Temporality, data::ResourceMetrics, exporter::PushMetricExporter},
+};
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tracing::{debug, warn};
+
+/// # BreakerExporter
+///
+/// Wraps an exporter with a circuit breaker. A threshold of `0` disables the breaker.
+#[derive(Debug)]
+pub(crate) struct BreakerExporter<E> {
+    inner: E,
+    threshold: u32,
+    cooldown: Duration,
+    endpoint: String,
+    health: Option<ExportHealth>,
+    state: Mutex<Breaker>,
+}
+
+/// The mutable state of the breaker.
+#[derive(Debug, Default)]
+struct Breaker {
+    failures: u32,
+    opened_at: Option<Instant>,
+}
+
+impl<E> BreakerExporter<E> {
+    /// Wraps `inner`, publishing the breaker state of `endpoint` to `health` when configured.
+    pub(crate) fn new(
+        inner: E,
+        threshold: u32,
+        cooldown: Duration,
+        endpoint: &str,
+        health: Option<ExportHealth>,
+    ) -> Self {
+        let exporter = Self {
+            inner,
+            threshold,
+            cooldown,
+            endpoint: endpoint.to_string(),
+            health,
+            state: Mutex::new(Breaker::default()),
+        };
+        exporter.publish(BreakerState::Closed);
+
+        exporter
+    }
+
+    fn publish(&self, state: BreakerState) {
+        if let Some(health) = &self.health {
+            health.set_breaker_state(&self.endpoint, state);
+        }
+    }
+
+    /// Returns whether the export should be attempted, moving to half-open after the cooldown.
+    fn allow(&self) -> bool {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        match state.opened_at {
+            Some(opened_at) if opened_at.elapsed() < self.cooldown => false,
+            Some(_) => {
+                self.publish(BreakerState::HalfOpen);
+                true
+            }
+            None => true,
+        }
+    }
+
+    fn record(&self, success: bool) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        if success {
+            if state.opened_at.take().is_some() {
+                debug!(
+                    target: LOG_TARGET,
+                    endpoint = %self.endpoint,
+                    "metrics export circuit breaker closed"
+                );
+                self.publish(BreakerState::Closed);
+            }
+            state.failures = 0;
+            return;
+        }
+
+        state.failures = state.failures.saturating_add(1);
+        if state.opened_at.is_some() || state.failures >= self.threshold {
+            if state.opened_at.is_none() {
+                warn!(
+                    target: LOG_TARGET,
+                    endpoint = %self.endpoint,
+                    failures = state.failures,
+                    "metrics export circuit breaker opened"
+                );
+            }
+            state.opened_at = Some(Instant::now());
+            self.publish(BreakerState::Open);
+        }
+    }
+}
+
+impl<E: PushMetricExporter> PushMetricExporter for BreakerExporter<E> {
+    async fn export(&self, metrics: &mut ResourceMetrics) -> OTelSdkResult {
+        if self.threshold == 0 {
+            return self.inner.export(metrics).await;
+        }
+
+        if !self.allow() {
+            debug!(
+                target: LOG_TARGET,
+                endpoint = %self.endpoint,
+                "metrics export skipped, circuit breaker open"
+            );
+            return Ok(());
+        }
+
+        let result = self.inner.export(metrics).await;
+        self.record(result.is_ok());
+
+        result
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        self.inner.shutdown()
+    }
+
+    fn temporality(&self) -> Temporality {
+        self.inner.temporality()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, MockExporter};
+    use std::thread;
+
+    const ENDPOINT: &str = "http://collector:4317";
+
+    fn export(exporter: &BreakerExporter<MockExporter>) -> OTelSdkResult {
+        test_util::block_on(exporter.export(&mut test_util::empty_metrics()))
+    }
+
+    #[test]
+    fn breaker_opens_after_consecutive_failures() {
+        let mock = MockExporter::default();
+        mock.set_failing(true);
+        let health = ExportHealth::default();
+        let exporter = BreakerExporter::new(
+            mock.clone(),
+            2,
+            Duration::from_secs(3600),
+            ENDPOINT,
+            Some(health.clone()),
+        );
+        assert_eq!(health.breaker_state(ENDPOINT), Some(BreakerState::Closed));
+
+        assert!(export(&exporter).is_err());
+        assert!(export(&exporter).is_err());
+        // skipped while open, reported as successful
+        assert!(export(&exporter).is_ok());
+
+        assert_eq!(mock.exports(), 2);
+        assert_eq!(health.breaker_state(ENDPOINT), Some(BreakerState::Open));
+    }
+
+    #[test]
+    fn successful_probe_closes_the_breaker() {
+        let mock = MockExporter::default();
+        mock.set_failing(true);
+        let health = ExportHealth::default();
+        let exporter = BreakerExporter::new(
+            mock.clone(),
+            1,
+            Duration::from_millis(10),
+            ENDPOINT,
+            Some(health.clone()),
+        );
+
+        assert!(export(&exporter).is_err());
+        assert_eq!(health.breaker_state(ENDPOINT), Some(BreakerState::Open));
+
+        mock.set_failing(false);
+        thread::sleep(Duration::from_millis(20));
+        assert!(export(&exporter).is_ok());
+
+        assert_eq!(mock.exports(), 2);
+        assert_eq!(health.breaker_state(ENDPOINT), Some(BreakerState::Closed));
+    }
+
+    #[test]
+    fn zero_threshold_disables_the_breaker() {
+        let mock = MockExporter::default();
+        mock.set_failing(true);
+        let exporter =
+            BreakerExporter::new(mock.clone(), 0, Duration::from_secs(3600), ENDPOINT, None);
+
+        for _ in 0..3 {
+            assert!(export(&exporter).is_err());
+        }
+
+        assert_eq!(mock.exports(), 3);
+    }
+}
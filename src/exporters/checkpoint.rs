@@ -0,0 +1,234 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Checkpoints
+//!
+//! Exports the measurements of each stage of a long-running job on their own.
+//!
+//! A [`Checkpoint`] placed in `MetricsConfig::checkpoint` is registered as an additional reader
+//! with delta temporality on the installed provider. The periodic exports keep their own
+//! temporality; the checkpoint reader only resets its own accumulators, so each call to
+//! [`Checkpoint::collect`] or [`Checkpoint::export`] carries exactly the measurements recorded
+//! since the previous checkpoint, and the provider keeps running for the next stage. The
+//! transforms of the configuration (global attributes, redaction, rounding) apply to every
+//! checkpoint.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use metrics::{config::MetricsConfig, exporters::checkpoint::Checkpoint, provider};
+//! use opentelemetry::metrics::MeterProvider;
+//!
+//! let checkpoint = Checkpoint::new();
+//! let cfg = MetricsConfig {
+//!     checkpoint: Some(checkpoint.clone()),
+//!     ..MetricsConfig::load()
+//! };
+//!
+//! let provider = provider::install_with_config(&cfg).unwrap();
+//! let rows = provider.meter("etl").u64_counter("etl.rows").build();
+//!
+//! for _stage in ["extract", "transform", "load"] {
+//!     // ... run the stage, recording on `rows` ...
+//!     let _stage = checkpoint.collect().unwrap();
+//! }
+//! ```
+
+use super::{reader::SharedReader, transform::Transform};
+use crate::{LOG_TARGET, config::MetricsConfig, errors::MetricsError};
+use opentelemetry_sdk::{
+    Resource,
+    error::{OTelSdkError, OTelSdkResult},
+    metrics::{data::ResourceMetrics, exporter::PushMetricExporter, reader::MetricReader},
+};
+use std::sync::{Arc, OnceLock};
+use tracing::{debug, error};
+
+/// # Checkpoint
+///
+/// A handle collecting the measurements recorded on the provider since the previous checkpoint.
+///
+/// Clones share the same reader, so every checkpoint taken through any clone delimits the next
+/// stage for all of them.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    reader: SharedReader,
+    transform: Arc<OnceLock<Transform>>,
+}
+
+impl Default for Checkpoint {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for Checkpoint {
+    fn eq(&self, other: &Self) -> bool {
+        self.reader.ptr_eq(&other.reader)
+    }
+}
+
+impl Checkpoint {
+    /// Creates a checkpoint handle, not yet registered on any provider.
+    pub fn new() -> Self {
+        Self {
+            reader: SharedReader::delta(),
+            transform: Arc::new(OnceLock::new()),
+        }
+    }
+
+    /// Collects the measurements recorded since the previous checkpoint and starts a new stage.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ResourceMetrics)` - The deltas of the stage
+    /// * `Err(MetricsError::InternalError)` - If the checkpoint is not registered on a provider,
+    ///   or the provider was shut down
+    pub fn collect(&self) -> Result<ResourceMetrics, MetricsError> {
+        let mut rm = ResourceMetrics {
+            resource: Resource::builder_empty().build(),
+            scope_metrics: Vec::new(),
+        };
+
+        if let Err(err) = self.reader.collect(&mut rm) {
+            error!(target: LOG_TARGET, error = %err, "failure to collect the metrics checkpoint");
+            return Err(MetricsError::InternalError);
+        }
+
+        if let Some(transform) = self.transform.get() {
+            transform.apply(&mut rm);
+        }
+
+        Ok(rm)
+    }
+
+    /// Collects the measurements recorded since the previous checkpoint and exports them.
+    ///
+    /// The exporter receives delta data points whatever temporality it reports, so it should
+    /// be one the backend accepts deltas from, such as an OTLP exporter built with delta
+    /// temporality.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The stage was exported
+    /// * `Err(OTelSdkError)` - The collection or the export failed, the measurements of the
+    ///   stage are lost
+    pub async fn export<E: PushMetricExporter>(&self, exporter: &E) -> OTelSdkResult {
+        let mut rm = self
+            .collect()
+            .map_err(|err| OTelSdkError::InternalFailure(err.to_string()))?;
+
+        match exporter.export(&mut rm).await {
+            Ok(()) => {
+                debug!(target: LOG_TARGET, "metrics checkpoint exported");
+                Ok(())
+            }
+            Err(err) => {
+                error!(target: LOG_TARGET, error = %err, "failure to export metrics checkpoint");
+                Err(err)
+            }
+        }
+    }
+
+    /// Returns the reader to register on the provider built from `cfg`, whose transforms the
+    /// checkpoints apply.
+    ///
+    /// The checkpoint may be exported anywhere, so only the transforms shared by every exporter
+    /// apply, not the ones `Transform::for_exporter` adds for a given exporter kind.
+    pub(crate) fn register(&self, cfg: &MetricsConfig) -> SharedReader {
+        let _ = self.transform.set(Transform::new(cfg));
+        self.reader.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use opentelemetry::{KeyValue, metrics::MeterProvider};
+    use opentelemetry_sdk::metrics::data::Sum;
+
+    fn stage_sum(rm: &ResourceMetrics) -> Option<u64> {
+        let sum = test_util::metric(rm, "rows")?
+            .data
+            .as_any()
+            .downcast_ref::<Sum<u64>>()?;
+        Some(sum.data_points.iter().map(|point| point.value).sum())
+    }
+
+    #[test]
+    fn each_checkpoint_carries_its_own_stage() {
+        let checkpoint = Checkpoint::new();
+        let cfg = MetricsConfig {
+            checkpoint: Some(checkpoint.clone()),
+            ..MetricsConfig::default()
+        };
+        let (provider, reader) = test_util::provider(&cfg);
+        let rows = provider.meter("etl").u64_counter("rows").build();
+
+        rows.add(5, &[]);
+        assert_eq!(stage_sum(&checkpoint.collect().unwrap()), Some(5));
+
+        rows.add(2, &[]);
+        assert_eq!(stage_sum(&checkpoint.collect().unwrap()), Some(2));
+
+        // the other readers keep their cumulative totals
+        assert_eq!(stage_sum(&test_util::collect(&reader)), Some(7));
+    }
+
+    #[test]
+    fn export_hands_the_stage_to_the_exporter() {
+        let checkpoint = Checkpoint::new();
+        let cfg = MetricsConfig {
+            checkpoint: Some(checkpoint.clone()),
+            ..MetricsConfig::default()
+        };
+        let (provider, _reader) = test_util::provider(&cfg);
+        provider
+            .meter("etl")
+            .u64_counter("rows")
+            .build()
+            .add(1, &[]);
+
+        let exporter = test_util::MockExporter::default();
+        test_util::block_on(checkpoint.export(&exporter)).unwrap();
+        assert_eq!(exporter.names(), vec![vec!["rows".to_string()]]);
+
+        exporter.set_failing(true);
+        assert!(test_util::block_on(checkpoint.export(&exporter)).is_err());
+    }
+
+    #[test]
+    fn unregistered_checkpoint_fails() {
+        assert!(Checkpoint::new().collect().is_err());
+    }
+
+    #[test]
+    fn checkpoints_carry_the_global_attributes() {
+        let checkpoint = Checkpoint::new();
+        let cfg = MetricsConfig {
+            checkpoint: Some(checkpoint.clone()),
+            global_attributes: [("region".to_string(), "eu".to_string())].into(),
+            ..MetricsConfig::default()
+        };
+        let (provider, _reader) = test_util::provider(&cfg);
+        provider
+            .meter("etl")
+            .u64_counter("rows")
+            .build()
+            .add(1, &[]);
+
+        let rm = checkpoint.collect().unwrap();
+        let sum = test_util::metric(&rm, "rows")
+            .unwrap()
+            .data
+            .as_any()
+            .downcast_ref::<Sum<u64>>()
+            .unwrap();
+        assert_eq!(
+            sum.data_points[0].attributes,
+            vec![KeyValue::new("region", "eu")]
+        );
+    }
+}
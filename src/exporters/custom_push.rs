@@ -0,0 +1,294 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Custom Push Exporter
+//!
+//! Provides an exporter posting metrics serialized by the application to an HTTP endpoint.
+//!
+//! This module is conditionally compiled when the "custom-push" feature is enabled. It targets
+//! proprietary ingestion APIs that speak neither OTLP nor Prometheus: the application provides
+//! the serializer, and on every export cycle the exporter posts its output to the endpoint.
+//!
+//! ## Configuration
+//!
+//! Enable this exporter by building with the `custom-push` feature flag:
+//!
+//! ```sh
+//! cargo build --features custom-push
+//! ```
+//!
+//! The export interval, the timeout of each request, the global attributes and the resource are
+//! read from the [`MetricsConfig`]. Its per-exporter settings, such as `resource_keys`, apply
+//! under the `custom_push` key. A response with a non-success status fails the export.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use metrics::{config::MetricsConfig, exporters::custom_push};
+//! use opentelemetry_sdk::metrics::data::ResourceMetrics;
+//!
+//! let provider = custom_push::install(
+//!     &MetricsConfig::load(),
+//!     "https://ingest.internal/v1/metrics",
+//!     "application/json",
+//!     |metrics: &ResourceMetrics| {
+//!         let names: Vec<String> = metrics
+//!             .scope_metrics
+//!             .iter()
+//!             .flat_map(|s| s.metrics.iter().map(|m| format!("\"{}\"", m.name)))
+//!             .collect();
+//!         format!("{{\"metrics\":[{}]}}", names.join(",")).into_bytes()
+//!     },
+//! )
+//! .unwrap();
+//! ```
+
+use super::{reader::periodic, transform::TransformExporter};
+use crate::{
+    LOG_TARGET,
+    config::{ExporterKind, MetricsConfig},
+    errors::MetricsError,
+};
+use opentelemetry_sdk::{
+    error::{OTelSdkError, OTelSdkResult},
+    metrics::{SdkMeterProvider, Temporality, data::ResourceMetrics, exporter::PushMetricExporter},
+};
+use reqwest::{blocking::Client, header::CONTENT_TYPE};
+use std::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use tracing::{debug, error};
+
+/// Creates and installs an exporter posting the output of `serializer` to `endpoint`.
+///
+/// # Type Parameters
+///
+/// * `F` - The serializer, called with the collected metrics on every export cycle
+///
+/// # Parameters
+///
+/// * `cfg` - The metrics configuration
+/// * `endpoint` - The URL the serialized metrics are posted to
+/// * `content_type` - The value of the `content-type` header of the requests
+/// * `serializer` - Turns the collected metrics into the request body
+///
+/// # Returns
+///
+/// * `Ok(SdkMeterProvider)` - The configured meter provider
+/// * `Err(MetricsError::ExporterProviderError)` - If the HTTP client could not be created
+pub fn install<F>(
+    cfg: &MetricsConfig,
+    endpoint: &str,
+    content_type: &str,
+    serializer: F,
+) -> Result<SdkMeterProvider, MetricsError>
+where
+    F: Fn(&ResourceMetrics) -> Vec<u8> + Send + Sync + 'static,
+{
+    let client = match Client::builder().timeout(cfg.exporter_timeout).build() {
+        Ok(c) => Ok(c),
+        Err(err) => {
+            error!(
+                target: LOG_TARGET,
+                error = err.to_string(),
+                endpoint = endpoint,
+                "failure to create custom push client"
+            );
+            Err(MetricsError::ExporterProviderError)
+        }
+    }?;
+
+    let exporter = CustomPushExporter {
+        client,
+        endpoint: endpoint.to_string(),
+        content_type: content_type.to_string(),
+        serializer: Box::new(serializer),
+        is_shutdown: AtomicBool::new(false),
+    };
+    let reader = periodic(
+        TransformExporter::for_exporter(exporter, cfg, ExporterKind::CustomPush),
+        cfg,
+    );
+
+    let provider = super::provider_builder(cfg).with_reader(reader).build();
+
+    super::install_global(&provider);
+
+    debug!(
+        target: LOG_TARGET,
+        exporter = "custom_push",
+        endpoint = endpoint,
+        interval = ?cfg.exporter_interval,
+        "metrics::install custom push exporter installed"
+    );
+
+    Ok(provider)
+}
+
+/// # CustomPushExporter
+///
+/// Exporter posting the serialized metrics to an HTTP endpoint.
+///
+/// The blocking client is used because the periodic reader runs its exports on a dedicated
+/// thread, outside of any async runtime.
+struct CustomPushExporter {
+    client: Client,
+    endpoint: String,
+    content_type: String,
+    serializer: Box<dyn Fn(&ResourceMetrics) -> Vec<u8> + Send + Sync>,
+    is_shutdown: AtomicBool,
+}
+
+impl fmt::Debug for CustomPushExporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomPushExporter")
+            .field("endpoint", &self.endpoint)
+            .field("content_type", &self.content_type)
+            .finish()
+    }
+}
+
+impl PushMetricExporter for CustomPushExporter {
+    async fn export(&self, metrics: &mut ResourceMetrics) -> OTelSdkResult {
+        if self.is_shutdown.load(Ordering::SeqCst) {
+            return Err(OTelSdkError::AlreadyShutdown);
+        }
+
+        let body = (self.serializer)(metrics);
+
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .header(CONTENT_TYPE, &self.content_type)
+            .body(body)
+            .send()
+            .map_err(|err| OTelSdkError::InternalFailure(err.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(OTelSdkError::InternalFailure(format!(
+                "custom push endpoint responded with {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        Ok(())
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        self.is_shutdown.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn temporality(&self) -> Temporality {
+        Temporality::Cumulative
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use opentelemetry::metrics::MeterProvider;
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::mpsc,
+        thread,
+        time::Duration,
+    };
+
+    /// Serves a single request with `status`, and sends its headers and body to the channel.
+    fn serve_once(status: &'static str) -> (String, mpsc::Receiver<(Vec<String>, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}/v1/metrics", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut headers = Vec::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end().to_ascii_lowercase();
+                if line.is_empty() {
+                    break;
+                }
+                headers.push(line);
+            }
+
+            let length = headers
+                .iter()
+                .find_map(|h| h.strip_prefix("content-length: "))
+                .and_then(|len| len.parse().ok())
+                .unwrap_or(0);
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+
+            let response = format!("HTTP/1.1 {status}\r\ncontent-length: 0\r\n\r\n");
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+            let _ = sender.send((headers, String::from_utf8(body).unwrap()));
+        });
+
+        (endpoint, receiver)
+    }
+
+    fn names(metrics: &ResourceMetrics) -> Vec<u8> {
+        metrics
+            .scope_metrics
+            .iter()
+            .flat_map(|s| s.metrics.iter().map(|m| m.name.to_string()))
+            .collect::<Vec<_>>()
+            .join(",")
+            .into_bytes()
+    }
+
+    #[test]
+    fn export_posts_the_serialized_metrics() {
+        let _lock = test_util::global_lock();
+        let (endpoint, requests) = serve_once("200 OK");
+        let cfg = MetricsConfig {
+            exporter_interval: Duration::from_secs(3600),
+            ..MetricsConfig::default()
+        };
+        let provider = install(&cfg, &endpoint, "text/plain", names).unwrap();
+        provider
+            .meter("test")
+            .u64_counter("requests")
+            .build()
+            .add(1, &[]);
+
+        assert!(provider.force_flush().is_ok());
+
+        let (headers, body) = requests.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(headers.iter().any(|h| h == "content-type: text/plain"));
+        assert_eq!(body, "requests");
+        let _ = provider.shutdown();
+    }
+
+    #[test]
+    fn error_status_fails_the_export() {
+        let _lock = test_util::global_lock();
+        let (endpoint, requests) = serve_once("500 Internal Server Error");
+        let cfg = MetricsConfig {
+            exporter_interval: Duration::from_secs(3600),
+            ..MetricsConfig::default()
+        };
+        let provider = install(&cfg, &endpoint, "text/plain", names).unwrap();
+        provider
+            .meter("test")
+            .u64_counter("requests")
+            .build()
+            .add(1, &[]);
+
+        assert!(provider.force_flush().is_err());
+        assert!(requests.recv_timeout(Duration::from_secs(5)).is_ok());
+        let _ = provider.shutdown();
+    }
+}
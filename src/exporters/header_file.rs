@@ -0,0 +1,218 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Header File
+//!
+//! Provides an interceptor sending a metadata header whose value is read from a file.
+//!
+//! Secrets are often mounted as files rather than passed as environment variables. When
+//! `MetricsConfig::header_value_file` is set, the file is read at install and its trimmed
+//! content is sent in the `header_access_key` header of every export, in place of
+//! `access_key`. A missing or unreadable file fails the install.
+//!
+//! Mounted secrets may be rotated in place. With `header_refresh_interval` set, the file is read
+//! again on the first export after the interval elapsed. A failed refresh keeps the previous
+//! value, so a rotation caught halfway does not break the exports.
+
+use crate::{LOG_TARGET, config::MetricsConfig, errors::MetricsError};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tonic::{
+    Request, Status,
+    metadata::{Ascii, MetadataKey, MetadataValue},
+    service::Interceptor,
+};
+use tracing::{debug, error, warn};
+
+/// # HeaderFile
+///
+/// Interceptor inserting the header read from the configured file into every export request.
+///
+/// Clones share the cached value, so every endpoint refreshes the same file once.
+#[derive(Debug, Clone)]
+pub(crate) struct HeaderFile {
+    key: MetadataKey<Ascii>,
+    path: PathBuf,
+    refresh: Duration,
+    cached: Arc<Mutex<Cached>>,
+}
+
+/// The last value read from the file.
+#[derive(Debug)]
+struct Cached {
+    value: MetadataValue<Ascii>,
+    read_at: Instant,
+}
+
+impl HeaderFile {
+    /// Reads the header value from `MetricsConfig::header_value_file`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(None)` - If no header file is configured
+    /// * `Ok(Some(HeaderFile))` - The interceptor sending the value read from the file
+    /// * `Err(MetricsError::InvalidConfigError)` - If the file could not be read, or if the
+    ///   header name or value is invalid
+    pub(crate) fn load(cfg: &MetricsConfig) -> Result<Option<Self>, MetricsError> {
+        let Some(path) = &cfg.header_value_file else {
+            return Ok(None);
+        };
+
+        let Ok(key) = MetadataKey::from_bytes(cfg.header_access_key.as_bytes()) else {
+            error!(
+                target: LOG_TARGET,
+                header = %cfg.header_access_key,
+                "invalid metrics access key header"
+            );
+            return Err(MetricsError::InvalidConfigError(format!(
+                "invalid access key header: {}",
+                cfg.header_access_key
+            )));
+        };
+
+        let value = match read(path) {
+            Ok(value) => value,
+            Err(err) => {
+                error!(
+                    target: LOG_TARGET,
+                    error = %err,
+                    path = %path.display(),
+                    "failure to read the header value file"
+                );
+                return Err(MetricsError::InvalidConfigError(format!(
+                    "header_value_file {}: {err}",
+                    path.display()
+                )));
+            }
+        };
+
+        debug!(
+            target: LOG_TARGET,
+            header = %cfg.header_access_key,
+            path = %path.display(),
+            refresh = ?cfg.header_refresh_interval,
+            "header value read from file"
+        );
+
+        Ok(Some(Self {
+            key,
+            path: path.clone(),
+            refresh: cfg.header_refresh_interval,
+            cached: Arc::new(Mutex::new(Cached {
+                value,
+                read_at: Instant::now(),
+            })),
+        }))
+    }
+
+    /// Returns the current value, reading the file again once the refresh interval elapsed.
+    fn value(&self) -> MetadataValue<Ascii> {
+        let mut cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+
+        if !self.refresh.is_zero() && cached.read_at.elapsed() >= self.refresh {
+            match read(&self.path) {
+                Ok(value) => {
+                    debug!(target: LOG_TARGET, path = %self.path.display(), "header value refreshed");
+                    cached.value = value;
+                }
+                Err(err) => warn!(
+                    target: LOG_TARGET,
+                    error = %err,
+                    path = %self.path.display(),
+                    "failure to refresh the header value, keeping the previous one"
+                ),
+            }
+            cached.read_at = Instant::now();
+        }
+
+        cached.value.clone()
+    }
+}
+
+impl Interceptor for HeaderFile {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let value = self.value();
+        request.metadata_mut().insert(self.key.clone(), value);
+        Ok(request)
+    }
+}
+
+/// Reads the trimmed content of `path` as a metadata value.
+fn read(path: &Path) -> Result<MetadataValue<Ascii>, String> {
+    let content = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    MetadataValue::try_from(content.trim()).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempFile;
+    use std::thread;
+
+    fn config(path: &Path, refresh: Duration) -> MetricsConfig {
+        MetricsConfig {
+            header_access_key: "authorization".to_string(),
+            header_value_file: Some(path.to_path_buf()),
+            header_refresh_interval: refresh,
+            ..MetricsConfig::default()
+        }
+    }
+
+    fn header(interceptor: &mut HeaderFile) -> String {
+        let request = interceptor.call(Request::new(())).unwrap();
+        request
+            .metadata()
+            .get("authorization")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn header_is_read_from_the_file_and_refreshed() {
+        let file = TempFile::new("token", "token-1\n");
+        let mut interceptor = HeaderFile::load(&config(file.path(), Duration::from_millis(1)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(header(&mut interceptor), "token-1");
+
+        fs::write(file.path(), "token-2").unwrap();
+        thread::sleep(Duration::from_millis(5));
+
+        assert_eq!(header(&mut interceptor), "token-2");
+    }
+
+    #[test]
+    fn failed_refresh_keeps_the_previous_value() {
+        let file = TempFile::new("token", "token-1");
+        let mut interceptor = HeaderFile::load(&config(file.path(), Duration::from_millis(1)))
+            .unwrap()
+            .unwrap();
+
+        fs::remove_file(file.path()).unwrap();
+        thread::sleep(Duration::from_millis(5));
+
+        assert_eq!(header(&mut interceptor), "token-1");
+    }
+
+    #[test]
+    fn missing_file_fails_the_load() {
+        let cfg = config(Path::new("/nonexistent/token"), Duration::ZERO);
+
+        assert!(matches!(
+            HeaderFile::load(&cfg),
+            Err(MetricsError::InvalidConfigError(_))
+        ));
+        assert!(
+            HeaderFile::load(&MetricsConfig::default())
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Export Concurrency Limiter
+//!
+//! Provides an exporter wrapper bounding the number of exports in flight at the same time.
+//!
+//! When the collector is slow, export cycles and forced flushes can overlap, each holding a
+//! full batch of serialized metrics in memory. The wrapper acquires a permit before calling the
+//! underlying exporter, so an export started while the limit is reached waits for a running
+//! one to complete instead of stacking another batch.
+//!
+//! The `PeriodicReader` of the SDK currently runs its exports one at a time, so with the
+//! default limit of `1` the wrapper never blocks. It keeps the bound explicit should the reader
+//! or the way exporters are shared change.
+
+use opentelemetry_sdk::{
+    error::{OTelSdkError, OTelSdkResult},
+    metrics::{Temporality, data::ResourceMetrics, exporter::PushMetricExporter},
+};
+use tokio::sync::Semaphore;
+
+/// # LimitedExporter
+///
+/// Wraps an exporter and allows at most `max_concurrent_exports` exports to run at once.
+#[derive(Debug)]
+pub(crate) struct LimitedExporter<E> {
+    inner: E,
+    permits: Semaphore,
+}
+
+impl<E> LimitedExporter<E> {
+    /// Wraps `inner`, allowing `max_concurrent_exports` exports in flight. A limit of `0` is
+    /// treated as `1`.
+    pub(crate) fn new(inner: E, max_concurrent_exports: usize) -> Self {
+        Self {
+            inner,
+            permits: Semaphore::new(max_concurrent_exports.max(1)),
+        }
+    }
+}
+
+impl<E: PushMetricExporter> PushMetricExporter for LimitedExporter<E> {
+    async fn export(&self, metrics: &mut ResourceMetrics) -> OTelSdkResult {
+        let Ok(_permit) = self.permits.acquire().await else {
+            return Err(OTelSdkError::AlreadyShutdown);
+        };
+
+        self.inner.export(metrics).await
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        self.permits.close();
+        self.inner.shutdown()
+    }
+
+    fn temporality(&self) -> Temporality {
+        self.inner.temporality()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, MockExporter};
+    use std::sync::Arc;
+
+    fn run_concurrently(exporter: LimitedExporter<MockExporter>) {
+        let exporter = Arc::new(exporter);
+        test_util::block_on(async {
+            let exports: Vec<_> = (0..3)
+                .map(|_| {
+                    let exporter = exporter.clone();
+                    tokio::spawn(
+                        async move { exporter.export(&mut test_util::empty_metrics()).await },
+                    )
+                })
+                .collect();
+            for export in exports {
+                assert!(export.await.unwrap().is_ok());
+            }
+        });
+    }
+
+    #[test]
+    fn exports_beyond_the_limit_wait() {
+        let mock = MockExporter::default();
+        let exporter = LimitedExporter::new(mock.clone(), 1);
+
+        run_concurrently(exporter);
+
+        assert_eq!(mock.exports(), 3);
+        assert_eq!(mock.max_in_flight(), 1);
+    }
+
+    #[test]
+    fn exports_within_the_limit_run_together() {
+        let mock = MockExporter::default();
+        let exporter = LimitedExporter::new(mock.clone(), 2);
+
+        run_concurrently(exporter);
+
+        assert_eq!(mock.exports(), 3);
+        assert_eq!(mock.max_in_flight(), 2);
+    }
+
+    #[test]
+    fn exports_fail_after_shutdown() {
+        let exporter = LimitedExporter::new(MockExporter::default(), 1);
+        exporter.shutdown().unwrap();
+
+        let result = test_util::block_on(exporter.export(&mut test_util::empty_metrics()));
+
+        assert!(matches!(result, Err(OTelSdkError::AlreadyShutdown)));
+    }
+}
@@ -8,7 +8,10 @@
 //!
 //! This module contains implementations for different metrics exporters:
 //!
+//! - **Custom Push Exporter**: Posts metrics serialized by the application to an HTTP endpoint
 //! - **OTLP Exporter**: Sends metrics to an OpenTelemetry collector using the OpenTelemetry Protocol over gRPC
+//! - **Prometheus Exporter**: Renders metrics in the Prometheus text format for scraping
+//! - **StatsD Exporter**: Sends metrics to a StatsD server over UDP
 //! - **Stdout Exporter**: Writes metrics to standard output for development and debugging
 //! - **No-op Exporter**: A fallback exporter that discards metrics when no other exporter is enabled
 //!
@@ -17,21 +20,94 @@
 //! when only specific exporters are required.
 //!
 //! The module also includes common components like temporality selectors which define how
-//! successive metric data points relate to each other in time.
+//! successive metric data points relate to each other in time, and the views that apply
+//! configuration driven changes (such as renaming) to every instrument.
 //!
 //! ## Feature Flags
 //!
+//! - `custom-push`: Enable the custom push exporter
 //! - `otlp`: Enable the OTLP exporter (gRPC)
+//! - `prometheus`: Enable the Prometheus exporter
+//! - `self-metrics`: Enable the metrics the OTLP exporter reports about its own exports
+//! - `statsd`: Enable the StatsD exporter
 //! - `stdout`: Enable the stdout exporter
+//! - `stdout-pipe`: Enable the OTLP JSON output of the stdout exporter to a named pipe
 //!
 //! If no export feature is enabled, the no-op exporter will be used as a fallback.
 
+#[cfg(feature = "otlp")]
+mod breaker;
+pub mod checkpoint;
+#[cfg(feature = "otlp")]
+mod header_file;
+#[cfg(feature = "otlp")]
+mod limiter;
+pub(crate) mod reader;
+#[cfg(feature = "otlp")]
+mod reporting;
+pub(crate) mod resource;
+#[cfg(feature = "prometheus")]
+mod scrape;
 mod selectors;
+#[cfg(all(feature = "otlp", feature = "self-metrics"))]
+mod self_metrics;
+pub(crate) mod transform;
+pub(crate) mod views;
+
+#[cfg(feature = "custom-push")]
+pub mod custom_push;
 
 #[cfg(feature = "otlp")]
 pub mod otlp_grpc;
 
+#[cfg(feature = "otlp")]
+pub mod snapshot;
+
+#[cfg(feature = "prometheus")]
+pub mod prom;
+
+#[cfg(feature = "statsd")]
+pub mod statsd;
+
+#[cfg(feature = "stdout-pipe")]
+mod pipe;
+
 #[cfg(feature = "stdout")]
 pub mod stdout;
 
 pub mod noop;
+
+use crate::{config::MetricsConfig, deferred};
+use opentelemetry::global;
+use opentelemetry_sdk::metrics::{MeterProviderBuilder, SdkMeterProvider};
+
+/// Creates the meter provider builder shared by every exporter.
+///
+/// The builder carries the views, the resource and the on-demand readers described by the
+/// configuration; exporters only add their own readers before building it.
+pub(crate) fn provider_builder(cfg: &MetricsConfig) -> MeterProviderBuilder {
+    let builder =
+        views::register(SdkMeterProvider::builder(), cfg).with_resource(resource::build(cfg));
+
+    #[cfg(feature = "otlp")]
+    let builder = match &cfg.otlp_snapshot {
+        Some(snapshot) => builder.with_reader(snapshot.register(cfg)),
+        None => builder,
+    };
+
+    let builder = match &cfg.checkpoint {
+        Some(checkpoint) => builder.with_reader(checkpoint.register(cfg)),
+        None => builder,
+    };
+
+    builder
+}
+
+/// Installs the provider as the global meter provider, shared by every exporter's install.
+///
+/// The metrics buffered by [`defer_until_installed`](crate::defer_until_installed) are replayed
+/// onto the provider, whichever install function installed it.
+pub(crate) fn install_global(provider: &SdkMeterProvider) {
+    global::set_meter_provider(provider.clone());
+    deferred::replay(provider);
+}
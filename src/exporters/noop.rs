@@ -6,12 +6,13 @@
 //!
 //! Provides a no-operation exporter for metrics when no other exporter is enabled.
 //!
-//! This module serves as a fallback when neither "stdout" nor "otlp" features are enabled.
-//! It provides a minimal implementation that creates a default SdkMeterProvider without
-//! any actual metrics collection or export functionality.
+//! This module serves as a fallback when none of the "otlp", "stdout" or "statsd" features are
+//! enabled. It provides a minimal implementation that creates a default SdkMeterProvider
+//! without any actual metrics collection or export functionality.
 
-use crate::errors::MetricsError;
+use crate::{LOG_TARGET, config::MetricsConfig, errors::MetricsError};
 use opentelemetry_sdk::metrics::SdkMeterProvider;
+use tracing::debug;
 
 /// Creates and installs a no-operation metrics provider.
 ///
@@ -23,5 +24,53 @@ use opentelemetry_sdk::metrics::SdkMeterProvider;
 /// * `Ok(SdkMeterProvider)` - A default meter provider that doesn't export metrics
 /// * `Err(MetricsError)` - This implementation should never return an error
 pub fn install() -> Result<SdkMeterProvider, MetricsError> {
-    Ok(SdkMeterProvider::default())
+    debug!(target: LOG_TARGET, exporter = "noop", "metrics::install noop exporter installed");
+
+    let provider = SdkMeterProvider::default();
+    super::install_global(&provider);
+
+    Ok(provider)
+}
+
+/// Creates and installs a no-operation metrics provider from an explicit configuration.
+///
+/// The configuration is ignored; this function exists so every exporter exposes the same
+/// installation entry points.
+///
+/// # Returns
+///
+/// * `Ok(SdkMeterProvider)` - A default meter provider that doesn't export metrics
+/// * `Err(MetricsError)` - This implementation should never return an error
+pub fn install_with_config(_cfg: &MetricsConfig) -> Result<SdkMeterProvider, MetricsError> {
+    install()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn install_logs_under_the_crate_target() {
+        let _lock = test_util::global_lock();
+        let logs = test_util::capture_logs(|| {
+            install().unwrap();
+        });
+
+        assert!(!logs.is_empty());
+        assert!(logs.iter().all(|log| log.target == LOG_TARGET));
+    }
+
+    #[test]
+    fn install_messages_name_the_metrics_install() {
+        let _lock = test_util::global_lock();
+        let logs = test_util::capture_logs(|| {
+            install().unwrap();
+        });
+
+        assert!(
+            logs.iter()
+                .all(|log| log.message.starts_with("metrics::install"))
+        );
+    }
 }
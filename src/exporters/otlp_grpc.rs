@@ -23,22 +23,140 @@
 //! cargo build --features otlp
 //! ```
 //!
+//! ## Temporality
+//!
+//! The exporter uses the OTLP exporter's default cumulative temporality. When delta temporality
+//! is used, the SDK drains every series on collection: a series that received no recordings
+//! during a cycle produces no data point at all, while a series that explicitly recorded `0`
+//! is still exported. Zero-value deltas therefore never need to be filtered out before export.
+//!
+//! ## Collector Compatibility
+//!
+//! Older collectors reject some of what recent SDKs send, failing the whole batch. The exports
+//! always use cumulative temporality, which they accept. Setting
+//! `MetricsConfig::collector_compat` restricts the rest of the exports to what they accept:
+//!
+//! * Exponential histograms chosen by the `aggregation_selector` are exported as explicit
+//!   bucket histograms with the default boundaries, and `exponential_max_size` and
+//!   `exponential_max_scale` are ignored
+//! * Every export carries the full resource, `full_resource_every` is ignored
+//!
+//! The crate sets no schema URL on the resource or the scopes, so there is nothing to adjust
+//! there. The mode applies to the views of the whole provider, so mirrors share the explicit
+//! bucket histograms.
+//!
 //! ## Authentication
 //!
-//! The exporter uses the header access key and access key from configuration for
-//! authentication with the OpenTelemetry collector.
+//! The exporter sends `MetricsConfig::access_key` in the `MetricsConfig::header_access_key`
+//! metadata header of every export, authenticating with the OpenTelemetry collector. The
+//! ambient configuration reads them from the `METRIC_` variables, falling back to the `TRACE_`
+//! ones, so metrics and traces can use different collectors and credentials.
+//!
+//! Secrets mounted as files are read from `MetricsConfig::header_value_file` instead of
+//! `access_key`. The file is read at install, and again every `header_refresh_interval` when
+//! it is set, so a rotated secret is picked up without restarting.
+//!
+//! Credentials that rotate, such as short-lived bearer tokens, can be injected on every export
+//! RPC by passing a tonic [`Interceptor`] to [`install_with_interceptor`].
+//!
+//! ## Transport Security
+//!
+//! The scheme of each endpoint selects the transport: `https://` endpoints are reached over
+//! TLS, verified against the certificate authorities of the platform, and `http://` endpoints
+//! in plaintext. Setting `MetricsConfig::tls` overrides the scheme for every endpoint.
+//!
+//! ## User Agent
+//!
+//! Setting `MetricsConfig::user_agent` prefixes the `user-agent` header of every export RPC,
+//! which lets the collector attribute traffic to services. The default tonic user agent is
+//! kept when it is empty.
+//!
+//! ## Authority
+//!
+//! Behind a service mesh, the exports may have to connect to a local proxy while addressing the
+//! collector by another name. Setting `MetricsConfig::authority` overrides the `:authority` of
+//! every export request, and the name the TLS certificate of the collector is verified against,
+//! while the connection is still opened to the endpoint.
+//!
+//! ## Stdout Mirror
+//!
+//! Setting `MetricsConfig::mirror_stdout` attaches a stdout reader next to the collector
+//! readers, so the full stream is also printed while it keeps being shipped, for example during
+//! an incident. It requires the `stdout` feature.
+//!
+//! ## Prometheus Mirror
+//!
+//! Setting `MetricsConfig::mirror_prometheus` also registers the Prometheus registry returned
+//! by [`prom::registry`](crate::exporters::prom::registry), for services scraped by Prometheus
+//! while shipping to a collector. It requires the `prometheus` feature. Prometheus flattens the
+//! resource into the `target_info` labels, so `MetricsConfig::resource_keys` can trim the
+//! resource it exposes without affecting the collectors.
+//!
+//! ## Error Reporting
+//!
+//! Failed exports are classified as [`ExportError`](crate::errors::ExportError) values and
+//! passed to the hook configured in `MetricsConfig::on_export_error`, so applications can
+//! distinguish timeouts, unreachable collectors and serialization failures.
+//!
+//! ## Redundancy
+//!
+//! Metrics can be sent to several independent collectors by listing them in
+//! `MetricsConfig::secondary_endpoints`. Every endpoint gets its own exporter and periodic
+//! reader attached to the same provider, so each collector receives the full stream and a
+//! collector being unreachable does not affect exports to the others.
+//!
+//! ## Circuit Breaker
+//!
+//! When `MetricsConfig::breaker_threshold` is set, every endpoint stops exporting after that
+//! many consecutive failures and probes the collector again once `breaker_cooldown` has
+//! elapsed. The breaker state of each endpoint is published to `MetricsConfig::export_health`.
+//!
+//! ## Connection Check
+//!
+//! The exporters connect lazily, so by default an unreachable collector only shows up as
+//! failed exports. When `MetricsConfig::require_connection` is set, installing opens a TCP
+//! connection to every endpoint first, within `exporter_timeout`, and fails with
+//! [`MetricsError::ExporterProviderError`] if one cannot be reached. The same check is
+//! available at any time with [`ping`], for health endpoints.
+//!
+//! ## Self Metrics
+//!
+//! With the `self-metrics` feature, the duration of every export RPC is recorded in the
+//! `otel_metric_export_duration_seconds` histogram, per endpoint and outcome. Its name is
+//! prefixed with `MetricsConfig::self_metrics_prefix`.
+//!
+//! ## Backpressure
+//!
+//! At most `MetricsConfig::max_concurrent_exports` exports (default `1`) are in flight at the
+//! same time. An export started while the limit is reached waits for a running one to finish,
+//! which bounds the memory held by pending batches when the collector is slow.
 
-use crate::errors::MetricsError;
-use configs::{app::AppConfigs, otlp::OTLPConfigs};
-use opentelemetry::{KeyValue, global};
+#[cfg(feature = "self-metrics")]
+use super::self_metrics::SelfMetricsExporter;
+use super::{
+    breaker::BreakerExporter, header_file::HeaderFile, limiter::LimitedExporter, reader::periodic,
+    reporting::ReportingExporter, transform::TransformExporter,
+};
+use crate::{
+    LOG_TARGET,
+    config::{ExporterKind, MetricsConfig},
+    errors::MetricsError,
+};
 use opentelemetry_otlp::{
     Compression, MetricExporter, Protocol, WithExportConfig, WithTonicConfig,
 };
-use opentelemetry_sdk::{
-    Resource,
-    metrics::{PeriodicReader, SdkMeterProvider},
+use opentelemetry_sdk::metrics::{MeterProviderBuilder, SdkMeterProvider, reader::MetricReader};
+use std::{
+    iter,
+    net::{TcpStream, ToSocketAddrs},
+};
+use tonic::{
+    Request, Status,
+    metadata::{MetadataKey, MetadataMap, MetadataValue},
+    service::Interceptor,
+    transport::{Channel, ClientTlsConfig, Uri},
 };
-use tracing::{error, info};
+use tracing::{debug, error, warn};
 
 /// Creates and installs an OTLP metrics exporter.
 ///
@@ -46,14 +164,6 @@ use tracing::{error, info};
 /// exporter based on the application configuration. The exporter sends metrics
 /// to an OpenTelemetry collector via gRPC with proper authentication headers.
 ///
-/// # Type Parameters
-///
-/// * `T` - A type implementing `DynamicConfigs` for application-specific configuration
-///
-/// # Parameters
-///
-/// * `cfg` - The application configuration containing metrics settings
-///
 /// # Returns
 ///
 /// * `Ok(SdkMeterProvider)` - The configured meter provider
@@ -61,7 +171,9 @@ use tracing::{error, info};
 ///
 /// # Configuration
 ///
-/// The OTLP exporter is configured using the application's OTLP settings, including endpoint, timeout, and authentication headers. See the `OTLPConfigs` struct for details.
+/// The configuration is read with [`MetricsConfig::load`], which takes the endpoint, timeout,
+/// interval and authentication headers from the environment. Use [`install_with_config`] to
+/// pass an explicit [`MetricsConfig`] instead.
 ///
 /// # Example
 ///
@@ -71,49 +183,783 @@ use tracing::{error, info};
 /// ```
 ///
 pub fn install() -> Result<SdkMeterProvider, MetricsError> {
-    let app_cfgs = AppConfigs::new();
-    let otlp_cfgs = OTLPConfigs::new();
+    install_with_config(&MetricsConfig::load())
+}
+
+/// Creates and installs an OTLP metrics exporter from an explicit configuration.
+///
+/// Behaves like [`install`], but reads the endpoint, timeout, interval and resource
+/// attributes from the given [`MetricsConfig`] instead of the ambient Ruskit configuration.
+///
+/// # Parameters
+///
+/// * `cfg` - The metrics configuration
+///
+/// # Returns
+///
+/// * `Ok(SdkMeterProvider)` - The configured meter provider
+/// * `Err(MetricsError)` - If an error occurred during exporter setup
+pub fn install_with_config(cfg: &MetricsConfig) -> Result<SdkMeterProvider, MetricsError> {
+    install_with_interceptor(cfg, passthrough)
+}
+
+/// Creates and installs an OTLP metrics exporter that calls an interceptor on every export RPC.
+///
+/// The interceptor receives each outbound request before it is sent to the collector, which
+/// allows injecting metadata that changes over time, for example a freshly issued
+/// `authorization` header.
+///
+/// # Type Parameters
+///
+/// * `I` - A tonic interceptor, either a type implementing `Interceptor` or a closure
+///   `FnMut(Request<()>) -> Result<Request<()>, Status>`
+///
+/// # Parameters
+///
+/// * `cfg` - The metrics configuration
+/// * `interceptor` - The interceptor called on every export request
+///
+/// # Returns
+///
+/// * `Ok(SdkMeterProvider)` - The configured meter provider
+/// * `Err(MetricsError)` - If an error occurred during exporter setup
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use metrics::{config::MetricsConfig, exporters::otlp_grpc};
+/// use tonic::Request;
+///
+/// let provider = otlp_grpc::install_with_interceptor(
+///     &MetricsConfig::load(),
+///     |mut req: Request<()>| {
+///         let token = "Bearer fresh-token".parse().unwrap();
+///         req.metadata_mut().insert("authorization", token);
+///         Ok(req)
+///     },
+/// )
+/// .unwrap();
+/// ```
+pub fn install_with_interceptor<I>(
+    cfg: &MetricsConfig,
+    interceptor: I,
+) -> Result<SdkMeterProvider, MetricsError>
+where
+    I: Interceptor + Clone + Send + Sync + 'static,
+{
+    let interceptor = with_header_file(interceptor, HeaderFile::load(cfg)?);
+    let mut builder = super::provider_builder(cfg);
+
+    for endpoint in iter::once(&cfg.endpoint).chain(&cfg.secondary_endpoints) {
+        if cfg.require_connection {
+            probe(cfg, endpoint)?;
+        }
+
+        let reader = build_reader(cfg, endpoint, interceptor.clone())?;
+        builder = builder.with_reader(reader);
+    }
+
+    if cfg.mirror_stdout {
+        builder = with_stdout_mirror(builder, cfg);
+    }
+
+    if cfg.mirror_prometheus {
+        builder = with_prometheus_mirror(builder, cfg);
+    }
+
+    let provider = builder.build();
+
+    super::install_global(&provider);
+
+    debug!(
+        target: LOG_TARGET,
+        exporter = "otlp",
+        endpoint = %cfg.endpoint,
+        secondary_endpoints = ?cfg.secondary_endpoints,
+        mirror_stdout = cfg.mirror_stdout,
+        mirror_prometheus = cfg.mirror_prometheus,
+        interval = ?cfg.exporter_interval,
+        timeout = ?cfg.exporter_timeout,
+        "metrics::install otlp exporter installed"
+    );
+
+    Ok(provider)
+}
+
+/// Attaches a stdout reader receiving the same stream as the collectors.
+#[cfg(feature = "stdout")]
+fn with_stdout_mirror(builder: MeterProviderBuilder, cfg: &MetricsConfig) -> MeterProviderBuilder {
+    builder.with_reader(super::stdout::pretty_reader(cfg))
+}
+
+/// Stdout mirroring requires the stdout exporter, which was not compiled in.
+#[cfg(not(feature = "stdout"))]
+fn with_stdout_mirror(builder: MeterProviderBuilder, _cfg: &MetricsConfig) -> MeterProviderBuilder {
+    warn!(
+        target: LOG_TARGET,
+        "mirror_stdout requires the stdout feature, metrics are not mirrored"
+    );
+    builder
+}
+
+/// Attaches the Prometheus registry, scraped with the same stream as the collectors receive.
+#[cfg(feature = "prometheus")]
+fn with_prometheus_mirror(
+    builder: MeterProviderBuilder,
+    cfg: &MetricsConfig,
+) -> MeterProviderBuilder {
+    builder.with_reader(super::prom::register(cfg))
+}
+
+/// Prometheus mirroring requires the Prometheus exporter, which was not compiled in.
+#[cfg(not(feature = "prometheus"))]
+fn with_prometheus_mirror(
+    builder: MeterProviderBuilder,
+    _cfg: &MetricsConfig,
+) -> MeterProviderBuilder {
+    warn!(
+        target: LOG_TARGET,
+        "mirror_prometheus requires the prometheus feature, metrics are not mirrored"
+    );
+    builder
+}
+
+/// Calls `interceptor`, then inserts the header read from the header file when configured.
+fn with_header_file<I>(
+    mut interceptor: I,
+    mut header: Option<HeaderFile>,
+) -> impl Interceptor + Clone + Send + Sync + 'static
+where
+    I: Interceptor + Clone + Send + Sync + 'static,
+{
+    move |request: Request<()>| {
+        let request = interceptor.call(request)?;
+        match &mut header {
+            Some(header) => header.call(request),
+            None => Ok(request),
+        }
+    }
+}
+
+/// Builds the metadata authenticating the exports, empty when no access key is configured.
+///
+/// The header is sent by the header file interceptor instead when `header_value_file` is set.
+fn metadata(cfg: &MetricsConfig) -> Result<MetadataMap, MetricsError> {
+    let mut metadata = MetadataMap::new();
+
+    if cfg.header_access_key.is_empty()
+        || cfg.access_key.is_empty()
+        || cfg.header_value_file.is_some()
+    {
+        return Ok(metadata);
+    }
+
+    let key = MetadataKey::from_bytes(cfg.header_access_key.as_bytes());
+    let value = MetadataValue::try_from(cfg.access_key.as_str());
+
+    match (key, value) {
+        (Ok(key), Ok(value)) => {
+            metadata.insert(key, value);
+            Ok(metadata)
+        }
+        _ => {
+            error!(
+                target: LOG_TARGET,
+                header = %cfg.header_access_key,
+                "invalid metrics access key header"
+            );
+            Err(MetricsError::InvalidConfigError(format!(
+                "invalid access key header: {}",
+                cfg.header_access_key
+            )))
+        }
+    }
+}
+
+/// Builds the channel to `endpoint` when the configuration requires a custom one.
+///
+/// The exporter creates its own channel otherwise, which does not allow setting the user
+/// agent or the authority.
+fn channel(cfg: &MetricsConfig, endpoint: &str) -> Result<Option<Channel>, MetricsError> {
+    if cfg.user_agent.is_empty() && cfg.authority.is_empty() {
+        return Ok(None);
+    }
+
+    let channel = origin(cfg, endpoint).and_then(|origin| {
+        let mut endpoint_builder =
+            Channel::from_shared(endpoint.to_string()).map_err(|err| err.to_string())?;
+
+        if !cfg.user_agent.is_empty() {
+            endpoint_builder = endpoint_builder
+                .user_agent(cfg.user_agent.as_str())
+                .map_err(|err| err.to_string())?;
+        }
+
+        if cfg.uses_tls(endpoint) {
+            let mut tls = tls_config();
+            if let Some(host) = origin.as_ref().and_then(Uri::host) {
+                tls = tls.domain_name(host);
+            }
+            endpoint_builder = endpoint_builder
+                .tls_config(tls)
+                .map_err(|err| err.to_string())?;
+        }
+
+        match origin {
+            Some(origin) => Ok(endpoint_builder.origin(origin)),
+            None => Ok(endpoint_builder),
+        }
+    });
+
+    match channel {
+        Ok(e) => Ok(Some(e.timeout(cfg.exporter_timeout).connect_lazy())),
+        Err(err) => {
+            error!(
+                target: LOG_TARGET,
+                error = %err,
+                endpoint = endpoint,
+                user_agent = %cfg.user_agent,
+                authority = %cfg.authority,
+                "failure to create exporter channel"
+            );
+            Err(MetricsError::ExporterProviderError)
+        }
+    }
+}
+
+/// Builds the origin overriding the `:authority` of the requests to `endpoint`, if configured.
+fn origin(cfg: &MetricsConfig, endpoint: &str) -> Result<Option<Uri>, String> {
+    if cfg.authority.is_empty() {
+        return Ok(None);
+    }
 
-    let exporter = match MetricExporter::builder()
+    let scheme = if cfg.uses_tls(endpoint) {
+        "https"
+    } else {
+        "http"
+    };
+    format!("{scheme}://{}", cfg.authority)
+        .parse::<Uri>()
+        .map(Some)
+        .map_err(|err| format!("invalid authority {}: {err}", cfg.authority))
+}
+
+/// Checks that every configured collector is reachable.
+///
+/// Installing only checks the collectors once, with `require_connection`. This function can
+/// be called at any time, for example from the health endpoint of the service, to know whether
+/// the collectors are reachable independently of the export cycles. It opens a TCP connection
+/// to the primary and secondary endpoints within `exporter_timeout`, without sending data, so
+/// it confirms the network path but not that the collector accepts the exports.
+///
+/// # Parameters
+///
+/// * `cfg` - The configuration the exporter was installed with
+///
+/// # Returns
+///
+/// * `Ok(())` - If every endpoint accepted a connection
+/// * `Err(MetricsError::ExporterProviderError)` - If an endpoint could not be reached
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use metrics::{config::MetricsConfig, exporters::otlp_grpc};
+///
+/// let cfg = MetricsConfig::load();
+/// let provider = otlp_grpc::install_with_config(&cfg).unwrap();
+///
+/// // in the health handler
+/// let healthy = otlp_grpc::ping(&cfg).is_ok();
+/// ```
+pub fn ping(cfg: &MetricsConfig) -> Result<(), MetricsError> {
+    iter::once(&cfg.endpoint)
+        .chain(&cfg.secondary_endpoints)
+        .try_for_each(|endpoint| probe(cfg, endpoint))
+}
+
+/// Checks that a TCP connection to the collector can be opened within the export timeout.
+fn probe(cfg: &MetricsConfig, endpoint: &str) -> Result<(), MetricsError> {
+    let connected = endpoint
+        .parse::<Uri>()
+        .map_err(|err| err.to_string())
+        .and_then(|uri| {
+            let host = uri.host().ok_or("endpoint has no host")?.to_string();
+            let port = uri
+                .port_u16()
+                .unwrap_or(if cfg.uses_tls(endpoint) { 443 } else { 80 });
+            (host.as_str(), port)
+                .to_socket_addrs()
+                .map_err(|err| err.to_string())?
+                .find_map(|addr| TcpStream::connect_timeout(&addr, cfg.exporter_timeout).ok())
+                .ok_or_else(|| "connection refused or timed out".to_string())
+        });
+
+    match connected {
+        Ok(_) => {
+            debug!(target: LOG_TARGET, endpoint = endpoint, "collector reachable");
+            Ok(())
+        }
+        Err(err) => {
+            error!(
+                target: LOG_TARGET,
+                error = %err,
+                endpoint = endpoint,
+                "collector unreachable"
+            );
+            Err(MetricsError::ExporterProviderError)
+        }
+    }
+}
+
+/// TLS settings trusting the certificate authorities of the platform.
+fn tls_config() -> ClientTlsConfig {
+    ClientTlsConfig::new().with_native_roots()
+}
+
+/// Builds the periodic reader exporting to a single collector endpoint.
+fn build_reader<I>(
+    cfg: &MetricsConfig,
+    endpoint: &str,
+    interceptor: I,
+) -> Result<impl MetricReader, MetricsError>
+where
+    I: Interceptor + Clone + Send + Sync + 'static,
+{
+    let mut builder = MetricExporter::builder()
         .with_tonic()
         .with_protocol(Protocol::Grpc)
-        .with_timeout(otlp_cfgs.exporter_timeout)
-        .with_endpoint(&otlp_cfgs.endpoint)
+        .with_timeout(cfg.exporter_timeout)
+        .with_endpoint(endpoint)
         .with_compression(Compression::Gzip)
-        .build()
-    {
+        .with_interceptor(interceptor)
+        .with_metadata(metadata(cfg)?);
+
+    if cfg.uses_tls(endpoint) {
+        builder = builder.with_tls_config(tls_config());
+    }
+
+    if let Some(channel) = channel(cfg, endpoint)? {
+        builder = builder.with_channel(channel);
+    }
+
+    let exporter = match builder.build() {
         Ok(p) => Ok(p),
         Err(err) => {
             error!(
+                target: LOG_TARGET,
                 error = err.to_string(),
+                endpoint = endpoint,
                 "failure to create exporter provider"
             );
             Err(MetricsError::ExporterProviderError)
         }
     }?;
 
-    let reader = PeriodicReader::builder(exporter)
-        .with_interval(otlp_cfgs.exporter_interval)
-        .build();
-
-    let provider = SdkMeterProvider::builder()
-        .with_reader(reader)
-        .with_resource(
-            Resource::builder()
-                .with_service_name(app_cfgs.name.clone())
-                .with_attribute(KeyValue::new(
-                    "service.namespace",
-                    format!("{}", app_cfgs.namespace),
-                ))
-                .with_attribute(KeyValue::new("environment", format!("{}", app_cfgs.env)))
-                .with_attribute(KeyValue::new("library.language", "rust"))
-                .build(),
-        )
-        .build();
-
-    global::set_meter_provider(provider.clone());
-
-    info!("traces::install otlp metric installed");
+    #[cfg(feature = "self-metrics")]
+    let exporter = SelfMetricsExporter::new(exporter, endpoint, &cfg.self_metrics_prefix);
+    let exporter = TransformExporter::for_exporter(exporter, cfg, ExporterKind::Otlp);
+    let exporter = LimitedExporter::new(exporter, cfg.max_concurrent_exports);
+    let exporter = BreakerExporter::new(
+        exporter,
+        cfg.breaker_threshold,
+        cfg.breaker_cooldown,
+        endpoint,
+        cfg.export_health.clone(),
+    );
+    let exporter = ReportingExporter::new(exporter, cfg.on_export_error.clone());
 
-    Ok(provider)
+    Ok(periodic(exporter, cfg))
+}
+
+/// Interceptor used when no custom interceptor is provided, forwards the request unchanged.
+fn passthrough(request: Request<()>) -> Result<Request<()>, Status> {
+    Ok(request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use opentelemetry::metrics::MeterProvider;
+    use std::{
+        net::TcpListener,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    /// Returns an endpoint on a port nothing listens on.
+    fn closed_endpoint() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+
+    #[test]
+    fn custom_interceptor_is_called_on_every_request() {
+        let mut interceptor = with_header_file(
+            |mut request: Request<()>| -> Result<Request<()>, Status> {
+                request
+                    .metadata_mut()
+                    .insert("authorization", "Bearer token".parse().unwrap());
+                Ok(request)
+            },
+            None,
+        );
+
+        let request = interceptor.call(Request::new(())).unwrap();
+
+        assert_eq!(
+            request.metadata().get("authorization").unwrap(),
+            "Bearer token"
+        );
+    }
+
+    #[test]
+    fn custom_interceptor_can_reject_requests() {
+        let mut interceptor = with_header_file(
+            |_: Request<()>| -> Result<Request<()>, Status> {
+                Err(Status::unauthenticated("no token"))
+            },
+            None,
+        );
+
+        let status = interceptor.call(Request::new(())).unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
+
+    #[test]
+    fn collector_receives_the_header_of_the_interceptor() {
+        let _lock = test_util::global_lock();
+        let collector = test_util::MockCollector::start();
+        let cfg = MetricsConfig {
+            endpoint: collector.endpoint(),
+            exporter_interval: Duration::from_secs(3600),
+            ..MetricsConfig::default()
+        };
+        let token = Arc::new(Mutex::new("Bearer token-1".to_string()));
+        let interceptor = {
+            let token = token.clone();
+            move |mut request: Request<()>| -> Result<Request<()>, Status> {
+                let value = token.lock().unwrap().parse().unwrap();
+                request.metadata_mut().insert("authorization", value);
+                Ok(request)
+            }
+        };
+        let provider = {
+            let _runtime = collector.enter();
+            install_with_interceptor(&cfg, interceptor).unwrap()
+        };
+        let requests = provider.meter("test").u64_counter("requests").build();
+        let last_header = || {
+            let received = collector.received();
+            let last = received.last().expect("an export request");
+            last.metadata
+                .get("authorization")
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .to_string()
+        };
+
+        requests.add(1, &[]);
+        provider.force_flush().unwrap();
+        assert_eq!(last_header(), "Bearer token-1");
+
+        // the token rotates, the next export carries the fresh one
+        *token.lock().unwrap() = "Bearer token-2".to_string();
+        requests.add(1, &[]);
+        provider.force_flush().unwrap();
+        assert_eq!(last_header(), "Bearer token-2");
+
+        provider.shutdown().unwrap();
+    }
+
+    #[test]
+    fn collector_receives_the_refreshed_header_file_value() {
+        use opentelemetry::metrics::MeterProvider;
+        use std::{fs, thread};
+
+        let _lock = test_util::global_lock();
+        let collector = test_util::MockCollector::start();
+        let file = test_util::TempFile::new("token", "token-1\n");
+        let cfg = MetricsConfig {
+            endpoint: collector.endpoint(),
+            exporter_interval: Duration::from_secs(3600),
+            header_access_key: "authorization".to_string(),
+            header_value_file: Some(file.path().clone()),
+            header_refresh_interval: Duration::from_millis(1),
+            ..MetricsConfig::default()
+        };
+        let provider = {
+            let _runtime = collector.enter();
+            install_with_config(&cfg).unwrap()
+        };
+        let requests = provider.meter("test").u64_counter("requests").build();
+        let last_header = || {
+            let received = collector.received();
+            let last = received.last().expect("an export request");
+            last.metadata
+                .get("authorization")
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .to_string()
+        };
+
+        requests.add(1, &[]);
+        provider.force_flush().unwrap();
+        assert_eq!(last_header(), "token-1");
+
+        fs::write(file.path(), "token-2").unwrap();
+        thread::sleep(Duration::from_millis(5));
+        requests.add(1, &[]);
+        provider.force_flush().unwrap();
+        assert_eq!(last_header(), "token-2");
+
+        provider.shutdown().unwrap();
+    }
+
+    #[test]
+    fn install_connects_to_every_secondary_endpoint() {
+        let collector = TcpListener::bind("127.0.0.1:0").unwrap();
+        let cfg = MetricsConfig {
+            endpoint: format!("http://{}", collector.local_addr().unwrap()),
+            secondary_endpoints: vec![closed_endpoint()],
+            require_connection: true,
+            exporter_timeout: Duration::from_secs(1),
+            ..MetricsConfig::default()
+        };
+
+        // the exporters connect lazily, which needs a runtime to spawn the channel on
+        let result = test_util::block_on(async { install_with_config(&cfg) });
+
+        assert_eq!(result.unwrap_err(), MetricsError::ExporterProviderError);
+    }
+
+    #[test]
+    fn every_endpoint_receives_the_batch() {
+        let _lock = test_util::global_lock();
+        let primary = test_util::MockCollector::start();
+        let secondary = test_util::MockCollector::start();
+        let cfg = MetricsConfig {
+            endpoint: primary.endpoint(),
+            secondary_endpoints: vec![secondary.endpoint()],
+            exporter_interval: Duration::from_secs(3600),
+            ..MetricsConfig::default()
+        };
+        let provider = {
+            let _runtime = primary.enter();
+            install_with_config(&cfg).unwrap()
+        };
+        provider
+            .meter("test")
+            .u64_counter("requests")
+            .build()
+            .add(1, &[]);
+
+        provider.force_flush().unwrap();
+
+        assert_eq!(primary.metric_names(), vec!["requests".to_string()]);
+        assert_eq!(secondary.metric_names(), vec!["requests".to_string()]);
+        provider.shutdown().unwrap();
+    }
+
+    #[test]
+    fn access_key_is_sent_under_the_configured_header() {
+        let cfg = MetricsConfig {
+            header_access_key: "x-api-key".to_string(),
+            access_key: "secret".to_string(),
+            ..MetricsConfig::default()
+        };
+
+        let metadata = metadata(&cfg).unwrap();
+
+        assert_eq!(metadata.get("x-api-key").unwrap(), "secret");
+    }
+
+    #[test]
+    fn access_key_is_omitted_when_unset() {
+        let cfg = MetricsConfig {
+            header_access_key: "x-api-key".to_string(),
+            ..MetricsConfig::default()
+        };
+
+        assert!(metadata(&cfg).unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_access_key_header_is_rejected() {
+        let cfg = MetricsConfig {
+            header_access_key: "x api key".to_string(),
+            access_key: "secret".to_string(),
+            ..MetricsConfig::default()
+        };
+
+        assert!(matches!(
+            metadata(&cfg),
+            Err(MetricsError::InvalidConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn custom_channel_is_only_built_for_a_user_agent() {
+        let endpoint = closed_endpoint();
+        let cfg = MetricsConfig {
+            user_agent: "checkout/1.0".to_string(),
+            ..MetricsConfig::default()
+        };
+
+        // the channel connects lazily, which needs a runtime to spawn it on
+        test_util::block_on(async {
+            assert!(channel(&cfg, &endpoint).unwrap().is_some());
+            assert!(
+                channel(&MetricsConfig::default(), &endpoint)
+                    .unwrap()
+                    .is_none()
+            );
+        });
+    }
+
+    #[test]
+    fn invalid_user_agent_is_rejected() {
+        let cfg = MetricsConfig {
+            user_agent: "checkout\n1.0".to_string(),
+            ..MetricsConfig::default()
+        };
+
+        let result = test_util::block_on(async { channel(&cfg, &closed_endpoint()) });
+
+        assert_eq!(result.unwrap_err(), MetricsError::ExporterProviderError);
+    }
+
+    #[cfg(feature = "stdout")]
+    #[test]
+    fn stdout_mirror_receives_the_stream() {
+        use opentelemetry::metrics::MeterProvider;
+
+        let cfg = MetricsConfig {
+            exporter_interval: Duration::from_secs(3600),
+            ..MetricsConfig::default()
+        };
+        let reader = crate::exporters::reader::SharedReader::new();
+        let provider = with_stdout_mirror(crate::exporters::provider_builder(&cfg), &cfg)
+            .with_reader(reader.clone())
+            .build();
+        provider
+            .meter("test")
+            .u64_counter("requests")
+            .build()
+            .add(1, &[]);
+
+        // the mirror exports the same data the other readers collect
+        assert!(provider.force_flush().is_ok());
+        assert!(test_util::metric(&test_util::collect(&reader), "requests").is_some());
+        assert!(provider.shutdown().is_ok());
+    }
+
+    #[cfg(not(feature = "stdout"))]
+    #[test]
+    fn stdout_mirror_warns_without_the_stdout_feature() {
+        let cfg = MetricsConfig::default();
+
+        let logs = test_util::capture_logs(|| {
+            with_stdout_mirror(crate::exporters::provider_builder(&cfg), &cfg);
+        });
+
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].target, LOG_TARGET);
+    }
+
+    #[cfg(feature = "prometheus")]
+    #[test]
+    fn prometheus_mirror_exposes_the_stream() {
+        use opentelemetry::metrics::MeterProvider;
+
+        let _lock = test_util::global_lock();
+        let cfg = MetricsConfig::default();
+        let provider =
+            with_prometheus_mirror(crate::exporters::provider_builder(&cfg), &cfg).build();
+        provider
+            .meter("test")
+            .u64_counter("requests")
+            .build()
+            .add(1, &[]);
+
+        let text = crate::exporters::prom::registry()
+            .unwrap()
+            .gather()
+            .unwrap();
+
+        assert!(text.contains("requests_total 1\n"));
+    }
+
+    #[test]
+    fn probe_checks_the_collector_is_reachable() {
+        let collector = TcpListener::bind("127.0.0.1:0").unwrap();
+        let cfg = MetricsConfig {
+            exporter_timeout: Duration::from_secs(1),
+            ..MetricsConfig::default()
+        };
+
+        let reachable = format!("http://{}", collector.local_addr().unwrap());
+        assert!(probe(&cfg, &reachable).is_ok());
+        assert_eq!(
+            probe(&cfg, &closed_endpoint()).unwrap_err(),
+            MetricsError::ExporterProviderError
+        );
+        assert_eq!(
+            probe(&cfg, "not a uri").unwrap_err(),
+            MetricsError::ExporterProviderError
+        );
+    }
+
+    #[test]
+    fn authority_overrides_the_origin_of_the_requests() {
+        let cfg = MetricsConfig {
+            authority: "collector.internal:4317".to_string(),
+            ..MetricsConfig::default()
+        };
+
+        let plain = origin(&cfg, "http://10.0.0.1:4317").unwrap().unwrap();
+        let tls = origin(&cfg, "https://10.0.0.1:4317").unwrap().unwrap();
+
+        assert_eq!(plain.to_string(), "http://collector.internal:4317/");
+        assert_eq!(tls.scheme_str(), Some("https"));
+        assert_eq!(tls.host(), Some("collector.internal"));
+        assert!(
+            origin(&MetricsConfig::default(), "http://10.0.0.1:4317")
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn invalid_authority_fails_the_channel() {
+        let cfg = MetricsConfig {
+            authority: "bad authority".to_string(),
+            ..MetricsConfig::default()
+        };
+
+        let result = test_util::block_on(async { channel(&cfg, &closed_endpoint()) });
+
+        assert_eq!(result.unwrap_err(), MetricsError::ExporterProviderError);
+    }
+
+    #[test]
+    fn ping_checks_every_endpoint() {
+        let primary = TcpListener::bind("127.0.0.1:0").unwrap();
+        let secondary = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut cfg = MetricsConfig {
+            endpoint: format!("http://{}", primary.local_addr().unwrap()),
+            secondary_endpoints: vec![format!("http://{}", secondary.local_addr().unwrap())],
+            exporter_timeout: Duration::from_secs(1),
+            ..MetricsConfig::default()
+        };
+
+        assert!(ping(&cfg).is_ok());
+
+        cfg.secondary_endpoints.push(closed_endpoint());
+        assert_eq!(ping(&cfg).unwrap_err(), MetricsError::ExporterProviderError);
+    }
 }
@@ -0,0 +1,227 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Pipe Output
+//!
+//! Writes metrics as OTLP JSON lines to a named pipe or a file.
+//!
+//! This module is conditionally compiled when the "stdout-pipe" feature is enabled. It serves
+//! sandboxed environments that cannot open network connections but can write to a FIFO read by
+//! a sidecar, which forwards the lines to a collector. Every export writes one line holding an
+//! `ExportMetricsServiceRequest` in the OTLP JSON encoding.
+//!
+//! The stdout exporter switches to this output when `MetricsConfig::pipe_path` is set. The path
+//! is opened on the first export rather than at install, and without blocking on Unix, so the
+//! exports never wait for the sidecar: while no sidecar has the FIFO open for reading, the
+//! lines are dropped and the path is opened again on the next export. When the sidecar goes
+//! away or stops reading until the pipe is full, the write fails, the handle is dropped and the
+//! path is opened again on the next export; a line that only partially fit is truncated, and
+//! the sidecar should skip lines that are not valid JSON. A path that is not a FIFO is created
+//! if needed and appended to.
+
+use super::{reader::periodic, transform::TransformExporter};
+use crate::{
+    LOG_TARGET,
+    config::{ExporterKind, MetricsConfig},
+};
+use opentelemetry_proto::tonic::collector::metrics::v1::ExportMetricsServiceRequest;
+use opentelemetry_sdk::{
+    error::{OTelSdkError, OTelSdkResult},
+    metrics::{
+        Temporality, data::ResourceMetrics, exporter::PushMetricExporter, reader::MetricReader,
+    },
+};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+use tracing::debug;
+
+/// Builds the periodic reader writing OTLP JSON lines to `path`.
+pub(crate) fn reader(cfg: &MetricsConfig, path: &Path) -> impl MetricReader {
+    let exporter = TransformExporter::for_exporter(
+        PipeExporter::new(path.to_path_buf()),
+        cfg,
+        ExporterKind::Stdout,
+    );
+    periodic(exporter, cfg)
+}
+
+/// # PipeExporter
+///
+/// Exporter appending one OTLP JSON line per export to a path, opened on demand.
+#[derive(Debug)]
+struct PipeExporter {
+    path: PathBuf,
+    file: Mutex<Option<File>>,
+    is_shutdown: AtomicBool,
+}
+
+impl PipeExporter {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            file: Mutex::new(None),
+            is_shutdown: AtomicBool::new(false),
+        }
+    }
+
+    fn write_line(&self, line: &[u8]) -> io::Result<()> {
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+
+        if file.is_none() {
+            match open(&self.path) {
+                Ok(opened) => *file = Some(opened),
+                Err(err) if is_unread_fifo(&err) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        path = %self.path.display(),
+                        "metrics line dropped, the pipe has no reader"
+                    );
+                    return Ok(());
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        let result = file.as_mut().map_or(Ok(()), |f| f.write_all(line));
+        if result.is_err() {
+            // the reader of the pipe is gone or the pipe is full, reopen on the next export
+            *file = None;
+        }
+        result
+    }
+}
+
+/// Opens `path` for appending, without blocking on a FIFO nobody reads.
+fn open(path: &Path) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    options.custom_flags(libc::O_NONBLOCK);
+    options.open(path)
+}
+
+/// Returns whether opening failed because the path is a FIFO without a reader.
+#[cfg(unix)]
+fn is_unread_fifo(err: &io::Error) -> bool {
+    err.raw_os_error() == Some(libc::ENXIO)
+}
+
+#[cfg(not(unix))]
+fn is_unread_fifo(_err: &io::Error) -> bool {
+    false
+}
+
+impl PushMetricExporter for PipeExporter {
+    async fn export(&self, metrics: &mut ResourceMetrics) -> OTelSdkResult {
+        if self.is_shutdown.load(Ordering::SeqCst) {
+            return Err(OTelSdkError::AlreadyShutdown);
+        }
+
+        let request = ExportMetricsServiceRequest::from(&*metrics);
+        let mut line = serde_json::to_vec(&request)
+            .map_err(|err| OTelSdkError::InternalFailure(format!("serialize: {err}")))?;
+        line.push(b'\n');
+
+        self.write_line(&line)
+            .map_err(|err| OTelSdkError::InternalFailure(err.to_string()))
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        Ok(())
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        self.is_shutdown.store(true, Ordering::SeqCst);
+        *self.file.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        Ok(())
+    }
+
+    fn temporality(&self) -> Temporality {
+        Temporality::Cumulative
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, TempFile};
+    use opentelemetry::metrics::MeterProvider;
+    use std::fs;
+
+    #[test]
+    fn every_export_appends_an_otlp_json_line() {
+        let file = TempFile::new("metrics.jsonl", "");
+        let exporter = PipeExporter::new(file.path().clone());
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        provider
+            .meter("test")
+            .u64_counter("requests")
+            .build()
+            .add(1, &[]);
+
+        for _ in 0..2 {
+            let mut rm = test_util::collect(&reader);
+            test_util::block_on(exporter.export(&mut rm)).unwrap();
+        }
+
+        let content = fs::read_to_string(file.path()).unwrap();
+        let lines = content.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        for line in lines {
+            let request: ExportMetricsServiceRequest = serde_json::from_str(line).unwrap();
+            let metrics = &request.resource_metrics[0].scope_metrics[0].metrics;
+            assert_eq!(metrics[0].name, "requests");
+        }
+    }
+
+    #[test]
+    fn export_fails_after_shutdown() {
+        let file = TempFile::new("metrics.jsonl", "");
+        let exporter = PipeExporter::new(file.path().clone());
+
+        exporter.shutdown().unwrap();
+
+        assert!(matches!(
+            test_util::block_on(exporter.export(&mut test_util::empty_metrics())),
+            Err(OTelSdkError::AlreadyShutdown)
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fifo_without_reader_does_not_block() {
+        use std::{ffi::CString, io::Read, os::unix::ffi::OsStrExt};
+
+        let fifo = TempFile::new("metrics.fifo", "");
+        fs::remove_file(fifo.path()).unwrap();
+        let path = CString::new(fifo.path().as_os_str().as_bytes()).unwrap();
+        // SAFETY: the path is a valid NUL terminated string
+        assert_eq!(unsafe { libc::mkfifo(path.as_ptr(), 0o600) }, 0);
+        let exporter = PipeExporter::new(fifo.path().clone());
+
+        // no reader yet, the line is dropped instead of blocking the export
+        test_util::block_on(exporter.export(&mut test_util::empty_metrics())).unwrap();
+        assert!(exporter.file.lock().unwrap().is_none());
+
+        let mut reader = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(fifo.path())
+            .unwrap();
+        test_util::block_on(exporter.export(&mut test_util::empty_metrics())).unwrap();
+
+        let mut buf = [0; 4096];
+        let len = reader.read(&mut buf).unwrap();
+        assert_eq!(buf[..len].iter().filter(|b| **b == b'\n').count(), 1);
+    }
+}
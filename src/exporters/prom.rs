@@ -0,0 +1,1107 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Prometheus Metrics Exporter
+//!
+//! Provides a pull exporter rendering metrics in the Prometheus text exposition format.
+//!
+//! This module is conditionally compiled when the "prometheus" feature is enabled. Instead of
+//! depending on `opentelemetry-prometheus`, which regularly lags behind the SDK release, the
+//! metrics are collected from a manual reader on every scrape and serialized by [`encode`].
+//!
+//! ## Translation
+//!
+//! The registry uses cumulative temporality, as Prometheus expects.
+//!
+//! | OpenTelemetry | Prometheus |
+//! |---------------|------------|
+//! | Counter | `counter`, with a `_total` suffix |
+//! | UpDownCounter | `gauge` |
+//! | Gauge | `gauge` |
+//! | Histogram | `histogram`, as `_bucket`, `_sum` and `_count` series |
+//! | Histogram listed in `prometheus_summaries` | `summary`, as `quantile` series, `_sum` and `_count` |
+//!
+//! Attribute keys are sanitized to the Prometheus character set, and so are names, through
+//! [`sanitize`], unless `MetricsConfig::name_transform` is set: the configured transform then
+//! replaces the sanitization and must produce valid Prometheus names itself, for example by
+//! calling [`sanitize`] on its result. The description of the instrument becomes the `# HELP`
+//! line and its unit the `# UNIT` line, which scrapers of the text format read as a comment.
+//! Units are not appended to the names. The resource is exposed once as the `target_info`
+//! gauge, whose labels can be trimmed with the `prometheus` entries of
+//! `MetricsConfig::resource_keys` and `MetricsConfig::resource_exclude`. Exponential histograms
+//! have no text representation and are skipped.
+//!
+//! The SDK keeps series and resource attributes in hash maps, whose iteration order changes
+//! between runs. Families are therefore sorted by name, the series of each family by their
+//! labels and the labels of each series by name, so the same recordings always render the same
+//! output, byte for byte, which keeps golden files of the scrape output stable.
+//!
+//! Instruments are created after install, so the registry checks their metadata when it first
+//! gathers them: every metric without a description is logged once as a warning.
+//!
+//! ## Summaries
+//!
+//! Dashboards built before Prometheus histograms expect summaries. The histograms listed in
+//! `MetricsConfig::prometheus_summaries` are rendered as summaries with the configured
+//! quantiles instead. The quantiles are estimated from the bucket counts, interpolating
+//! linearly within the bucket holding each quantile like `histogram_quantile` does, and
+//! clamped to the recorded minimum and maximum. Their precision therefore depends on the bucket
+//! boundaries of the instrument. The quantiles cover everything recorded since install, since
+//! the registry uses cumulative temporality.
+//!
+//! ## Tenants
+//!
+//! Multi-tenant services can give every tenant its own registry with [`install_tenant`], so
+//! the scrape endpoint of a tenant only shows its own series. Each tenant gets an independent
+//! provider, which is not installed globally: the tenant records through the meters of its
+//! [`PrometheusHandle`]. [`Tenants`] creates the handles on first use and keeps them by tenant
+//! id.
+//!
+//! ## Configuration
+//!
+//! Enable this exporter by building with the `prometheus` feature flag:
+//!
+//! ```sh
+//! cargo build --features prometheus
+//! ```
+//!
+//! The application serves the scrape endpoint itself, with the output of
+//! [`PrometheusRegistry::gather`], or lets [`serve`] run a minimal HTTP server exposing it on
+//! `/metrics`.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use metrics::exporters::prom;
+//!
+//! let provider = prom::install().unwrap();
+//!
+//! // later, in the `/metrics` handler
+//! let body: String = prom::registry().unwrap().gather().unwrap();
+//! ```
+
+pub use super::scrape::{ServerHandle, serve};
+
+use super::{reader::SharedReader, transform::Transform};
+use crate::{
+    LOG_TARGET,
+    cardinality::series_counts,
+    config::{ExporterKind, MetricsConfig},
+    errors::MetricsError,
+};
+use opentelemetry::{
+    KeyValue,
+    metrics::{Meter, MeterProvider},
+};
+use opentelemetry_sdk::{
+    Resource,
+    metrics::{
+        SdkMeterProvider,
+        data::{Gauge, Histogram, HistogramDataPoint, ResourceMetrics, Sum},
+        reader::MetricReader,
+    },
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    sync::{Arc, Mutex, RwLock},
+};
+use tracing::{debug, error, warn};
+
+/// Content type of the text exposition format, for the `content-type` header of the scrape
+/// response.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// The registry of the last installed provider.
+static REGISTRY: RwLock<Option<PrometheusRegistry>> = RwLock::new(None);
+
+/// Creates and installs a Prometheus metrics exporter.
+///
+/// # Returns
+///
+/// * `Ok(SdkMeterProvider)` - The configured meter provider
+/// * `Err(MetricsError)` - If an error occurred during exporter setup
+pub fn install() -> Result<SdkMeterProvider, MetricsError> {
+    install_with_config(&MetricsConfig::load())
+}
+
+/// Creates and installs a Prometheus metrics exporter from an explicit configuration.
+///
+/// Behaves like [`install`], but reads the resource attributes from the given
+/// [`MetricsConfig`] instead of the ambient Ruskit configuration. The registry to scrape is
+/// then returned by [`registry`].
+///
+/// # Parameters
+///
+/// * `cfg` - The metrics configuration
+///
+/// # Returns
+///
+/// * `Ok(SdkMeterProvider)` - The configured meter provider
+/// * `Err(MetricsError)` - If an error occurred during exporter setup
+pub fn install_with_config(cfg: &MetricsConfig) -> Result<SdkMeterProvider, MetricsError> {
+    let provider = super::provider_builder(cfg)
+        .with_reader(register(cfg))
+        .build();
+
+    super::install_global(&provider);
+
+    debug!(
+        target: LOG_TARGET,
+        exporter = "prometheus",
+        "metrics::install prometheus exporter installed"
+    );
+
+    Ok(provider)
+}
+
+/// Creates the registry returned by [`registry`], and the reader to register on the provider.
+///
+/// Also used by the OTLP exporter to mirror its stream when `mirror_prometheus` is set.
+pub(crate) fn register(cfg: &MetricsConfig) -> SharedReader {
+    let registry = PrometheusRegistry::new(cfg);
+    let reader = registry.reader.clone();
+
+    *REGISTRY.write().unwrap_or_else(|e| e.into_inner()) = Some(registry);
+
+    reader
+}
+
+/// Returns the registry of the last installed Prometheus exporter.
+///
+/// # Returns
+///
+/// The registry, or `None` if the Prometheus exporter was never installed
+pub fn registry() -> Option<PrometheusRegistry> {
+    REGISTRY.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Creates a provider and a registry isolated from every other one, for a tenant.
+///
+/// The provider is not installed globally and the registry is not returned by [`registry`]:
+/// only the meters of the returned handle record into it, and only its registry exposes their
+/// series. Every call creates new ones, so calling it twice for the same tenant creates two
+/// independent registries; [`Tenants`] keeps a single handle per tenant.
+///
+/// # Parameters
+///
+/// * `cfg` - The metrics configuration, shared by the tenants
+/// * `tenant_id` - The identifier of the tenant
+///
+/// # Returns
+///
+/// The handle of the tenant
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use metrics::{config::MetricsConfig, exporters::prom};
+///
+/// let tenant = prom::install_tenant(&MetricsConfig::load(), "acme");
+/// tenant.meter("gateway").u64_counter("requests").build().add(1, &[]);
+///
+/// let body = tenant.registry().gather().unwrap();
+/// ```
+pub fn install_tenant(cfg: &MetricsConfig, tenant_id: &str) -> PrometheusHandle {
+    let registry = PrometheusRegistry::new(cfg);
+    let provider = super::provider_builder(cfg)
+        .with_reader(registry.reader.clone())
+        .build();
+
+    debug!(
+        target: LOG_TARGET,
+        exporter = "prometheus",
+        tenant = tenant_id,
+        "metrics::install prometheus tenant registry created"
+    );
+
+    PrometheusHandle {
+        tenant_id: tenant_id.to_string(),
+        provider,
+        registry,
+    }
+}
+
+/// # PrometheusHandle
+///
+/// The provider and registry of a tenant, created by [`install_tenant`].
+///
+/// Clones share the same provider and registry.
+#[derive(Debug, Clone)]
+pub struct PrometheusHandle {
+    tenant_id: String,
+    provider: SdkMeterProvider,
+    registry: PrometheusRegistry,
+}
+
+impl PrometheusHandle {
+    /// Returns the identifier of the tenant.
+    pub fn tenant_id(&self) -> &str {
+        &self.tenant_id
+    }
+
+    /// Returns the provider of the tenant.
+    pub fn provider(&self) -> &SdkMeterProvider {
+        &self.provider
+    }
+
+    /// Returns the registry exposing the series of the tenant.
+    pub fn registry(&self) -> &PrometheusRegistry {
+        &self.registry
+    }
+
+    /// Returns a meter recording into the registry of the tenant.
+    pub fn meter(&self, name: &'static str) -> Meter {
+        self.provider.meter(name)
+    }
+}
+
+/// # Tenants
+///
+/// The handles of the tenants, created with [`install_tenant`] when a tenant is first seen.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use metrics::{config::MetricsConfig, exporters::prom::Tenants};
+///
+/// let tenants = Tenants::new(MetricsConfig::load());
+///
+/// // when handling a request of a tenant
+/// let tenant = tenants.get_or_install("acme");
+/// tenant.meter("gateway").u64_counter("requests").build().add(1, &[]);
+///
+/// // when serving the scrape endpoint of a tenant
+/// let body = tenants.get("acme").map(|tenant| tenant.registry().gather());
+/// ```
+#[derive(Debug)]
+pub struct Tenants {
+    cfg: MetricsConfig,
+    handles: RwLock<BTreeMap<String, PrometheusHandle>>,
+}
+
+impl Tenants {
+    /// Creates an empty set of tenants, whose registries are created from `cfg`.
+    pub fn new(cfg: MetricsConfig) -> Self {
+        Self {
+            cfg,
+            handles: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// Returns the handle of the tenant, or `None` if it was never installed.
+    pub fn get(&self, tenant_id: &str) -> Option<PrometheusHandle> {
+        self.handles
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(tenant_id)
+            .cloned()
+    }
+
+    /// Returns the handle of the tenant, installing it on first use.
+    pub fn get_or_install(&self, tenant_id: &str) -> PrometheusHandle {
+        if let Some(handle) = self.get(tenant_id) {
+            return handle;
+        }
+
+        self.handles
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(tenant_id.to_string())
+            .or_insert_with(|| install_tenant(&self.cfg, tenant_id))
+            .clone()
+    }
+
+    /// Removes the tenant and shuts its provider down.
+    ///
+    /// # Returns
+    ///
+    /// The handle of the removed tenant, or `None` if it was never installed
+    pub fn remove(&self, tenant_id: &str) -> Option<PrometheusHandle> {
+        let handle = self
+            .handles
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(tenant_id)?;
+
+        if let Err(err) = handle.provider.shutdown() {
+            warn!(
+                target: LOG_TARGET,
+                error = %err,
+                tenant = tenant_id,
+                "failure to shut down the tenant provider"
+            );
+        }
+
+        Some(handle)
+    }
+
+    /// Returns the identifiers of the installed tenants, sorted.
+    pub fn tenant_ids(&self) -> Vec<String> {
+        self.handles
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .keys()
+            .cloned()
+            .collect()
+    }
+}
+
+/// # PrometheusRegistry
+///
+/// A handle collecting the metrics of the installed provider in the Prometheus text format.
+///
+/// Clones share the same reader, so every clone can serve the scrape endpoint.
+#[derive(Debug, Clone)]
+pub struct PrometheusRegistry {
+    reader: SharedReader,
+    transform: Transform,
+    summaries: BTreeMap<String, Vec<f64>>,
+    sanitize_names: bool,
+    undescribed: Arc<Mutex<BTreeSet<String>>>,
+}
+
+impl PrometheusRegistry {
+    fn new(cfg: &MetricsConfig) -> Self {
+        Self {
+            reader: SharedReader::new(),
+            transform: Transform::for_exporter(cfg, ExporterKind::Prometheus),
+            summaries: cfg.prometheus_summaries.clone(),
+            // a configured name transform was already applied by the views and replaces it
+            sanitize_names: cfg.name_transform.is_none(),
+            undescribed: Arc::new(Mutex::new(BTreeSet::new())),
+        }
+    }
+
+    /// Collects the current metrics and renders them in the text exposition format.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The scrape response body
+    /// * `Err(MetricsError::InternalError)` - If the provider was shut down
+    pub fn gather(&self) -> Result<String, MetricsError> {
+        let rm = self.collect()?;
+        Ok(render(&rm, &self.summaries, self.sanitize_names))
+    }
+
+    /// Collects the current metrics and counts the series of every metric, that is the label
+    /// permutations of each family.
+    ///
+    /// Summaries and histograms render several samples per series, which are counted once.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(BTreeMap<String, usize>)` - The number of series, by metric name
+    /// * `Err(MetricsError::InternalError)` - If the provider was shut down
+    pub fn series_counts(&self) -> Result<BTreeMap<String, usize>, MetricsError> {
+        let rm = self.collect()?;
+        Ok(series_counts(&rm))
+    }
+
+    /// Collects the current metrics and applies the transforms of the exporter.
+    fn collect(&self) -> Result<ResourceMetrics, MetricsError> {
+        let mut rm = ResourceMetrics {
+            resource: Resource::builder_empty().build(),
+            scope_metrics: Vec::new(),
+        };
+
+        if let Err(err) = self.reader.collect(&mut rm) {
+            error!(target: LOG_TARGET, error = %err, "failure to collect the prometheus metrics");
+            return Err(MetricsError::InternalError);
+        }
+
+        self.transform.apply(&mut rm);
+        self.warn_undescribed(&rm);
+
+        Ok(rm)
+    }
+
+    /// Logs a warning for every metric without a description, once per metric.
+    fn warn_undescribed(&self, rm: &ResourceMetrics) {
+        let mut undescribed = self.undescribed.lock().unwrap_or_else(|e| e.into_inner());
+
+        for metric in rm.scope_metrics.iter().flat_map(|scope| &scope.metrics) {
+            if metric.description.is_empty() && undescribed.insert(metric.name.to_string()) {
+                warn!(
+                    target: LOG_TARGET,
+                    name = %metric.name,
+                    "metric without description, its family has no # HELP line"
+                );
+            }
+        }
+    }
+}
+
+/// Serializes collected metrics in the Prometheus text exposition format.
+///
+/// Metrics sharing a name across scopes are rendered as a single family. Families are sorted by
+/// name, series by labels and labels by name, so the output is stable across scrapes.
+///
+/// # Parameters
+///
+/// * `rm` - The collected metrics, with cumulative temporality
+///
+/// # Returns
+///
+/// The rendered metrics, one family after the other
+///
+/// # Example
+///
+/// ```text
+/// # HELP http_requests_total Number of requests
+/// # TYPE http_requests_total counter
+/// http_requests_total{method="GET"} 42
+/// ```
+pub fn encode(rm: &ResourceMetrics) -> String {
+    render(rm, &BTreeMap::new(), true)
+}
+
+/// Serializes collected metrics, rendering the histograms listed in `summaries` as summaries
+/// with the associated quantiles. Names are sanitized when `sanitize_names` is set, and
+/// rendered as collected otherwise.
+fn render(
+    rm: &ResourceMetrics,
+    summaries: &BTreeMap<String, Vec<f64>>,
+    sanitize_names: bool,
+) -> String {
+    let mut families: BTreeMap<String, Family> = BTreeMap::new();
+
+    for scope in &rm.scope_metrics {
+        for metric in &scope.metrics {
+            let name = if sanitize_names {
+                sanitize(&metric.name)
+            } else {
+                metric.name.to_string()
+            };
+            let data = metric.data.as_any();
+            let quantiles = summaries.get(metric.name.as_ref());
+
+            let (name, kind, samples) = if let Some(sum) = data.downcast_ref::<Sum<u64>>() {
+                sum_samples(name, sum)
+            } else if let Some(sum) = data.downcast_ref::<Sum<i64>>() {
+                sum_samples(name, sum)
+            } else if let Some(sum) = data.downcast_ref::<Sum<f64>>() {
+                sum_samples(name, sum)
+            } else if let Some(gauge) = data.downcast_ref::<Gauge<u64>>() {
+                gauge_samples(name, gauge)
+            } else if let Some(gauge) = data.downcast_ref::<Gauge<i64>>() {
+                gauge_samples(name, gauge)
+            } else if let Some(gauge) = data.downcast_ref::<Gauge<f64>>() {
+                gauge_samples(name, gauge)
+            } else if let Some(hist) = data.downcast_ref::<Histogram<u64>>() {
+                match quantiles {
+                    Some(quantiles) => summary_samples(name, hist, quantiles),
+                    None => histogram_samples(name, hist),
+                }
+            } else if let Some(hist) = data.downcast_ref::<Histogram<f64>>() {
+                match quantiles {
+                    Some(quantiles) => summary_samples(name, hist, quantiles),
+                    None => histogram_samples(name, hist),
+                }
+            } else {
+                continue;
+            };
+
+            let family = families.entry(name).or_insert_with(|| Family {
+                kind,
+                help: metric.description.to_string(),
+                unit: metric.unit.to_string(),
+                samples: Vec::new(),
+            });
+            family.samples.extend(samples);
+        }
+    }
+
+    let mut out = String::new();
+
+    let resource: Vec<KeyValue> = rm
+        .resource
+        .iter()
+        .map(|(k, v)| KeyValue::new(k.clone(), v.clone()))
+        .collect();
+    if !resource.is_empty() {
+        out.push_str("# HELP target_info Target metadata\n# TYPE target_info gauge\n");
+        let _ = writeln!(out, "target_info{} 1", labels(&resource, None));
+    }
+
+    for (name, mut family) in families {
+        family.samples.sort();
+
+        if !family.help.is_empty() {
+            let _ = writeln!(out, "# HELP {name} {}", escape_help(&family.help));
+        }
+        if !family.unit.is_empty() {
+            let _ = writeln!(out, "# UNIT {name} {}", escape_help(&family.unit));
+        }
+        let _ = writeln!(out, "# TYPE {name} {}", family.kind);
+        for sample in family.samples {
+            out.push_str(&sample);
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+/// A metric family: the samples rendered under one `# TYPE` line.
+///
+/// Each sample holds the lines of one series, so sorting the samples orders the series by their
+/// labels while keeping the lines of a histogram series together.
+struct Family {
+    kind: &'static str,
+    help: String,
+    unit: String,
+    samples: Vec<String>,
+}
+
+/// A value in the text format, where non-finite floats have their own spelling.
+trait SampleValue {
+    fn render(&self) -> String;
+
+    fn to_f64(&self) -> f64;
+}
+
+impl SampleValue for u64 {
+    fn render(&self) -> String {
+        self.to_string()
+    }
+
+    fn to_f64(&self) -> f64 {
+        *self as f64
+    }
+}
+
+impl SampleValue for i64 {
+    fn render(&self) -> String {
+        self.to_string()
+    }
+
+    fn to_f64(&self) -> f64 {
+        *self as f64
+    }
+}
+
+impl SampleValue for f64 {
+    fn render(&self) -> String {
+        if self.is_nan() {
+            "NaN".to_string()
+        } else if self.is_infinite() {
+            if self.is_sign_positive() {
+                "+Inf"
+            } else {
+                "-Inf"
+            }
+            .to_string()
+        } else {
+            self.to_string()
+        }
+    }
+
+    fn to_f64(&self) -> f64 {
+        *self
+    }
+}
+
+/// Renders monotonic sums as counters and the others as gauges.
+fn sum_samples<T: SampleValue>(name: String, sum: &Sum<T>) -> (String, &'static str, Vec<String>) {
+    let (name, kind) = if sum.is_monotonic {
+        let name = if name.ends_with("_total") {
+            name
+        } else {
+            format!("{name}_total")
+        };
+        (name, "counter")
+    } else {
+        (name, "gauge")
+    };
+
+    let samples = sum
+        .data_points
+        .iter()
+        .map(|dp| {
+            format!(
+                "{name}{} {}",
+                labels(&dp.attributes, None),
+                dp.value.render()
+            )
+        })
+        .collect();
+
+    (name, kind, samples)
+}
+
+fn gauge_samples<T: SampleValue>(
+    name: String,
+    gauge: &Gauge<T>,
+) -> (String, &'static str, Vec<String>) {
+    let samples = gauge
+        .data_points
+        .iter()
+        .map(|dp| {
+            format!(
+                "{name}{} {}",
+                labels(&dp.attributes, None),
+                dp.value.render()
+            )
+        })
+        .collect();
+
+    (name, "gauge", samples)
+}
+
+/// Renders histograms as cumulative buckets, followed by the sum and count series.
+fn histogram_samples<T: SampleValue>(
+    name: String,
+    hist: &Histogram<T>,
+) -> (String, &'static str, Vec<String>) {
+    let mut samples = Vec::new();
+
+    for dp in &hist.data_points {
+        let mut lines = Vec::new();
+        let mut cumulative = 0;
+        for (i, count) in dp.bucket_counts.iter().enumerate() {
+            cumulative += count;
+            let le = dp
+                .bounds
+                .get(i)
+                .map_or_else(|| "+Inf".to_string(), |b| b.render());
+            lines.push(format!(
+                "{name}_bucket{} {cumulative}",
+                labels(&dp.attributes, Some(("le", &le)))
+            ));
+        }
+        let attrs = labels(&dp.attributes, None);
+        lines.push(format!("{name}_sum{attrs} {}", dp.sum.render()));
+        lines.push(format!("{name}_count{attrs} {}", dp.count));
+        samples.push(lines.join("\n"));
+    }
+
+    (name, "histogram", samples)
+}
+
+/// Renders histograms as summaries, with one series per quantile followed by the sum and count
+/// series.
+fn summary_samples<T: SampleValue>(
+    name: String,
+    hist: &Histogram<T>,
+    quantiles: &[f64],
+) -> (String, &'static str, Vec<String>) {
+    let mut samples = Vec::new();
+
+    for dp in &hist.data_points {
+        let mut lines = Vec::new();
+        for q in quantiles {
+            lines.push(format!(
+                "{name}{} {}",
+                labels(&dp.attributes, Some(("quantile", &q.render()))),
+                estimate_quantile(dp, *q).render()
+            ));
+        }
+        let attrs = labels(&dp.attributes, None);
+        lines.push(format!("{name}_sum{attrs} {}", dp.sum.render()));
+        lines.push(format!("{name}_count{attrs} {}", dp.count));
+        samples.push(lines.join("\n"));
+    }
+
+    (name, "summary", samples)
+}
+
+/// Estimates the `q` quantile of a histogram data point from its bucket counts.
+///
+/// The rank is located in the bucket holding it and interpolated linearly between the bucket
+/// boundaries. The unbounded first and last buckets are closed by the recorded minimum and
+/// maximum, or by the nearest boundary when they were not recorded.
+fn estimate_quantile<T: SampleValue>(dp: &HistogramDataPoint<T>, q: f64) -> f64 {
+    if dp.count == 0 {
+        return f64::NAN;
+    }
+
+    let min = dp.min.as_ref().map(SampleValue::to_f64);
+    let max = dp.max.as_ref().map(SampleValue::to_f64);
+    let rank = q.clamp(0.0, 1.0) * dp.count as f64;
+
+    let mut cumulative = 0.0;
+    for (i, &bucket) in dp.bucket_counts.iter().enumerate() {
+        let count = bucket as f64;
+        if bucket == 0 || cumulative + count < rank {
+            cumulative += count;
+            continue;
+        }
+
+        let lower = match i {
+            0 => min.unwrap_or_else(|| dp.bounds.first().copied().unwrap_or(0.0)),
+            _ => dp.bounds[i - 1],
+        };
+        let upper = dp.bounds.get(i).copied().or(max).unwrap_or(lower);
+
+        let estimate = lower + (upper - lower) * (rank - cumulative) / count;
+        return match (min, max) {
+            (Some(min), Some(max)) => estimate.clamp(min, max),
+            _ => estimate,
+        };
+    }
+
+    max.unwrap_or(f64::NAN)
+}
+
+/// Renders the label set of a sample, sorted by name with the `extra` label last, or an empty
+/// string when there are no labels.
+fn labels(attributes: &[KeyValue], extra: Option<(&str, &str)>) -> String {
+    let mut labels: Vec<(String, String)> = attributes
+        .iter()
+        .map(|kv| {
+            (
+                sanitize(kv.key.as_str()).replace(':', "_"),
+                escape_label(&kv.value.to_string()),
+            )
+        })
+        .collect();
+    labels.sort();
+
+    let mut pairs: Vec<String> = labels
+        .into_iter()
+        .map(|(key, value)| format!("{key}=\"{value}\""))
+        .collect();
+
+    if let Some((key, value)) = extra {
+        pairs.push(format!("{key}=\"{value}\""));
+    }
+
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+/// Replaces the characters Prometheus does not accept in names with `_`, and prefixes the names
+/// starting with a digit with `_`.
+///
+/// This is the default name transform of the Prometheus exporter. A custom
+/// `MetricsConfig::name_transform` replaces it, and can call it to keep the names valid.
+///
+/// # Example
+///
+/// ```rust
+/// use metrics::exporters::prom::sanitize;
+///
+/// assert_eq!(sanitize("http.server.duration"), "http_server_duration");
+/// ```
+pub fn sanitize(name: &str) -> String {
+    let mut sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == ':' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+        sanitized.insert(0, '_');
+    }
+
+    sanitized
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn escape_help(help: &str) -> String {
+    help.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{AggregationSelector, NameTransform},
+        test_util,
+    };
+    use opentelemetry_sdk::metrics::{Aggregation, InstrumentKind};
+
+    /// Collects the metrics of the reader without the resource, so no `target_info` is rendered.
+    fn collect(reader: &SharedReader) -> ResourceMetrics {
+        let mut rm = test_util::collect(reader);
+        rm.resource = Resource::builder_empty().build();
+        rm
+    }
+
+    fn small_buckets() -> MetricsConfig {
+        MetricsConfig {
+            aggregation_selector: Some(AggregationSelector::new(|kind| match kind {
+                InstrumentKind::Histogram => Some(Aggregation::ExplicitBucketHistogram {
+                    boundaries: vec![1.0, 2.0],
+                    record_min_max: true,
+                }),
+                _ => None,
+            })),
+            ..MetricsConfig::default()
+        }
+    }
+
+    #[test]
+    fn encode_renders_the_text_format() {
+        let (provider, reader) = test_util::provider(&small_buckets());
+        let meter = provider.meter("http");
+        meter
+            .u64_counter("http.requests")
+            .with_description("Number of requests")
+            .build()
+            .add(3, &[KeyValue::new("method", "GET")]);
+        let latency = meter.f64_histogram("latency").build();
+        latency.record(1.5, &[]);
+        latency.record(3.0, &[]);
+        meter.i64_up_down_counter("active").build().add(-2, &[]);
+
+        let text = encode(&collect(&reader));
+
+        assert_eq!(
+            text,
+            "# TYPE active gauge\n\
+             active -2\n\
+             # HELP http_requests_total Number of requests\n\
+             # TYPE http_requests_total counter\n\
+             http_requests_total{method=\"GET\"} 3\n\
+             # TYPE latency histogram\n\
+             latency_bucket{le=\"1\"} 0\n\
+             latency_bucket{le=\"2\"} 1\n\
+             latency_bucket{le=\"+Inf\"} 2\n\
+             latency_sum 4.5\n\
+             latency_count 2\n"
+        );
+    }
+
+    #[test]
+    fn encode_sanitizes_names_and_escapes_labels() {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        provider
+            .meter("test")
+            .u64_gauge("1queue-depth")
+            .build()
+            .record(4, &[KeyValue::new("queue.name", "a\"b")]);
+
+        let text = encode(&collect(&reader));
+
+        assert!(text.contains("_1queue_depth{queue_name=\"a\\\"b\"} 4\n"));
+    }
+
+    #[test]
+    fn name_transform_replaces_the_sanitization() {
+        let cfg = MetricsConfig {
+            name_transform: Some(NameTransform::new(|name| name.to_uppercase())),
+            ..MetricsConfig::default()
+        };
+        let registry = PrometheusRegistry::new(&cfg);
+        let provider = crate::exporters::provider_builder(&cfg)
+            .with_reader(registry.reader.clone())
+            .build();
+        provider
+            .meter("test")
+            .u64_gauge("queue.depth")
+            .build()
+            .record(4, &[]);
+
+        let text = registry.gather().unwrap();
+
+        assert!(text.contains("QUEUE.DEPTH 4\n"));
+        assert!(!text.contains("queue_depth"));
+    }
+
+    #[test]
+    fn resource_is_exposed_as_target_info() {
+        let (provider, reader) = test_util::provider(&MetricsConfig {
+            service_name: "checkout".to_string(),
+            ..MetricsConfig::default()
+        });
+        provider
+            .meter("test")
+            .u64_counter("requests")
+            .build()
+            .add(1, &[]);
+
+        let text = encode(&test_util::collect(&reader));
+
+        assert!(text.starts_with("# HELP target_info Target metadata\n# TYPE target_info gauge\n"));
+        assert!(text.contains("service_name=\"checkout\""));
+    }
+
+    #[test]
+    fn listed_histograms_render_as_summaries() {
+        let (provider, reader) = test_util::provider(&small_buckets());
+        let latency = provider.meter("http").f64_histogram("latency").build();
+        for value in [0.5, 1.5, 1.5, 3.0] {
+            latency.record(value, &[]);
+        }
+        let summaries = BTreeMap::from([("latency".to_string(), vec![0.5, 1.0])]);
+
+        let text = render(&collect(&reader), &summaries);
+
+        assert_eq!(
+            text,
+            "# TYPE latency summary\n\
+             latency{quantile=\"0.5\"} 1.5\n\
+             latency{quantile=\"1\"} 3\n\
+             latency_sum 6.5\n\
+             latency_count 4\n"
+        );
+    }
+
+    #[test]
+    fn units_are_rendered_and_undescribed_metrics_warned_once() {
+        let cfg = MetricsConfig::default();
+        let registry = PrometheusRegistry::new(&cfg);
+        let provider = crate::exporters::provider_builder(&cfg)
+            .with_reader(registry.reader.clone())
+            .build();
+        let meter = provider.meter("http");
+        meter
+            .f64_gauge("latency")
+            .with_description("Last latency")
+            .with_unit("s")
+            .build()
+            .record(0.5, &[]);
+        meter.u64_gauge("queue").build().record(2, &[]);
+
+        let mut text = String::new();
+        let logs = test_util::capture_logs(|| {
+            text = registry.gather().unwrap();
+            registry.gather().unwrap();
+        });
+
+        assert!(
+            text.contains("# HELP latency Last latency\n# UNIT latency s\n# TYPE latency gauge\n")
+        );
+        let warnings = logs
+            .iter()
+            .filter(|log| log.message.contains("without description"))
+            .count();
+        assert_eq!(warnings, 1);
+    }
+
+    #[test]
+    fn excluded_resource_keys_are_not_target_info_labels() {
+        let cfg = MetricsConfig {
+            service_name: "checkout".to_string(),
+            resource_exclude: BTreeMap::from([(
+                ExporterKind::Prometheus,
+                vec!["library.language".to_string()],
+            )]),
+            ..MetricsConfig::default()
+        };
+        let registry = PrometheusRegistry::new(&cfg);
+        let provider = crate::exporters::provider_builder(&cfg)
+            .with_reader(registry.reader.clone())
+            .build();
+        provider
+            .meter("test")
+            .u64_counter("requests")
+            .build()
+            .add(1, &[]);
+
+        let text = registry.gather().unwrap();
+
+        assert!(text.contains("service_name=\"checkout\""));
+        assert!(!text.contains("library_language"));
+    }
+
+    #[test]
+    fn series_and_labels_are_sorted() {
+        let (provider, reader) = test_util::provider(&small_buckets());
+        let meter = provider.meter("http");
+        let requests = meter.u64_counter("requests").build();
+        for (route, method) in [("/b", "POST"), ("/a", "GET"), ("/b", "GET")] {
+            requests.add(
+                1,
+                &[
+                    KeyValue::new("route", route),
+                    KeyValue::new("method", method),
+                ],
+            );
+        }
+        let latency = meter.f64_histogram("latency").build();
+        latency.record(3.0, &[KeyValue::new("route", "/b")]);
+        latency.record(0.5, &[KeyValue::new("route", "/a")]);
+
+        let text = encode(&collect(&reader));
+
+        assert_eq!(
+            text,
+            "# TYPE latency histogram\n\
+             latency_bucket{route=\"/a\",le=\"1\"} 1\n\
+             latency_bucket{route=\"/a\",le=\"2\"} 1\n\
+             latency_bucket{route=\"/a\",le=\"+Inf\"} 1\n\
+             latency_sum{route=\"/a\"} 0.5\n\
+             latency_count{route=\"/a\"} 1\n\
+             latency_bucket{route=\"/b\",le=\"1\"} 0\n\
+             latency_bucket{route=\"/b\",le=\"2\"} 0\n\
+             latency_bucket{route=\"/b\",le=\"+Inf\"} 1\n\
+             latency_sum{route=\"/b\"} 3\n\
+             latency_count{route=\"/b\"} 1\n\
+             # TYPE requests_total counter\n\
+             requests_total{method=\"GET\",route=\"/a\"} 1\n\
+             requests_total{method=\"GET\",route=\"/b\"} 1\n\
+             requests_total{method=\"POST\",route=\"/b\"} 1\n"
+        );
+    }
+
+    #[test]
+    fn tenant_registries_are_isolated() {
+        let tenants = Tenants::new(MetricsConfig::default());
+        let acme = tenants.get_or_install("acme");
+        let globex = tenants.get_or_install("globex");
+        acme.meter("gateway")
+            .u64_counter("acme.requests")
+            .build()
+            .add(1, &[]);
+        globex
+            .meter("gateway")
+            .u64_counter("globex.requests")
+            .build()
+            .add(2, &[]);
+
+        let acme_text = tenants.get("acme").unwrap().registry().gather().unwrap();
+        let globex_text = globex.registry().gather().unwrap();
+
+        assert!(acme_text.contains("acme_requests_total 1\n"));
+        assert!(!acme_text.contains("globex"));
+        assert!(globex_text.contains("globex_requests_total 2\n"));
+        assert!(!globex_text.contains("acme"));
+        assert_eq!(tenants.tenant_ids(), vec!["acme", "globex"]);
+    }
+
+    #[test]
+    fn removed_tenants_are_shut_down() {
+        let tenants = Tenants::new(MetricsConfig::default());
+        tenants.get_or_install("acme");
+
+        let removed = tenants.remove("acme").unwrap();
+
+        assert!(tenants.get("acme").is_none());
+        assert!(removed.registry().gather().is_err());
+    }
+
+    #[test]
+    fn install_replays_the_deferred_metrics() {
+        let _lock = test_util::global_lock();
+        let guard = crate::defer_until_installed();
+        opentelemetry::global::meter("startup")
+            .u64_counter("config.loaded")
+            .build()
+            .add(2, &[]);
+
+        let provider = install_with_config(&MetricsConfig::default()).unwrap();
+        drop(guard);
+
+        let body = registry().unwrap().gather().unwrap();
+        assert!(body.contains("config_loaded_total"), "{body}");
+        let _ = provider.shutdown();
+    }
+}
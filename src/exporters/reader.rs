@@ -0,0 +1,407 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Readers
+//!
+//! Provides the readers shared by the exporters.
+//!
+//! [`SharedReader`] is a manual reader that stays accessible once registered on a provider. It
+//! is used wherever the crate collects metrics on demand instead of periodically, such as the
+//! pre-install buffer, the OTLP snapshots and the checkpoints.
+//!
+//! [`periodic`] builds the reader of every push exporter. With `align_to_wall_clock` enabled,
+//! the periodic reader is only started on the next multiple of `exporter_interval` since the
+//! Unix epoch, so every export lands on an interval boundary instead of drifting from the
+//! process start time. Measurements recorded before are aggregated as usual and exported with
+//! the first cycle, or by a flush or a shutdown happening before it.
+
+use crate::{LOG_TARGET, config::MetricsConfig};
+use opentelemetry_sdk::{
+    Resource,
+    error::{OTelSdkError, OTelSdkResult},
+    metrics::{
+        InstrumentKind, ManualReader, MetricResult, PeriodicReader, Pipeline, Temporality,
+        data::ResourceMetrics, exporter::PushMetricExporter, reader::MetricReader,
+    },
+};
+use std::{
+    fmt,
+    sync::{
+        Arc, Mutex, OnceLock, Weak,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tracing::{debug, warn};
+
+/// # SharedReader
+///
+/// A `ManualReader` that can be registered on a provider and still be collected by its owner.
+///
+/// The provider takes ownership of its readers, so the reader is shared behind an `Arc`: one
+/// clone is registered on the provider, the other is kept to collect on demand.
+#[derive(Debug, Clone)]
+pub(crate) struct SharedReader(Arc<ManualReader>);
+
+impl SharedReader {
+    /// Creates a reader with cumulative temporality.
+    pub(crate) fn new() -> Self {
+        Self(Arc::new(ManualReader::builder().build()))
+    }
+
+    /// Creates a reader with delta temporality, resetting its accumulators on every collect.
+    pub(crate) fn delta() -> Self {
+        Self(Arc::new(
+            ManualReader::builder()
+                .with_temporality(Temporality::Delta)
+                .build(),
+        ))
+    }
+
+    /// Returns whether both handles share the same reader.
+    pub(crate) fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl MetricReader for SharedReader {
+    fn register_pipeline(&self, pipeline: Weak<Pipeline>) {
+        self.0.register_pipeline(pipeline)
+    }
+
+    fn collect(&self, rm: &mut ResourceMetrics) -> MetricResult<()> {
+        self.0.collect(rm)
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.0.force_flush()
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        self.0.shutdown()
+    }
+
+    fn temporality(&self, kind: InstrumentKind) -> Temporality {
+        self.0.temporality(kind)
+    }
+}
+
+/// Builds the periodic reader exporting through `exporter` every `exporter_interval`.
+///
+/// The reader is started right away, or on the next interval boundary when
+/// `align_to_wall_clock` is enabled.
+pub(crate) fn periodic<E: PushMetricExporter>(
+    exporter: E,
+    cfg: &MetricsConfig,
+) -> AlignedReader<E> {
+    let reader = AlignedReader {
+        temporality: exporter.temporality(),
+        state: Arc::new(AlignedState {
+            interval: cfg.exporter_interval,
+            pending: ManualReader::builder()
+                .with_temporality(exporter.temporality())
+                .build(),
+            exporter: Mutex::new(Some(exporter)),
+            pipeline: Mutex::new(None),
+            reader: OnceLock::new(),
+            is_shutdown: AtomicBool::new(false),
+        }),
+    };
+
+    if !cfg.align_to_wall_clock {
+        reader.state.start();
+        return reader;
+    }
+
+    let delay = until_boundary(cfg.exporter_interval);
+    let state = reader.state.clone();
+    let spawned = thread::Builder::new()
+        .name("metrics-aligned-reader".to_string())
+        .spawn(move || {
+            thread::sleep(delay);
+            state.start();
+        });
+
+    match spawned {
+        Ok(_) => debug!(
+            target: LOG_TARGET,
+            delay = ?delay,
+            interval = ?cfg.exporter_interval,
+            "periodic reader aligned to the wall clock"
+        ),
+        Err(err) => {
+            warn!(
+                target: LOG_TARGET,
+                error = err.to_string(),
+                "failure to delay the periodic reader, starting it unaligned"
+            );
+            reader.state.start();
+        }
+    }
+
+    reader
+}
+
+/// Returns the time left until the next multiple of `interval` since the Unix epoch.
+fn until_boundary(interval: Duration) -> Duration {
+    let interval = interval.as_millis();
+    if interval == 0 {
+        return Duration::ZERO;
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    Duration::from_millis((interval - now % interval) as u64)
+}
+
+/// # AlignedReader
+///
+/// A `PeriodicReader` whose start can be deferred.
+///
+/// The provider registers its pipeline as soon as it is built, so the pipeline is kept until
+/// the periodic reader exists. Until then collecting does nothing, flushing exports the
+/// pending measurements through a manual reader, and shutting down exports them before
+/// preventing the periodic reader from ever starting.
+pub(crate) struct AlignedReader<E: PushMetricExporter> {
+    temporality: Temporality,
+    state: Arc<AlignedState<E>>,
+}
+
+struct AlignedState<E: PushMetricExporter> {
+    interval: Duration,
+    pending: ManualReader,
+    exporter: Mutex<Option<E>>,
+    pipeline: Mutex<Option<Weak<Pipeline>>>,
+    reader: OnceLock<PeriodicReader<E>>,
+    is_shutdown: AtomicBool,
+}
+
+impl<E: PushMetricExporter> AlignedState<E> {
+    /// Builds the periodic reader and registers the pipeline on it, unless already shut down.
+    fn start(&self) {
+        // held until the reader is set, so a concurrent shutdown sees either of them
+        let mut exporter = self.exporter.lock().unwrap_or_else(|e| e.into_inner());
+        if self.is_shutdown.load(Ordering::SeqCst) {
+            return;
+        }
+        let Some(exporter) = exporter.take() else {
+            return;
+        };
+
+        let reader = PeriodicReader::builder(exporter)
+            .with_interval(self.interval)
+            .build();
+
+        let mut pipeline = self.pipeline.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(pipeline) = pipeline.take() {
+            reader.register_pipeline(pipeline);
+        }
+        let _ = self.reader.set(reader);
+    }
+
+    /// Collects the measurements recorded before the periodic reader started and exports them.
+    fn export_pending(&self, exporter: &E) -> OTelSdkResult {
+        let registered = self
+            .pipeline
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_some();
+        if !registered {
+            return Ok(());
+        }
+
+        let mut rm = ResourceMetrics {
+            resource: Resource::builder_empty().build(),
+            scope_metrics: Vec::new(),
+        };
+        self.pending
+            .collect(&mut rm)
+            .map_err(|err| OTelSdkError::InternalFailure(err.to_string()))?;
+        if rm.scope_metrics.is_empty() {
+            return Ok(());
+        }
+
+        // exported from its own thread, so flushing from an async context does not start a
+        // runtime within the caller's one
+        thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                        .map_err(|err| OTelSdkError::InternalFailure(err.to_string()))?
+                        .block_on(exporter.export(&mut rm))
+                })
+                .join()
+                .unwrap_or_else(|_| {
+                    Err(OTelSdkError::InternalFailure(
+                        "the pending metrics export panicked".to_string(),
+                    ))
+                })
+        })
+    }
+}
+
+impl<E: PushMetricExporter> fmt::Debug for AlignedReader<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AlignedReader")
+            .field("interval", &self.state.interval)
+            .field("started", &self.state.reader.get().is_some())
+            .finish()
+    }
+}
+
+impl<E: PushMetricExporter> MetricReader for AlignedReader<E> {
+    fn register_pipeline(&self, pipeline: Weak<Pipeline>) {
+        let mut pending = self
+            .state
+            .pipeline
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        match self.state.reader.get() {
+            Some(reader) => reader.register_pipeline(pipeline),
+            None => {
+                self.state.pending.register_pipeline(pipeline.clone());
+                *pending = Some(pipeline);
+            }
+        }
+    }
+
+    fn collect(&self, rm: &mut ResourceMetrics) -> MetricResult<()> {
+        match self.state.reader.get() {
+            Some(reader) => reader.collect(rm),
+            None => Ok(()),
+        }
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        {
+            // held while exporting, so the periodic reader cannot start meanwhile
+            let exporter = self
+                .state
+                .exporter
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            if let Some(exporter) = exporter.as_ref() {
+                return self.state.export_pending(exporter);
+            }
+        }
+
+        match self.state.reader.get() {
+            Some(reader) => reader.force_flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        let pending = {
+            let mut exporter = self
+                .state
+                .exporter
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            self.state.is_shutdown.store(true, Ordering::SeqCst);
+            exporter.take()
+        };
+
+        match (pending, self.state.reader.get()) {
+            (Some(exporter), _) => {
+                let exported = self.state.export_pending(&exporter);
+                exported.and(exporter.shutdown())
+            }
+            (None, Some(reader)) => reader.shutdown(),
+            (None, None) => Ok(()),
+        }
+    }
+
+    fn temporality(&self, _kind: InstrumentKind) -> Temporality {
+        self.temporality
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MockExporter;
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry_sdk::metrics::SdkMeterProvider;
+
+    #[test]
+    fn until_boundary_stays_within_the_interval() {
+        let interval = Duration::from_secs(60);
+
+        let delay = until_boundary(interval);
+
+        assert!(delay > Duration::ZERO && delay <= interval);
+        assert_eq!(until_boundary(Duration::ZERO), Duration::ZERO);
+    }
+
+    #[test]
+    fn unaligned_reader_starts_right_away() {
+        let cfg = MetricsConfig::default();
+
+        let reader = periodic(MockExporter::default(), &cfg);
+
+        assert!(reader.state.reader.get().is_some());
+        assert!(reader.shutdown().is_ok());
+    }
+
+    #[test]
+    fn aligned_reader_waits_and_never_starts_once_shut_down() {
+        let cfg = MetricsConfig {
+            align_to_wall_clock: true,
+            exporter_interval: Duration::from_secs(3600),
+            ..MetricsConfig::default()
+        };
+
+        let reader = periodic(MockExporter::default(), &cfg);
+
+        assert!(reader.state.reader.get().is_none());
+        assert!(reader.force_flush().is_ok());
+        assert!(reader.shutdown().is_ok());
+        reader.state.start();
+        assert!(reader.state.reader.get().is_none());
+    }
+
+    fn aligned_provider(exporter: &MockExporter) -> SdkMeterProvider {
+        let cfg = MetricsConfig {
+            align_to_wall_clock: true,
+            exporter_interval: Duration::from_secs(3600),
+            ..MetricsConfig::default()
+        };
+        let provider = SdkMeterProvider::builder()
+            .with_reader(periodic(exporter.clone(), &cfg))
+            .build();
+        provider
+            .meter("test")
+            .u64_counter("jobs")
+            .build()
+            .add(1, &[]);
+        provider
+    }
+
+    #[test]
+    fn shutdown_before_the_boundary_exports_the_pending_metrics() {
+        let exporter = MockExporter::default();
+        let provider = aligned_provider(&exporter);
+
+        provider.shutdown().unwrap();
+
+        assert_eq!(exporter.names(), vec![vec!["jobs".to_string()]]);
+    }
+
+    #[test]
+    fn flush_before_the_boundary_exports_the_pending_metrics() {
+        let exporter = MockExporter::default();
+        let provider = aligned_provider(&exporter);
+
+        provider.force_flush().unwrap();
+
+        assert_eq!(exporter.names(), vec![vec!["jobs".to_string()]]);
+        provider.shutdown().unwrap();
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Export Error Reporting
+//!
+//! Provides an exporter wrapper that reports failed exports to the application.
+//!
+//! The `PeriodicReader` swallows export errors after logging them. This module wraps the
+//! underlying exporter so every failure is classified as an [`ExportError`] and delivered to
+//! the [`ExportErrorHook`] configured on the [`MetricsConfig`](crate::config::MetricsConfig).
+//!
+//! ## Usage
+//!
+//! The wrapper is applied internally by the network exporters. Applications only configure the
+//! hook.
+
+use crate::{LOG_TARGET, config::ExportErrorHook, errors::ExportError};
+use opentelemetry_sdk::{
+    error::OTelSdkResult,
+    metrics::{Temporality, data::ResourceMetrics, exporter::PushMetricExporter},
+};
+use tracing::warn;
+
+/// # ReportingExporter
+///
+/// Wraps an exporter and calls the export error hook whenever an export fails.
+///
+/// The result of the wrapped exporter is returned unchanged, so the SDK keeps its own error
+/// handling.
+#[derive(Debug)]
+pub(crate) struct ReportingExporter<E> {
+    inner: E,
+    hook: Option<ExportErrorHook>,
+}
+
+impl<E> ReportingExporter<E> {
+    /// Wraps `inner`, reporting its failures to `hook` when one is configured.
+    pub(crate) fn new(inner: E, hook: Option<ExportErrorHook>) -> Self {
+        Self { inner, hook }
+    }
+}
+
+impl<E: PushMetricExporter> PushMetricExporter for ReportingExporter<E> {
+    async fn export(&self, metrics: &mut ResourceMetrics) -> OTelSdkResult {
+        let result = self.inner.export(metrics).await;
+
+        if let (Err(err), Some(hook)) = (&result, &self.hook) {
+            let err = ExportError::from(err);
+            warn!(target: LOG_TARGET, error = err.to_string(), "metrics export failed");
+            hook.call(&err);
+        }
+
+        result
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        self.inner.shutdown()
+    }
+
+    fn temporality(&self) -> Temporality {
+        self.inner.temporality()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, MockExporter};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn hook_is_called_on_failed_exports_only() {
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let hook = {
+            let reported = reported.clone();
+            ExportErrorHook::new(move |err| reported.lock().unwrap().push(err.clone()))
+        };
+        let mock = MockExporter::default();
+        let exporter = ReportingExporter::new(mock.clone(), Some(hook));
+
+        test_util::block_on(exporter.export(&mut test_util::empty_metrics())).unwrap();
+        assert!(reported.lock().unwrap().is_empty());
+
+        mock.set_failing(true);
+        let result = test_util::block_on(exporter.export(&mut test_util::empty_metrics()));
+
+        assert!(result.is_err());
+        assert_eq!(
+            *reported.lock().unwrap(),
+            vec![ExportError::Other("mock failure".to_string())]
+        );
+    }
+}
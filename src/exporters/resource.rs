@@ -0,0 +1,298 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Metrics Resource
+//!
+//! Builds the OpenTelemetry resource attached to every exported metric.
+//!
+//! The resource identifies the entity producing the metrics. It always carries the service name,
+//! namespace, environment and library language from the [`MetricsConfig`], and the service type
+//! when configured. The environment is emitted under `MetricsConfig::environment_key`,
+//! `environment` unless configured otherwise. When detectors are enabled, their attributes (host,
+//! operating system, process and Kubernetes information, plus any custom detector) are merged into
+//! the resource as well.
+//!
+//! ## Precedence
+//!
+//! When the same key comes from several sources, `MetricsConfig::resource_merge_policy`
+//! selects the winner. With the default [`ResourceMergePolicy::ConfigOverEnv`], attributes are
+//! merged in the following order, later sources overriding earlier ones:
+//!
+//! 1. The `OTEL_RESOURCE_ATTRIBUTES` / `OTEL_SERVICE_NAME` variables
+//! 2. The standard detectors, when `with_detectors` is enabled
+//! 3. The custom detectors from `detectors`
+//! 4. The attributes from the configuration
+//!
+//! [`ResourceMergePolicy::EnvOverConfig`] applies the same sources in the reverse order, so the
+//! environment variables win. In both cases the SDK defaults, such as `telemetry.sdk.name`,
+//! only fill the keys no source provides.
+//!
+//! An empty `MetricsConfig::service_name` is not exported, so `service.name` falls back to the
+//! `OTEL_SERVICE_NAME` variable, or to `unknown_service` as the OpenTelemetry convention
+//! requires, rather than attributing the metrics to an empty service.
+//!
+//! ## Renaming
+//!
+//! Once merged, the keys listed in `MetricsConfig::resource_rename` are exported under their new
+//! name, for backends expecting for example `env` instead of `environment`. The renaming applies
+//! whatever the source of the attribute, SDK defaults included.
+
+use crate::config::{MetricsConfig, ResourceMergePolicy};
+use opentelemetry::KeyValue;
+use opentelemetry_resource_detectors::{
+    HostResourceDetector, K8sResourceDetector, OsResourceDetector, ProcessResourceDetector,
+};
+use opentelemetry_sdk::{
+    Resource,
+    resource::{EnvResourceDetector, ResourceDetector},
+};
+use std::env;
+
+/// Builds the resource described by the configuration.
+///
+/// # Parameters
+///
+/// * `cfg` - The metrics configuration
+///
+/// # Returns
+///
+/// The resource to attach to the meter provider
+pub(crate) fn build(cfg: &MetricsConfig) -> Resource {
+    let mut layers = vec![
+        env_attributes(),
+        standard_attributes(cfg),
+        cfg.detectors
+            .iter()
+            .flat_map(|detector| detected_attributes(detector.detect()))
+            .collect(),
+        config_attributes(cfg),
+    ];
+
+    if cfg.resource_merge_policy == ResourceMergePolicy::EnvOverConfig {
+        layers.reverse();
+    }
+
+    let resource = layers
+        .into_iter()
+        .fold(Resource::builder(), |builder, layer| {
+            builder.with_attributes(layer)
+        })
+        .build();
+
+    rename(resource, cfg)
+}
+
+/// Renames the keys listed in `resource_rename`, passing the other keys through unchanged.
+fn rename(resource: Resource, cfg: &MetricsConfig) -> Resource {
+    if cfg.resource_rename.is_empty() {
+        return resource;
+    }
+
+    let attributes = resource.iter().map(|(k, v)| {
+        let key = match cfg.resource_rename.get(k.as_str()) {
+            Some(renamed) => renamed.clone(),
+            None => k.to_string(),
+        };
+        KeyValue::new(key, v.clone())
+    });
+
+    Resource::builder_empty()
+        .with_attributes(attributes)
+        .build()
+}
+
+/// Attributes from the `OTEL_RESOURCE_ATTRIBUTES` and `OTEL_SERVICE_NAME` variables.
+fn env_attributes() -> Vec<KeyValue> {
+    let mut attributes = detected_attributes(EnvResourceDetector::new().detect());
+
+    if let Some(name) = env::var("OTEL_SERVICE_NAME").ok().filter(|n| !n.is_empty()) {
+        attributes.push(KeyValue::new("service.name", name));
+    }
+
+    attributes
+}
+
+/// Attributes from the standard detectors, when enabled.
+fn standard_attributes(cfg: &MetricsConfig) -> Vec<KeyValue> {
+    if !cfg.with_detectors {
+        return Vec::new();
+    }
+
+    let standard: Vec<Box<dyn ResourceDetector>> = vec![
+        Box::new(HostResourceDetector::default()),
+        Box::new(OsResourceDetector),
+        Box::new(ProcessResourceDetector),
+        Box::new(K8sResourceDetector),
+    ];
+
+    detected_attributes(Resource::builder_empty().with_detectors(&standard).build())
+}
+
+/// Attributes from the configuration.
+fn config_attributes(cfg: &MetricsConfig) -> Vec<KeyValue> {
+    let mut attributes = vec![
+        KeyValue::new("service.namespace", cfg.namespace.clone()),
+        KeyValue::new(cfg.environment_key.clone(), cfg.environment.clone()),
+        KeyValue::new("library.language", "rust"),
+    ];
+
+    // an empty name would override the `unknown_service` default of the SDK
+    if !cfg.service_name.is_empty() {
+        attributes.push(KeyValue::new("service.name", cfg.service_name.clone()));
+    }
+
+    if !cfg.service_type.is_empty() {
+        attributes.push(KeyValue::new("service.type", cfg.service_type.clone()));
+    }
+
+    attributes
+}
+
+/// Converts a detected resource into key values that can be merged into a builder.
+fn detected_attributes(resource: Resource) -> Vec<KeyValue> {
+    resource
+        .iter()
+        .map(|(k, v)| KeyValue::new(k.clone(), v.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Detector;
+    use opentelemetry::Key;
+
+    struct StaticDetector(Vec<KeyValue>);
+
+    impl ResourceDetector for StaticDetector {
+        fn detect(&self) -> Resource {
+            Resource::builder_empty()
+                .with_attributes(self.0.clone())
+                .build()
+        }
+    }
+
+    fn value(resource: &Resource, key: &'static str) -> Option<String> {
+        resource
+            .get(&Key::from_static_str(key))
+            .map(|v| v.to_string())
+    }
+
+    #[test]
+    fn custom_detectors_are_merged_below_the_configuration() {
+        let cfg = MetricsConfig {
+            service_name: "checkout".to_string(),
+            detectors: vec![Detector::new(StaticDetector(vec![
+                KeyValue::new("cloud.region", "eu-west-1"),
+                KeyValue::new("service.name", "detected"),
+            ]))],
+            ..MetricsConfig::default()
+        };
+
+        let resource = build(&cfg);
+
+        assert_eq!(
+            value(&resource, "cloud.region").as_deref(),
+            Some("eu-west-1")
+        );
+        assert_eq!(
+            value(&resource, "service.name").as_deref(),
+            Some("checkout")
+        );
+        assert_eq!(
+            value(&resource, "library.language").as_deref(),
+            Some("rust")
+        );
+    }
+
+    #[test]
+    fn environment_is_emitted_under_the_configured_key() {
+        let cfg = MetricsConfig {
+            environment: "staging".to_string(),
+            environment_key: "deployment.environment".to_string(),
+            ..MetricsConfig::default()
+        };
+
+        let resource = build(&cfg);
+
+        assert_eq!(
+            value(&resource, "deployment.environment").as_deref(),
+            Some("staging")
+        );
+        assert_eq!(value(&resource, "environment"), None);
+    }
+
+    #[test]
+    fn service_type_is_emitted_only_when_configured() {
+        let cfg = MetricsConfig {
+            service_type: "api".to_string(),
+            ..MetricsConfig::default()
+        };
+
+        assert_eq!(value(&build(&cfg), "service.type").as_deref(), Some("api"));
+        assert_eq!(
+            value(&build(&MetricsConfig::default()), "service.type"),
+            None
+        );
+    }
+
+    #[test]
+    fn merge_policy_selects_the_winning_source() {
+        let cfg = MetricsConfig {
+            service_name: "checkout".to_string(),
+            detectors: vec![Detector::new(StaticDetector(vec![KeyValue::new(
+                "service.name",
+                "detected",
+            )]))],
+            ..MetricsConfig::default()
+        };
+        let reversed = MetricsConfig {
+            resource_merge_policy: ResourceMergePolicy::EnvOverConfig,
+            ..cfg.clone()
+        };
+
+        assert_eq!(
+            value(&build(&cfg), "service.name").as_deref(),
+            Some("checkout")
+        );
+        assert_eq!(
+            value(&build(&reversed), "service.name").as_deref(),
+            Some("detected")
+        );
+    }
+
+    #[test]
+    fn renamed_keys_are_exported_under_their_new_name() {
+        let cfg = MetricsConfig {
+            environment: "staging".to_string(),
+            resource_rename: [("environment".to_string(), "env".to_string())]
+                .into_iter()
+                .collect(),
+            ..MetricsConfig::default()
+        };
+
+        let resource = build(&cfg);
+
+        assert_eq!(value(&resource, "env").as_deref(), Some("staging"));
+        assert_eq!(value(&resource, "environment"), None);
+        assert_eq!(
+            value(&resource, "library.language").as_deref(),
+            Some("rust")
+        );
+    }
+
+    #[test]
+    fn empty_service_name_falls_back_to_unknown_service() {
+        let _lock = crate::test_util::global_lock();
+        // SAFETY: the tests touching the environment hold the global lock
+        unsafe { std::env::remove_var("OTEL_SERVICE_NAME") };
+
+        let resource = build(&MetricsConfig::default());
+
+        assert_eq!(
+            value(&resource, "service.name").as_deref(),
+            Some("unknown_service")
+        );
+    }
+}
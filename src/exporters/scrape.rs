@@ -0,0 +1,544 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Scrape Server
+//!
+//! Provides a minimal HTTP server exposing the Prometheus registry on `/metrics`.
+//!
+//! This module is conditionally compiled when the "prometheus" feature is enabled. Services
+//! without a web framework can expose their metrics with [`serve`] instead of wiring
+//! [`PrometheusRegistry::gather`](super::prom::PrometheusRegistry::gather) into a handler.
+//!
+//! The server only implements what scrapers need: `GET /metrics` returns the registry of the
+//! installed Prometheus exporter, every other path `404` and every other method `405`. Every
+//! connection is served on its own thread and closed after a single response.
+//!
+//! ## Limits
+//!
+//! Misbehaving scrapers must not exhaust the resources of the service:
+//!
+//! * `MetricsConfig::scrape_read_timeout` bounds the wait for the request, so a client that
+//!   connects and stalls, or sends its request a byte at a time, is disconnected
+//! * `MetricsConfig::scrape_write_timeout` bounds the time taken to send the whole response,
+//!   so a client that stops reading, or reads a byte at a time, is disconnected as well
+//! * `MetricsConfig::scrape_max_connections` bounds the connections served at the same time,
+//!   and therefore the threads. Connections beyond the limit are closed right away
+
+use super::prom::{self, CONTENT_TYPE};
+use crate::{LOG_TARGET, config::MetricsConfig, errors::MetricsError};
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+use tracing::{debug, error, warn};
+
+/// Maximum size of a request head, larger requests are rejected.
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+
+/// Path serving the metrics.
+const METRICS_PATH: &str = "/metrics";
+
+/// Starts a server exposing the installed Prometheus registry on `/metrics`.
+///
+/// # Parameters
+///
+/// * `cfg` - The metrics configuration, whose `scrape_` fields limit the connections
+/// * `addr` - The address to listen on
+///
+/// # Returns
+///
+/// * `Ok(ServerHandle)` - The handle of the running server
+/// * `Err(MetricsError::ExporterProviderError)` - If the address could not be bound
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use metrics::{config::MetricsConfig, exporters::prom};
+///
+/// let cfg = MetricsConfig::load();
+/// let provider = prom::install_with_config(&cfg).unwrap();
+/// let server = prom::serve(&cfg, "0.0.0.0:9464").unwrap();
+///
+/// // ... run the service ...
+///
+/// server.shutdown();
+/// ```
+pub fn serve(cfg: &MetricsConfig, addr: impl ToSocketAddrs) -> Result<ServerHandle, MetricsError> {
+    let bound = TcpListener::bind(addr)
+        .and_then(|listener| listener.local_addr().map(|addr| (addr, listener)));
+    let (addr, listener) = match bound {
+        Ok(bound) => Ok(bound),
+        Err(err) => {
+            error!(
+                target: LOG_TARGET,
+                error = %err,
+                "failure to bind the prometheus scrape server"
+            );
+            Err(MetricsError::ExporterProviderError)
+        }
+    }?;
+
+    let is_shutdown = Arc::new(AtomicBool::new(false));
+    let limits = Limits {
+        read_timeout: (!cfg.scrape_read_timeout.is_zero()).then_some(cfg.scrape_read_timeout),
+        write_timeout: (!cfg.scrape_write_timeout.is_zero()).then_some(cfg.scrape_write_timeout),
+        max_connections: cfg.scrape_max_connections,
+    };
+
+    let accept = {
+        let is_shutdown = is_shutdown.clone();
+        thread::Builder::new()
+            .name("metrics-scrape-server".to_string())
+            .spawn(move || accept_loop(listener, limits, is_shutdown))
+    };
+
+    let accept = match accept {
+        Ok(accept) => accept,
+        Err(err) => {
+            error!(
+                target: LOG_TARGET,
+                error = %err,
+                "failure to start the prometheus scrape server"
+            );
+            return Err(MetricsError::ExporterProviderError);
+        }
+    };
+
+    debug!(
+        target: LOG_TARGET,
+        address = %addr,
+        "prometheus scrape server listening"
+    );
+
+    Ok(ServerHandle {
+        addr,
+        is_shutdown,
+        accept: Some(accept),
+    })
+}
+
+/// # ServerHandle
+///
+/// Handle of a running scrape server. The server stops when the handle is shut down or dropped.
+#[derive(Debug)]
+pub struct ServerHandle {
+    addr: SocketAddr,
+    is_shutdown: Arc<AtomicBool>,
+    accept: Option<JoinHandle<()>>,
+}
+
+impl ServerHandle {
+    /// Returns the address the server listens on, useful when bound to port `0`.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Stops accepting connections and waits for the server thread to exit.
+    ///
+    /// Connections already accepted are still answered.
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        let Some(accept) = self.accept.take() else {
+            return;
+        };
+
+        self.is_shutdown.store(true, Ordering::SeqCst);
+        // wake the blocking accept so the loop observes the flag
+        let _ = TcpStream::connect(wake_addr(self.addr));
+        let _ = accept.join();
+
+        debug!(target: LOG_TARGET, address = %self.addr, "prometheus scrape server stopped");
+    }
+}
+
+impl Drop for ServerHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Returns the address to connect to in order to reach the listener bound on `addr`.
+///
+/// A listener bound to every interface, such as `0.0.0.0` or `[::]`, cannot be connected to
+/// through that address on every platform, so the loopback address of the same family is used.
+fn wake_addr(addr: SocketAddr) -> SocketAddr {
+    let ip = match addr.ip() {
+        IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        ip => ip,
+    };
+    SocketAddr::new(ip, addr.port())
+}
+
+/// The limits applied to the connections, from the configuration.
+#[derive(Debug, Clone, Copy)]
+struct Limits {
+    read_timeout: Option<Duration>,
+    write_timeout: Option<Duration>,
+    max_connections: usize,
+}
+
+/// Decrements the number of active connections when the connection is done.
+struct Active(Arc<AtomicUsize>);
+
+impl Drop for Active {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Accepts connections until shut down, serving each one on its own thread.
+fn accept_loop(listener: TcpListener, limits: Limits, is_shutdown: Arc<AtomicBool>) {
+    let active = Arc::new(AtomicUsize::new(0));
+
+    for stream in listener.incoming() {
+        if is_shutdown.load(Ordering::SeqCst) {
+            return;
+        }
+
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                warn!(target: LOG_TARGET, error = %err, "failure to accept scrape connection");
+                continue;
+            }
+        };
+
+        let count = active.fetch_add(1, Ordering::SeqCst);
+        let guard = Active(active.clone());
+        if limits.max_connections > 0 && count >= limits.max_connections {
+            warn!(
+                target: LOG_TARGET,
+                max_connections = limits.max_connections,
+                "scrape connection closed, too many connections"
+            );
+            continue;
+        }
+
+        let spawned = thread::Builder::new()
+            .name("metrics-scrape".to_string())
+            .spawn(move || {
+                let _guard = guard;
+                handle(stream, limits)
+            });
+        if let Err(err) = spawned {
+            warn!(target: LOG_TARGET, error = %err, "failure to serve scrape connection");
+        }
+    }
+}
+
+/// Answers a single request on the connection.
+fn handle(mut stream: TcpStream, limits: Limits) {
+    let response = match read_request_line(&mut stream, limits.read_timeout) {
+        Ok(line) => respond(&line),
+        Err(err) => {
+            debug!(target: LOG_TARGET, error = %err, "invalid scrape request");
+            response("400 Bad Request", "text/plain", "bad request\n")
+        }
+    };
+
+    if let Err(err) = write_response(&mut stream, response.as_bytes(), limits.write_timeout) {
+        debug!(target: LOG_TARGET, error = %err, "failure to write scrape response");
+    }
+}
+
+/// Writes the whole response.
+///
+/// The socket timeout bounds each write, so it is lowered before every write to what is left of
+/// `timeout`, which a client reading slowly would otherwise stretch indefinitely.
+fn write_response(
+    stream: &mut TcpStream,
+    mut response: &[u8],
+    timeout: Option<Duration>,
+) -> Result<(), String> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+    while !response.is_empty() {
+        if let Some(deadline) = deadline {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err("response not sent in time".to_string());
+            }
+            stream
+                .set_write_timeout(Some(remaining))
+                .map_err(|err| err.to_string())?;
+        }
+
+        match stream.write(response) {
+            Ok(0) => return Err("connection closed before the end of the response".to_string()),
+            Ok(written) => response = &response[written..],
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(err.to_string()),
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the request head and returns its first line.
+///
+/// The socket timeout of each read is the time left before `timeout`, so `timeout` bounds the
+/// whole head, which a client trickling bytes would otherwise stretch indefinitely.
+fn read_request_line(stream: &mut TcpStream, timeout: Option<Duration>) -> Result<String, String> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut head = Vec::new();
+    let mut buf = [0; 1024];
+
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_SIZE {
+            return Err("request head too large".to_string());
+        }
+        if let Some(deadline) = deadline {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err("request head not received in time".to_string());
+            }
+            stream
+                .set_read_timeout(Some(remaining))
+                .map_err(|err| err.to_string())?;
+        }
+
+        match stream.read(&mut buf) {
+            Ok(0) => return Err("connection closed before the end of the request".to_string()),
+            Ok(read) => head.extend_from_slice(&buf[..read]),
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(err.to_string()),
+        }
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    Ok(head.lines().next().unwrap_or_default().to_string())
+}
+
+/// Builds the response to the request line.
+fn respond(line: &str) -> String {
+    let mut parts = line.split_whitespace();
+    let (method, target) = (parts.next(), parts.next());
+
+    // the query string is ignored
+    let path = target.map(|t| t.split('?').next().unwrap_or_default());
+
+    match (method, path) {
+        (Some("GET"), Some(METRICS_PATH)) => match prom::registry().map(|r| r.gather()) {
+            Some(Ok(body)) => response("200 OK", CONTENT_TYPE, &body),
+            Some(Err(_)) => response(
+                "500 Internal Server Error",
+                "text/plain",
+                "failure to collect the metrics\n",
+            ),
+            None => response(
+                "503 Service Unavailable",
+                "text/plain",
+                "prometheus exporter not installed\n",
+            ),
+        },
+        (Some("GET"), _) => response("404 Not Found", "text/plain", "not found\n"),
+        _ => response(
+            "405 Method Not Allowed",
+            "text/plain",
+            "method not allowed\n",
+        ),
+    }
+}
+
+/// Formats a response closing the connection.
+fn response(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {status}\r\ncontent-type: {content_type}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use opentelemetry::metrics::MeterProvider;
+
+    /// Sends the raw request to the server and returns the whole response.
+    fn request(addr: SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn metrics_are_served_on_the_metrics_path() {
+        let _lock = test_util::global_lock();
+        let cfg = MetricsConfig::default();
+        let provider = crate::exporters::provider_builder(&cfg)
+            .with_reader(prom::register(&cfg))
+            .build();
+        provider
+            .meter("test")
+            .u64_counter("requests")
+            .build()
+            .add(2, &[]);
+        let server = serve(&cfg, "127.0.0.1:0").unwrap();
+        let addr = server.local_addr();
+
+        let metrics = request(
+            addr,
+            "GET /metrics?format=text HTTP/1.1\r\nHost: test\r\n\r\n",
+        );
+        let missing = request(addr, "GET /other HTTP/1.1\r\n\r\n");
+        let post = request(addr, "POST /metrics HTTP/1.1\r\n\r\n");
+        server.shutdown();
+
+        assert!(metrics.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(metrics.contains(&format!("content-type: {CONTENT_TYPE}\r\n")));
+        assert!(metrics.ends_with("requests_total 2\n"));
+        assert!(missing.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        assert!(post.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+    }
+
+    #[test]
+    fn server_bound_to_every_interface_shuts_down() {
+        let cfg = MetricsConfig::default();
+        let server = serve(&cfg, "0.0.0.0:0").unwrap();
+        let port = server.local_addr().port();
+
+        server.shutdown();
+
+        assert!(TcpStream::connect(("127.0.0.1", port)).is_err());
+    }
+
+    #[test]
+    fn wake_addr_uses_the_loopback_of_the_same_family() {
+        let v4 = wake_addr("0.0.0.0:9464".parse().unwrap());
+        let v6 = wake_addr("[::]:9464".parse().unwrap());
+        let specific = wake_addr("10.0.0.1:9464".parse().unwrap());
+
+        assert_eq!(v4, "127.0.0.1:9464".parse().unwrap());
+        assert_eq!(v6, "[::1]:9464".parse().unwrap());
+        assert_eq!(specific, "10.0.0.1:9464".parse().unwrap());
+    }
+
+    /// Returns a configuration with short limits, so the tests do not wait for the defaults.
+    fn short_limits() -> MetricsConfig {
+        MetricsConfig {
+            scrape_read_timeout: Duration::from_millis(200),
+            scrape_write_timeout: Duration::from_millis(200),
+            scrape_max_connections: 1,
+            ..MetricsConfig::default()
+        }
+    }
+
+    #[test]
+    fn stalled_clients_are_timed_out() {
+        let server = serve(&short_limits(), "127.0.0.1:0").unwrap();
+        let mut stalled = TcpStream::connect(server.local_addr()).unwrap();
+        stalled
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let started_at = Instant::now();
+
+        let mut response = String::new();
+        stalled.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+        assert!(started_at.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn trickling_clients_are_timed_out() {
+        let server = serve(&short_limits(), "127.0.0.1:0").unwrap();
+        let mut trickling = TcpStream::connect(server.local_addr()).unwrap();
+        trickling
+            .set_read_timeout(Some(Duration::from_millis(50)))
+            .unwrap();
+
+        // each byte arrives well within the read timeout, the whole head does not
+        let mut response = Vec::new();
+        for byte in b"GET /metrics HTTP/1.1\r\nHost: test\r\n"
+            .iter()
+            .cycle()
+            .take(200)
+        {
+            if trickling.write_all(&[*byte]).is_err() {
+                break;
+            }
+            match trickling.read_to_end(&mut response) {
+                Ok(_) => break,
+                // nothing to read yet, the server is still waiting for the head
+                Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(_) => break,
+            }
+        }
+
+        assert!(String::from_utf8_lossy(&response).starts_with("HTTP/1.1 400 Bad Request\r\n"));
+    }
+
+    #[test]
+    fn late_bytes_do_not_extend_the_read_timeout() {
+        let cfg = MetricsConfig {
+            scrape_read_timeout: Duration::from_millis(500),
+            ..short_limits()
+        };
+        let server = serve(&cfg, "127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(server.local_addr()).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let started_at = Instant::now();
+
+        // a byte just before the deadline only leaves the rest of the timeout for the next read
+        thread::sleep(Duration::from_millis(400));
+        client.write_all(b"G").unwrap();
+
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+        assert!(started_at.elapsed() < Duration::from_millis(800));
+    }
+
+    #[test]
+    fn slow_readers_are_bounded_by_the_write_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        // the client never reads, so the socket buffers fill up
+        let _client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (mut stream, _) = listener.accept().unwrap();
+        let started_at = Instant::now();
+
+        let written = write_response(
+            &mut stream,
+            &vec![b'x'; 64 * 1024 * 1024],
+            Some(Duration::from_millis(200)),
+        );
+
+        assert!(written.is_err());
+        assert!(started_at.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn connections_beyond_the_limit_are_closed() {
+        let server = serve(&short_limits(), "127.0.0.1:0").unwrap();
+        let _first = TcpStream::connect(server.local_addr()).unwrap();
+        let mut second = TcpStream::connect(server.local_addr()).unwrap();
+        second
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+
+        // the connection is closed right away rather than answered once it times out
+        let mut response = Vec::new();
+        second.read_to_end(&mut response).unwrap();
+
+        assert!(response.is_empty());
+    }
+}
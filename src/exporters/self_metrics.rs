@@ -0,0 +1,193 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Self Metrics
+//!
+//! Provides an exporter wrapper reporting metrics about the exports themselves.
+//!
+//! This module is conditionally compiled when the "self-metrics" feature is enabled. The OTLP
+//! exporter wraps each collector exporter so the time every export RPC takes is recorded in the
+//! `otel_metric_export_duration_seconds` histogram, with the following attributes:
+//!
+//! * `endpoint` - The collector the export was sent to
+//! * `outcome` - `success` or `failure`
+//!
+//! Comparing the duration with `MetricsConfig::exporter_timeout` shows whether exports are
+//! getting close to the timeout, which helps tuning the interval and the timeout.
+//!
+//! The histogram is recorded on the installed provider, so each value is exported with the
+//! next cycle.
+//!
+//! The names of the self metrics are prefixed with `MetricsConfig::self_metrics_prefix`,
+//! `ruskit_metrics_` by default, so they cannot collide with the metrics of the application or
+//! of other libraries and are easy to tell apart in dashboards.
+
+use crate::LOG_TARGET;
+use opentelemetry::{KeyValue, global, metrics::Histogram};
+use opentelemetry_sdk::{
+    error::OTelSdkResult,
+    metrics::{Temporality, data::ResourceMetrics, exporter::PushMetricExporter},
+};
+use std::{sync::OnceLock, time::Instant};
+use tracing::debug;
+
+/// Name of the meter owning the self metrics.
+const METER_NAME: &str = "metrics";
+
+/// Name of the export duration histogram, without the prefix.
+const EXPORT_DURATION: &str = "otel_metric_export_duration_seconds";
+
+/// Bucket boundaries of the export duration histogram, in seconds.
+const EXPORT_DURATION_BOUNDARIES: [f64; 12] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+/// # SelfMetricsExporter
+///
+/// Wraps an exporter and records the duration of every export.
+///
+/// The histogram is created on the first export rather than with the wrapper, because the
+/// wrapper is built before the provider it records on is installed globally.
+#[derive(Debug)]
+pub(crate) struct SelfMetricsExporter<E> {
+    inner: E,
+    endpoint: String,
+    prefix: String,
+    duration: OnceLock<Histogram<f64>>,
+}
+
+impl<E> SelfMetricsExporter<E> {
+    /// Wraps `inner`, the exporter sending to `endpoint`, naming the metrics after `prefix`.
+    pub(crate) fn new(inner: E, endpoint: &str, prefix: &str) -> Self {
+        Self {
+            inner,
+            endpoint: endpoint.to_string(),
+            prefix: prefix.to_string(),
+            duration: OnceLock::new(),
+        }
+    }
+
+    fn duration(&self) -> &Histogram<f64> {
+        self.duration.get_or_init(|| {
+            let name = format!("{}{EXPORT_DURATION}", self.prefix);
+            debug!(target: LOG_TARGET, name = %name, "self metric registered");
+
+            global::meter(METER_NAME)
+                .f64_histogram(name)
+                .with_description("Duration of the metrics exports")
+                .with_unit("s")
+                .with_boundaries(EXPORT_DURATION_BOUNDARIES.to_vec())
+                .build()
+        })
+    }
+}
+
+impl<E: PushMetricExporter> PushMetricExporter for SelfMetricsExporter<E> {
+    async fn export(&self, metrics: &mut ResourceMetrics) -> OTelSdkResult {
+        let started_at = Instant::now();
+        let result = self.inner.export(metrics).await;
+
+        let outcome = if result.is_ok() { "success" } else { "failure" };
+        self.duration().record(
+            started_at.elapsed().as_secs_f64(),
+            &[
+                KeyValue::new("endpoint", self.endpoint.clone()),
+                KeyValue::new("outcome", outcome),
+            ],
+        );
+
+        result
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        self.inner.shutdown()
+    }
+
+    fn temporality(&self) -> Temporality {
+        self.inner.temporality()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::MetricsConfig,
+        test_util::{self, MockExporter},
+    };
+    use opentelemetry_sdk::metrics::data::Histogram as HistogramData;
+
+    #[test]
+    fn export_duration_is_recorded_with_the_outcome() {
+        let _lock = test_util::global_lock();
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        global::set_meter_provider(provider.clone());
+        let mock = MockExporter::default();
+        let exporter = SelfMetricsExporter::new(mock.clone(), "http://collector:4317", "app_");
+
+        test_util::block_on(async {
+            let _ = exporter.export(&mut test_util::empty_metrics()).await;
+            mock.set_failing(true);
+            let _ = exporter.export(&mut test_util::empty_metrics()).await;
+        });
+
+        let rm = test_util::collect(&reader);
+        let metric = test_util::metric(&rm, "app_otel_metric_export_duration_seconds")
+            .expect("export duration");
+        let hist = metric
+            .data
+            .as_any()
+            .downcast_ref::<HistogramData<f64>>()
+            .unwrap();
+        let mut outcomes = hist
+            .data_points
+            .iter()
+            .map(|dp| {
+                let outcome = dp
+                    .attributes
+                    .iter()
+                    .find(|kv| kv.key.as_str() == "outcome")
+                    .map(|kv| kv.value.to_string());
+                (outcome, dp.count)
+            })
+            .collect::<Vec<_>>();
+        outcomes.sort();
+        assert_eq!(
+            outcomes,
+            vec![
+                (Some("failure".to_string()), 1),
+                (Some("success".to_string()), 1)
+            ]
+        );
+        assert_eq!(
+            hist.data_points[0].bounds,
+            EXPORT_DURATION_BOUNDARIES.to_vec()
+        );
+    }
+
+    #[test]
+    fn self_metrics_use_the_default_prefix() {
+        let _lock = test_util::global_lock();
+        let cfg = MetricsConfig::default();
+        let (provider, reader) = test_util::provider(&cfg);
+        global::set_meter_provider(provider.clone());
+        let exporter = SelfMetricsExporter::new(
+            MockExporter::default(),
+            "http://collector:4317",
+            &cfg.self_metrics_prefix,
+        );
+
+        let _ = test_util::block_on(exporter.export(&mut test_util::empty_metrics()));
+
+        let rm = test_util::collect(&reader);
+        assert!(
+            test_util::metric(&rm, "ruskit_metrics_otel_metric_export_duration_seconds").is_some()
+        );
+        assert!(test_util::metric(&rm, EXPORT_DURATION).is_none());
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # OTLP Snapshots
+//!
+//! Encodes the current metrics to OTLP protobuf bytes on demand.
+//!
+//! This module is conditionally compiled when the "otlp" feature is enabled. An
+//! [`OtlpSnapshot`] placed in `MetricsConfig::otlp_snapshot` is registered as an additional
+//! reader on the installed provider. Each call to [`OtlpSnapshot::collect_bytes`] collects the
+//! metrics synchronously and encodes them as an `ExportMetricsServiceRequest`, the payload the
+//! OTLP exporters send, without involving any transport. The transforms of the configuration
+//! (global attributes, redaction, rounding) apply as they do to the OTLP exports.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use metrics::{config::MetricsConfig, exporters::snapshot::OtlpSnapshot, provider};
+//!
+//! let snapshot = OtlpSnapshot::new();
+//! let cfg = MetricsConfig {
+//!     otlp_snapshot: Some(snapshot.clone()),
+//!     ..MetricsConfig::load()
+//! };
+//! # let _ = cfg;
+//!
+//! // later, for example in a snapshot endpoint
+//! let bytes: Vec<u8> = snapshot.collect_bytes().unwrap();
+//! ```
+
+use super::{reader::SharedReader, transform::Transform};
+use crate::{
+    LOG_TARGET,
+    config::{ExporterKind, MetricsConfig},
+    errors::MetricsError,
+};
+use opentelemetry_proto::tonic::collector::metrics::v1::ExportMetricsServiceRequest;
+use opentelemetry_sdk::{
+    Resource,
+    metrics::{data::ResourceMetrics, reader::MetricReader},
+};
+use prost::Message;
+use std::sync::{Arc, OnceLock};
+use tracing::error;
+
+/// # OtlpSnapshot
+///
+/// A handle collecting the metrics of the provider it is registered on as OTLP protobuf bytes.
+///
+/// Clones share the same reader. The snapshot uses cumulative temporality, so every call
+/// returns the totals since the provider was installed.
+#[derive(Debug, Clone)]
+pub struct OtlpSnapshot {
+    reader: SharedReader,
+    transform: Arc<OnceLock<Transform>>,
+}
+
+impl Default for OtlpSnapshot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for OtlpSnapshot {
+    fn eq(&self, other: &Self) -> bool {
+        self.reader.ptr_eq(&other.reader)
+    }
+}
+
+impl OtlpSnapshot {
+    /// Creates a snapshot handle, not yet registered on any provider.
+    pub fn new() -> Self {
+        Self {
+            reader: SharedReader::new(),
+            transform: Arc::new(OnceLock::new()),
+        }
+    }
+
+    /// Collects the current metrics and encodes them as an OTLP `ExportMetricsServiceRequest`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<u8>)` - The protobuf encoded request
+    /// * `Err(MetricsError::InternalError)` - If the snapshot is not registered on a provider,
+    ///   or the provider was shut down
+    pub fn collect_bytes(&self) -> Result<Vec<u8>, MetricsError> {
+        let mut rm = ResourceMetrics {
+            resource: Resource::builder_empty().build(),
+            scope_metrics: Vec::new(),
+        };
+
+        if let Err(err) = self.reader.collect(&mut rm) {
+            error!(target: LOG_TARGET, error = %err, "failure to collect the metrics snapshot");
+            return Err(MetricsError::InternalError);
+        }
+
+        if let Some(transform) = self.transform.get() {
+            transform.apply(&mut rm);
+        }
+
+        Ok(ExportMetricsServiceRequest::from(&rm).encode_to_vec())
+    }
+
+    /// Returns the reader to register on the provider built from `cfg`, whose transforms the
+    /// snapshots apply.
+    pub(crate) fn register(&self, cfg: &MetricsConfig) -> SharedReader {
+        let _ = self
+            .transform
+            .set(Transform::for_exporter(cfg, ExporterKind::Otlp));
+        self.reader.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use opentelemetry::{KeyValue, metrics::MeterProvider};
+    use opentelemetry_proto::tonic::{common::v1::any_value::Value, metrics::v1::metric::Data};
+
+    #[test]
+    fn collect_bytes_encodes_the_current_metrics() {
+        let snapshot = OtlpSnapshot::new();
+        let cfg = MetricsConfig {
+            otlp_snapshot: Some(snapshot.clone()),
+            ..MetricsConfig::default()
+        };
+        let (provider, _reader) = test_util::provider(&cfg);
+        provider
+            .meter("test")
+            .u64_counter("requests")
+            .build()
+            .add(3, &[]);
+
+        let bytes = snapshot.collect_bytes().unwrap();
+        let request = ExportMetricsServiceRequest::decode(bytes.as_slice()).unwrap();
+
+        let metrics = &request.resource_metrics[0].scope_metrics[0].metrics;
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].name, "requests");
+    }
+
+    #[test]
+    fn collect_bytes_applies_the_redaction() {
+        let snapshot = OtlpSnapshot::new();
+        let cfg = MetricsConfig {
+            otlp_snapshot: Some(snapshot.clone()),
+            redacted_attributes: vec!["user.email".to_string()],
+            ..MetricsConfig::default()
+        };
+        let (provider, _reader) = test_util::provider(&cfg);
+        provider
+            .meter("test")
+            .u64_counter("logins")
+            .build()
+            .add(1, &[KeyValue::new("user.email", "jane@example.com")]);
+
+        let bytes = snapshot.collect_bytes().unwrap();
+        assert!(!String::from_utf8_lossy(&bytes).contains("jane@example.com"));
+
+        let request = ExportMetricsServiceRequest::decode(bytes.as_slice()).unwrap();
+        let metric = &request.resource_metrics[0].scope_metrics[0].metrics[0];
+        let Some(Data::Sum(sum)) = &metric.data else {
+            panic!("expected a sum, got {:?}", metric.data);
+        };
+        let attribute = &sum.data_points[0].attributes[0];
+        assert_eq!(attribute.key, "user.email");
+        assert_eq!(
+            attribute
+                .value
+                .as_ref()
+                .and_then(|value| value.value.clone()),
+            Some(Value::StringValue("[redacted]".to_string()))
+        );
+    }
+}
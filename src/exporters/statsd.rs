@@ -0,0 +1,556 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # StatsD Metrics Exporter
+//!
+//! Provides an exporter sending metrics to a StatsD server over UDP.
+//!
+//! This module is conditionally compiled when the "statsd" feature is enabled. It targets
+//! legacy infrastructure still ingesting metrics through StatsD, and sends its packets to
+//! `MetricsConfig::statsd_address`.
+//!
+//! ## Translation
+//!
+//! The exporter uses delta temporality, which matches StatsD semantics: every export sends what
+//! was recorded since the previous one, and the server aggregates.
+//!
+//! | OpenTelemetry | StatsD |
+//! |---------------|--------|
+//! | Counter | `name:delta\|c` |
+//! | UpDownCounter | `name:+delta\|g`, a relative gauge update |
+//! | Gauge | `name:value\|g` |
+//! | Histogram | `name.count:count\|c` and `name.sum:sum\|c` |
+//!
+//! Histograms are aggregated by the SDK before export, so the individual samples a StatsD
+//! timer expects are no longer available. Their count and sum are sent as counters instead,
+//! from which the server can derive the mean.
+//!
+//! Attributes are sent as DogStatsD tags, for example `http.requests:1|c|#method:GET,status:200`.
+//! The characters the protocol reserves, `|`, `,`, `:`, `#` and newlines, are replaced with `_`
+//! in metric names, tag keys and tag values, as the DogStatsD clients do, so a value cannot
+//! break the line or inject another metric.
+//!
+//! ## Failed Exports
+//!
+//! The SDK resets the delta aggregations when it collects, before the export runs, so the data
+//! of an export that fails is no longer held anywhere. `MetricsConfig::reset_on_failure`
+//! decides what happens to it:
+//!
+//! * `true`, the default - The data is discarded. The server misses the measurements of that
+//!   cycle, a gap, but never counts anything twice
+//! * `false` - The packets that could not be sent are kept and sent before the packets of the
+//!   next export, so the server receives every increment once the socket recovers. Packets are
+//!   only kept when the socket reported that they were not sent, so nothing is counted twice.
+//!   At most 64 packets are kept, the oldest are dropped beyond that
+//!
+//! Only transient failures stop the export: the server being unreachable (`ConnectionRefused`)
+//! or the socket buffer being full (`WouldBlock`). A packet the socket rejects for good, such
+//! as one larger than a datagram, is dropped with a warning whatever the option, and the
+//! following packets are still sent, so it never blocks the ones behind it.
+//!
+//! Gauges are absolute, so a kept gauge packet is overwritten by the value of the next export.
+//! The option only concerns the delta exports of this exporter: the cumulative exporters send
+//! the totals so far with every export, so a failed export only loses an intermediate point.
+//!
+//! ## Configuration
+//!
+//! Enable this exporter by building with the `statsd` feature flag:
+//!
+//! ```sh
+//! cargo build --features statsd
+//! ```
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use metrics::exporters::statsd;
+//! let provider = statsd::install().unwrap();
+//! ```
+
+use super::{reader::periodic, transform::TransformExporter};
+use crate::{
+    LOG_TARGET,
+    config::{ExporterKind, MetricsConfig},
+    errors::MetricsError,
+};
+use opentelemetry::KeyValue;
+use opentelemetry_sdk::{
+    error::{OTelSdkError, OTelSdkResult},
+    metrics::{
+        SdkMeterProvider, Temporality,
+        data::{Gauge, Histogram, ResourceMetrics, Sum},
+        exporter::PushMetricExporter,
+    },
+};
+use std::{
+    fmt::{self, Display},
+    io, iter,
+    net::UdpSocket,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+use tracing::{debug, error, warn};
+
+/// Maximum size of a single UDP packet, safe for the usual network MTUs.
+const MAX_PACKET_SIZE: usize = 1432;
+
+/// Maximum number of unsent packets kept for the next export when `reset_on_failure` is unset.
+const MAX_RETAINED_PACKETS: usize = 64;
+
+/// Creates and installs a StatsD metrics exporter.
+///
+/// # Returns
+///
+/// * `Ok(SdkMeterProvider)` - The configured meter provider
+/// * `Err(MetricsError)` - If an error occurred during exporter setup
+pub fn install() -> Result<SdkMeterProvider, MetricsError> {
+    install_with_config(&MetricsConfig::load())
+}
+
+/// Creates and installs a StatsD metrics exporter from an explicit configuration.
+///
+/// Behaves like [`install`], but reads the server address, interval and resource attributes
+/// from the given [`MetricsConfig`] instead of the ambient Ruskit configuration.
+///
+/// # Parameters
+///
+/// * `cfg` - The metrics configuration
+///
+/// # Returns
+///
+/// * `Ok(SdkMeterProvider)` - The configured meter provider
+/// * `Err(MetricsError::ExporterProviderError)` - If the UDP socket could not be created
+pub fn install_with_config(cfg: &MetricsConfig) -> Result<SdkMeterProvider, MetricsError> {
+    let socket = match connect(&cfg.statsd_address) {
+        Ok(s) => Ok(s),
+        Err(err) => {
+            error!(
+                target: LOG_TARGET,
+                error = err.to_string(),
+                address = %cfg.statsd_address,
+                "failure to create statsd socket"
+            );
+            Err(MetricsError::ExporterProviderError)
+        }
+    }?;
+
+    let exporter = TransformExporter::for_exporter(
+        StatsdExporter::new(socket, cfg.reset_on_failure),
+        cfg,
+        ExporterKind::Statsd,
+    );
+    let reader = periodic(exporter, cfg);
+
+    let provider = super::provider_builder(cfg).with_reader(reader).build();
+
+    super::install_global(&provider);
+
+    debug!(
+        target: LOG_TARGET,
+        exporter = "statsd",
+        address = %cfg.statsd_address,
+        interval = ?cfg.exporter_interval,
+        "metrics::install statsd exporter installed"
+    );
+
+    Ok(provider)
+}
+
+/// Binds an ephemeral UDP socket sending to `address`.
+fn connect(address: &str) -> std::io::Result<UdpSocket> {
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    socket.connect(address)?;
+    Ok(socket)
+}
+
+/// # StatsdExporter
+///
+/// Exporter translating every data point into a StatsD line, sent in UDP packets of at most
+/// `MAX_PACKET_SIZE` bytes.
+#[derive(Debug)]
+struct StatsdExporter {
+    socket: UdpSocket,
+    reset_on_failure: bool,
+    retained: Mutex<Vec<String>>,
+    is_shutdown: AtomicBool,
+}
+
+impl StatsdExporter {
+    fn new(socket: UdpSocket, reset_on_failure: bool) -> Self {
+        Self {
+            socket,
+            reset_on_failure,
+            retained: Mutex::new(Vec::new()),
+            is_shutdown: AtomicBool::new(false),
+        }
+    }
+
+    /// Sends the lines, packing as many of them as fit in each packet, after the packets kept
+    /// from a failed export.
+    fn send(&self, lines: Vec<String>) -> OTelSdkResult {
+        let mut retained = self.retained.lock().unwrap_or_else(|e| e.into_inner());
+        let mut packets = std::mem::take(&mut *retained)
+            .into_iter()
+            .chain(pack(lines));
+        let mut result = Ok(());
+
+        while let Some(packet) = packets.next() {
+            let err = match self.socket.send(packet.as_bytes()) {
+                Ok(_) => continue,
+                Err(err) => err,
+            };
+
+            if !is_transient(&err) {
+                warn!(
+                    target: LOG_TARGET,
+                    error = %err,
+                    size = packet.len(),
+                    "statsd packet dropped, the socket rejected it"
+                );
+                result = Err(OTelSdkError::InternalFailure(err.to_string()));
+                continue;
+            }
+
+            if !self.reset_on_failure {
+                *retained = iter::once(packet).chain(packets).collect();
+                if retained.len() > MAX_RETAINED_PACKETS {
+                    let dropped = retained.len() - MAX_RETAINED_PACKETS;
+                    retained.drain(..dropped);
+                    warn!(
+                        target: LOG_TARGET,
+                        dropped = dropped,
+                        "statsd packets dropped, too many kept from failed exports"
+                    );
+                }
+            }
+
+            return Err(OTelSdkError::InternalFailure(err.to_string()));
+        }
+
+        result
+    }
+}
+
+impl PushMetricExporter for StatsdExporter {
+    async fn export(&self, metrics: &mut ResourceMetrics) -> OTelSdkResult {
+        if self.is_shutdown.load(Ordering::SeqCst) {
+            return Err(OTelSdkError::AlreadyShutdown);
+        }
+
+        let mut lines = Vec::new();
+
+        for scope in &metrics.scope_metrics {
+            for metric in &scope.metrics {
+                let name = metric.name.as_ref();
+                let data = metric.data.as_any();
+
+                if let Some(sum) = data.downcast_ref::<Sum<u64>>() {
+                    push_sum(&mut lines, name, sum);
+                } else if let Some(sum) = data.downcast_ref::<Sum<i64>>() {
+                    push_sum(&mut lines, name, sum);
+                } else if let Some(sum) = data.downcast_ref::<Sum<f64>>() {
+                    push_sum(&mut lines, name, sum);
+                } else if let Some(gauge) = data.downcast_ref::<Gauge<u64>>() {
+                    push_gauge(&mut lines, name, gauge);
+                } else if let Some(gauge) = data.downcast_ref::<Gauge<i64>>() {
+                    push_gauge(&mut lines, name, gauge);
+                } else if let Some(gauge) = data.downcast_ref::<Gauge<f64>>() {
+                    push_gauge(&mut lines, name, gauge);
+                } else if let Some(hist) = data.downcast_ref::<Histogram<u64>>() {
+                    push_histogram(&mut lines, name, hist);
+                } else if let Some(hist) = data.downcast_ref::<Histogram<f64>>() {
+                    push_histogram(&mut lines, name, hist);
+                }
+            }
+        }
+
+        self.send(lines)
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        Ok(())
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        self.is_shutdown.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn temporality(&self) -> Temporality {
+        Temporality::Delta
+    }
+}
+
+/// Packs the lines into packets of at most `MAX_PACKET_SIZE` bytes, a longer line filling a
+/// packet of its own.
+fn pack(lines: Vec<String>) -> Vec<String> {
+    let mut packets = Vec::new();
+    let mut packet = String::new();
+
+    for line in lines {
+        if !packet.is_empty() && packet.len() + line.len() + 1 > MAX_PACKET_SIZE {
+            packets.push(std::mem::take(&mut packet));
+        }
+        if !packet.is_empty() {
+            packet.push('\n');
+        }
+        packet.push_str(&line);
+    }
+
+    if !packet.is_empty() {
+        packets.push(packet);
+    }
+
+    packets
+}
+
+/// Returns whether a send failure may succeed later, so the packet is worth keeping.
+fn is_transient(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::ConnectionRefused
+    )
+}
+
+/// Renders sums as counters, or as relative gauge updates when they are not monotonic.
+fn push_sum<T: Display>(lines: &mut Vec<String>, name: &str, sum: &Sum<T>) {
+    let name = sanitize(name);
+    for dp in &sum.data_points {
+        let tags = format_tags(&dp.attributes);
+        if sum.is_monotonic {
+            lines.push(format!("{name}:{}|c{tags}", dp.value));
+        } else {
+            lines.push(format!("{name}:{}|g{tags}", Signed(&dp.value)));
+        }
+    }
+}
+
+/// Renders gauges as absolute gauge values.
+fn push_gauge<T: Display>(lines: &mut Vec<String>, name: &str, gauge: &Gauge<T>) {
+    let name = sanitize(name);
+    for dp in &gauge.data_points {
+        let tags = format_tags(&dp.attributes);
+        lines.push(format!("{name}:{}|g{tags}", dp.value));
+    }
+}
+
+/// Renders histograms as a count and a sum counter.
+fn push_histogram<T: Display>(lines: &mut Vec<String>, name: &str, hist: &Histogram<T>) {
+    let name = sanitize(name);
+    for dp in &hist.data_points {
+        let tags = format_tags(&dp.attributes);
+        lines.push(format!("{name}.count:{}|c{tags}", dp.count));
+        lines.push(format!("{name}.sum:{}|c{tags}", dp.sum));
+    }
+}
+
+/// Renders attributes as DogStatsD tags, or an empty string when there are none.
+fn format_tags(attributes: &[KeyValue]) -> String {
+    if attributes.is_empty() {
+        return String::new();
+    }
+
+    let tags = attributes
+        .iter()
+        .map(|kv| {
+            format!(
+                "{}:{}",
+                sanitize(kv.key.as_str()),
+                sanitize(&kv.value.to_string())
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!("|#{tags}")
+}
+
+/// Replaces the characters reserved by the StatsD line protocol with `_`.
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '|' | ',' | ':' | '#' | '\n' | '\r' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// Displays a value with an explicit sign, as required by relative gauge updates.
+struct Signed<'a, T>(&'a T);
+
+impl<T: Display> Display for Signed<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self.0.to_string();
+        if value.starts_with('-') {
+            f.write_str(&value)
+        } else {
+            write!(f, "+{value}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry_sdk::metrics::data::GaugeDataPoint;
+    use std::{
+        thread,
+        time::{Duration, SystemTime},
+    };
+
+    /// Binds a UDP socket standing for the StatsD server.
+    fn server() -> UdpSocket {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        server
+    }
+
+    fn receive(server: &UdpSocket) -> String {
+        let mut buf = [0; MAX_PACKET_SIZE];
+        let len = server.recv(&mut buf).unwrap();
+        String::from_utf8_lossy(&buf[..len]).into_owned()
+    }
+
+    #[test]
+    fn export_sends_statsd_lines_to_the_server() {
+        let server = server();
+        let address = server.local_addr().unwrap().to_string();
+        let exporter = StatsdExporter::new(connect(&address).unwrap(), true);
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        let meter = provider.meter("test");
+        meter
+            .u64_counter("requests")
+            .build()
+            .add(2, &[KeyValue::new("method", "GET")]);
+        meter.i64_up_down_counter("active").build().add(3, &[]);
+        let mut rm = test_util::collect(&reader);
+
+        test_util::block_on(exporter.export(&mut rm)).unwrap();
+
+        let packet = receive(&server);
+        let mut lines = packet.lines().collect::<Vec<_>>();
+        lines.sort();
+        assert_eq!(lines, vec!["active:+3|g", "requests:2|c|#method:GET"]);
+    }
+
+    #[test]
+    fn reserved_characters_are_replaced() {
+        let mut lines = Vec::new();
+        let gauge = Gauge {
+            data_points: vec![GaugeDataPoint {
+                attributes: vec![KeyValue::new("path|#", "/a,b:c\ninjected:1|c")],
+                value: 1u64,
+                exemplars: Vec::new(),
+            }],
+            start_time: None,
+            time: SystemTime::now(),
+        };
+
+        push_gauge(&mut lines, "queue:depth", &gauge);
+
+        assert_eq!(lines, vec!["queue_depth:1|g|#path__:/a_b_c_injected_1_c"]);
+    }
+
+    #[test]
+    fn export_fails_after_shutdown() {
+        let server = server();
+        let address = server.local_addr().unwrap().to_string();
+        let exporter = StatsdExporter::new(connect(&address).unwrap(), true);
+
+        exporter.shutdown().unwrap();
+
+        assert!(matches!(
+            test_util::block_on(exporter.export(&mut test_util::empty_metrics())),
+            Err(OTelSdkError::AlreadyShutdown)
+        ));
+    }
+
+    /// Returns a line too large for any UDP datagram, so sending it always fails.
+    fn oversized_line() -> String {
+        "x".repeat(70_000)
+    }
+
+    #[test]
+    fn rejected_packets_are_dropped_without_blocking_the_others() {
+        let server = server();
+        let address = server.local_addr().unwrap().to_string();
+        let exporter = StatsdExporter::new(connect(&address).unwrap(), false);
+
+        let mut sent = Ok(());
+        let logs = test_util::capture_logs(|| {
+            sent = exporter.send(vec![
+                "first:1|c".to_string(),
+                oversized_line(),
+                "second:1|c".to_string(),
+            ]);
+        });
+
+        assert!(sent.is_err());
+        assert!(
+            logs.iter()
+                .any(|log| log.message.contains("packet dropped"))
+        );
+        assert_eq!(receive(&server), "first:1|c");
+        assert_eq!(receive(&server), "second:1|c");
+        assert!(exporter.retained.lock().unwrap().is_empty());
+
+        exporter.send(vec!["third:1|c".to_string()]).unwrap();
+        assert_eq!(receive(&server), "third:1|c");
+    }
+
+    /// Sends `line` until the socket reports that the server refused it.
+    fn send_until_refused(exporter: &StatsdExporter, line: &str) {
+        for _ in 0..100 {
+            if exporter.send(vec![line.to_string()]).is_err() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("the closed port never refused the packets");
+    }
+
+    #[test]
+    fn unsent_packets_are_resent_without_reset_on_failure() {
+        let closed = server();
+        let address = closed.local_addr().unwrap();
+        drop(closed);
+        let exporter = StatsdExporter::new(connect(&address.to_string()).unwrap(), false);
+
+        send_until_refused(&exporter, "first:1|c");
+        assert_eq!(
+            *exporter.retained.lock().unwrap(),
+            vec!["first:1|c".to_string()]
+        );
+
+        // once the server is back, the kept packet is sent ahead of the new lines
+        let server = UdpSocket::bind(address).unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        exporter.send(vec!["second:1|c".to_string()]).unwrap();
+
+        assert_eq!(receive(&server), "first:1|c");
+        assert_eq!(receive(&server), "second:1|c");
+        assert!(exporter.retained.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn unsent_packets_are_dropped_with_reset_on_failure() {
+        let closed = server();
+        let address = closed.local_addr().unwrap();
+        drop(closed);
+        let exporter = StatsdExporter::new(connect(&address.to_string()).unwrap(), true);
+
+        send_until_refused(&exporter, "first:1|c");
+
+        assert!(exporter.retained.lock().unwrap().is_empty());
+    }
+}
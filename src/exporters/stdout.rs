@@ -23,6 +23,23 @@
 //! cargo build --features stdout
 //! ```
 //!
+//! ## Output Format
+//!
+//! By default the exporter writes the verbose multi-line output of `opentelemetry_stdout`.
+//! A compact format printing one line per data point can be selected with
+//! [`install_with_options`], optionally followed by the resource attributes:
+//!
+//! ```text
+//! http.requests{method=GET,status=200} 42 [service.name=my-service environment=local]
+//! ```
+//!
+//! ## Named Pipe
+//!
+//! When `MetricsConfig::pipe_path` is set, the exporter writes one OTLP JSON line per export
+//! to that path instead, whatever the format option. This targets sandboxes where a sidecar
+//! reads a named pipe and forwards the lines to a collector, and requires the `stdout-pipe`
+//! feature.
+//!
 //! # Example
 //!
 //! ```rust
@@ -31,14 +48,80 @@
 //! ```
 //!
 
-use crate::errors::MetricsError;
-use configs::app::AppConfigs;
-use opentelemetry::{KeyValue, global};
+use super::{reader::periodic, transform::TransformExporter};
+use crate::{
+    LOG_TARGET,
+    config::{ExporterKind, MetricsConfig},
+    errors::MetricsError,
+};
+use opentelemetry::KeyValue;
 use opentelemetry_sdk::{
-    Resource,
-    metrics::{PeriodicReader, SdkMeterProvider},
+    error::{OTelSdkError, OTelSdkResult},
+    metrics::{
+        SdkMeterProvider, Temporality,
+        data::{ExponentialHistogram, Gauge, Histogram, ResourceMetrics, Sum},
+        exporter::PushMetricExporter,
+        reader::MetricReader,
+    },
 };
-use tracing::info;
+use std::{
+    fmt::Display,
+    io::Write,
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use tracing::{debug, error};
+
+/// # StdoutFormat
+///
+/// Selects how the stdout exporter renders metrics.
+///
+/// ## Variants
+///
+/// * `Pretty` - The verbose multi-line output of `opentelemetry_stdout`, always including the
+///   resource block
+/// * `Compact` - One line per data point, in the form `name{attributes} value`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StdoutFormat {
+    #[default]
+    Pretty,
+    Compact,
+}
+
+/// # StdoutOptions
+///
+/// Output options of the stdout exporter.
+///
+/// ## Fields
+///
+/// * `format` - The rendering format, see [`StdoutFormat`]
+/// * `include_resource` - Whether the compact format appends the resource attributes to each
+///    line. The pretty format always prints the resource block
+///
+/// ## Example
+///
+/// ```
+/// use metrics::exporters::stdout::{StdoutFormat, StdoutOptions};
+///
+/// let opts = StdoutOptions {
+///     format: StdoutFormat::Compact,
+///     include_resource: false,
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StdoutOptions {
+    pub format: StdoutFormat,
+    pub include_resource: bool,
+}
+
+impl Default for StdoutOptions {
+    fn default() -> Self {
+        Self {
+            format: StdoutFormat::Pretty,
+            include_resource: true,
+        }
+    }
+}
 
 /// Creates and installs a standard output metrics exporter.
 ///
@@ -56,29 +139,391 @@ use tracing::info;
 /// are being recorded correctly before configuring a production-ready exporter
 /// like OTLP or Prometheus.
 pub fn install() -> Result<SdkMeterProvider, MetricsError> {
-    let app_cfgs = AppConfigs::new();
+    install_with_config(&MetricsConfig::load())
+}
 
-    let exporter = opentelemetry_stdout::MetricExporter::default();
-    let reader = PeriodicReader::builder(exporter).build();
-
-    let provider = SdkMeterProvider::builder()
-        .with_reader(reader)
-        .with_resource(
-            Resource::builder()
-                .with_service_name(app_cfgs.name.clone())
-                .with_attribute(KeyValue::new(
-                    "service.namespace",
-                    format!("{}", app_cfgs.namespace),
-                ))
-                .with_attribute(KeyValue::new("environment", format!("{}", app_cfgs.env)))
-                .with_attribute(KeyValue::new("library.language", "rust"))
-                .build(),
-        )
+/// Creates and installs a standard output metrics exporter from an explicit configuration.
+///
+/// Behaves like [`install`], but reads the export interval and resource attributes from the
+/// given [`MetricsConfig`] instead of the ambient Ruskit configuration.
+///
+/// # Parameters
+///
+/// * `cfg` - The metrics configuration
+///
+/// # Returns
+///
+/// * `Ok(SdkMeterProvider)` - The configured meter provider
+/// * `Err(MetricsError)` - If an error occurred during exporter setup
+pub fn install_with_config(cfg: &MetricsConfig) -> Result<SdkMeterProvider, MetricsError> {
+    install_with_options(cfg, StdoutOptions::default())
+}
+
+/// Creates and installs a standard output metrics exporter with custom output options.
+///
+/// # Parameters
+///
+/// * `cfg` - The metrics configuration
+/// * `opts` - The output options, see [`StdoutOptions`]
+///
+/// # Returns
+///
+/// * `Ok(SdkMeterProvider)` - The configured meter provider
+/// * `Err(MetricsError)` - If an error occurred during exporter setup
+///
+/// # Example
+///
+/// ```rust
+/// use metrics::{config::MetricsConfig, exporters::stdout};
+/// use metrics::exporters::stdout::{StdoutFormat, StdoutOptions};
+///
+/// let opts = StdoutOptions {
+///     format: StdoutFormat::Compact,
+///     include_resource: false,
+/// };
+/// let provider = stdout::install_with_options(&MetricsConfig::default(), opts).unwrap();
+/// ```
+pub fn install_with_options(
+    cfg: &MetricsConfig,
+    opts: StdoutOptions,
+) -> Result<SdkMeterProvider, MetricsError> {
+    if let Some(path) = &cfg.pipe_path {
+        return install_with_pipe(cfg, path);
+    }
+
+    match opts.format {
+        StdoutFormat::Pretty => install_with_reader(cfg, opts.format, pretty_reader(cfg)),
+        StdoutFormat::Compact => {
+            let exporter = CompactExporter::new(opts.include_resource);
+            let exporter = TransformExporter::for_exporter(exporter, cfg, ExporterKind::Stdout);
+            install_with_reader(cfg, opts.format, periodic(exporter, cfg))
+        }
+    }
+}
+
+/// Installs the exporter writing OTLP JSON lines to the named pipe at `path`.
+#[cfg(feature = "stdout-pipe")]
+fn install_with_pipe(cfg: &MetricsConfig, path: &Path) -> Result<SdkMeterProvider, MetricsError> {
+    let provider = super::provider_builder(cfg)
+        .with_reader(super::pipe::reader(cfg, path))
         .build();
 
-    global::set_meter_provider(provider.clone());
+    super::install_global(&provider);
 
-    info!("traces::install stdout metric installed");
+    debug!(
+        target: LOG_TARGET,
+        exporter = "stdout",
+        path = %path.display(),
+        interval = ?cfg.exporter_interval,
+        "metrics::install stdout pipe exporter installed"
+    );
 
     Ok(provider)
 }
+
+#[cfg(not(feature = "stdout-pipe"))]
+fn install_with_pipe(_cfg: &MetricsConfig, path: &Path) -> Result<SdkMeterProvider, MetricsError> {
+    error!(
+        target: LOG_TARGET,
+        path = %path.display(),
+        feature = "stdout-pipe",
+        "pipe_path requires a feature that was not enabled at compile time"
+    );
+    Err(MetricsError::InvalidFeaturesError)
+}
+
+/// Builds the periodic reader writing the pretty format to standard output.
+///
+/// Also used by the OTLP exporter to mirror its stream when `mirror_stdout` is set.
+pub(crate) fn pretty_reader(cfg: &MetricsConfig) -> impl MetricReader {
+    let exporter = opentelemetry_stdout::MetricExporter::default();
+    let exporter = TransformExporter::for_exporter(exporter, cfg, ExporterKind::Stdout);
+    periodic(exporter, cfg)
+}
+
+/// Builds the meter provider around the given reader and installs it globally.
+fn install_with_reader<R: MetricReader>(
+    cfg: &MetricsConfig,
+    format: StdoutFormat,
+    reader: R,
+) -> Result<SdkMeterProvider, MetricsError> {
+    let provider = super::provider_builder(cfg).with_reader(reader).build();
+
+    super::install_global(&provider);
+
+    debug!(
+        target: LOG_TARGET,
+        exporter = "stdout",
+        format = ?format,
+        interval = ?cfg.exporter_interval,
+        "metrics::install stdout exporter installed"
+    );
+
+    Ok(provider)
+}
+
+/// # CompactExporter
+///
+/// Exporter writing one line per data point to standard output.
+///
+/// Sums and gauges are written as `name{attributes} value`, histograms and exponential
+/// histograms as `name{attributes} count=<count> sum=<sum>`, followed by `min` and `max` when
+/// recorded. When `include_resource` is set, the resource attributes are appended to each line
+/// between brackets.
+#[derive(Debug)]
+struct CompactExporter {
+    include_resource: bool,
+    is_shutdown: AtomicBool,
+}
+
+impl CompactExporter {
+    fn new(include_resource: bool) -> Self {
+        Self {
+            include_resource,
+            is_shutdown: AtomicBool::new(false),
+        }
+    }
+
+    /// Renders the resource attributes appended to each line, or an empty string.
+    fn resource_suffix(&self, metrics: &ResourceMetrics) -> String {
+        if !self.include_resource {
+            return String::new();
+        }
+
+        let attributes = metrics
+            .resource
+            .iter()
+            .map(|(k, v)| format!("{k}={v}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        format!(" [{attributes}]")
+    }
+
+    /// Renders one line per data point of the exported metrics.
+    fn lines(&self, metrics: &ResourceMetrics) -> Vec<String> {
+        let suffix = self.resource_suffix(metrics);
+        let mut lines = Vec::new();
+
+        for scope in &metrics.scope_metrics {
+            for metric in &scope.metrics {
+                let name = metric.name.as_ref();
+                let data = metric.data.as_any();
+
+                if let Some(sum) = data.downcast_ref::<Sum<u64>>() {
+                    push_values(&mut lines, name, sum_points(sum), &suffix);
+                } else if let Some(sum) = data.downcast_ref::<Sum<i64>>() {
+                    push_values(&mut lines, name, sum_points(sum), &suffix);
+                } else if let Some(sum) = data.downcast_ref::<Sum<f64>>() {
+                    push_values(&mut lines, name, sum_points(sum), &suffix);
+                } else if let Some(gauge) = data.downcast_ref::<Gauge<u64>>() {
+                    push_values(&mut lines, name, gauge_points(gauge), &suffix);
+                } else if let Some(gauge) = data.downcast_ref::<Gauge<i64>>() {
+                    push_values(&mut lines, name, gauge_points(gauge), &suffix);
+                } else if let Some(gauge) = data.downcast_ref::<Gauge<f64>>() {
+                    push_values(&mut lines, name, gauge_points(gauge), &suffix);
+                } else if let Some(hist) = data.downcast_ref::<Histogram<u64>>() {
+                    push_histogram(&mut lines, name, hist, &suffix);
+                } else if let Some(hist) = data.downcast_ref::<Histogram<f64>>() {
+                    push_histogram(&mut lines, name, hist, &suffix);
+                } else if let Some(hist) = data.downcast_ref::<ExponentialHistogram<u64>>() {
+                    push_exponential_histogram(&mut lines, name, hist, &suffix);
+                } else if let Some(hist) = data.downcast_ref::<ExponentialHistogram<f64>>() {
+                    push_exponential_histogram(&mut lines, name, hist, &suffix);
+                }
+            }
+        }
+
+        lines
+    }
+}
+
+impl PushMetricExporter for CompactExporter {
+    async fn export(&self, metrics: &mut ResourceMetrics) -> OTelSdkResult {
+        if self.is_shutdown.load(Ordering::SeqCst) {
+            return Err(OTelSdkError::AlreadyShutdown);
+        }
+
+        let lines = self.lines(metrics);
+
+        let mut stdout = std::io::stdout().lock();
+        for line in lines {
+            writeln!(stdout, "{line}")
+                .map_err(|err| OTelSdkError::InternalFailure(err.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        Ok(())
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        self.is_shutdown.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn temporality(&self) -> Temporality {
+        Temporality::Cumulative
+    }
+}
+
+/// Returns the attributes and value of every data point of a sum.
+fn sum_points<T: Copy>(sum: &Sum<T>) -> impl Iterator<Item = (&[KeyValue], T)> {
+    sum.data_points
+        .iter()
+        .map(|dp| (dp.attributes.as_slice(), dp.value))
+}
+
+/// Returns the attributes and value of every data point of a gauge.
+fn gauge_points<T: Copy>(gauge: &Gauge<T>) -> impl Iterator<Item = (&[KeyValue], T)> {
+    gauge
+        .data_points
+        .iter()
+        .map(|dp| (dp.attributes.as_slice(), dp.value))
+}
+
+/// Renders one line per sum or gauge data point.
+fn push_values<'a, T: Display>(
+    lines: &mut Vec<String>,
+    name: &str,
+    points: impl Iterator<Item = (&'a [KeyValue], T)>,
+    suffix: &str,
+) {
+    for (attributes, value) in points {
+        lines.push(format!(
+            "{name}{{{}}} {value}{suffix}",
+            format_attributes(attributes)
+        ));
+    }
+}
+
+/// Renders one line per histogram data point.
+fn push_histogram<T: Display>(
+    lines: &mut Vec<String>,
+    name: &str,
+    hist: &Histogram<T>,
+    suffix: &str,
+) {
+    for dp in &hist.data_points {
+        let min_max = match (&dp.min, &dp.max) {
+            (Some(min), Some(max)) => format!(" min={min} max={max}"),
+            _ => String::new(),
+        };
+        lines.push(format!(
+            "{name}{{{}}} count={} sum={}{min_max}{suffix}",
+            format_attributes(&dp.attributes),
+            dp.count,
+            dp.sum
+        ));
+    }
+}
+
+/// Renders one line per exponential histogram data point.
+fn push_exponential_histogram<T: Display>(
+    lines: &mut Vec<String>,
+    name: &str,
+    hist: &ExponentialHistogram<T>,
+    suffix: &str,
+) {
+    for dp in &hist.data_points {
+        let min_max = match (&dp.min, &dp.max) {
+            (Some(min), Some(max)) => format!(" min={min} max={max}"),
+            _ => String::new(),
+        };
+        lines.push(format!(
+            "{name}{{{}}} count={} sum={}{min_max}{suffix}",
+            format_attributes(&dp.attributes),
+            dp.count,
+            dp.sum
+        ));
+    }
+}
+
+/// Renders attributes as a comma separated `key=value` list.
+fn format_attributes(attributes: &[KeyValue]) -> String {
+    attributes
+        .iter()
+        .map(|kv| format!("{}={}", kv.key, kv.value))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::AggregationSelector, test_util};
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry_sdk::{
+        Resource,
+        metrics::{Aggregation, InstrumentKind},
+    };
+
+    #[test]
+    fn compact_renders_one_line_per_point() {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        let counter = provider.meter("test").u64_counter("requests").build();
+        counter.add(3, &[KeyValue::new("method", "GET")]);
+
+        let lines = CompactExporter::new(false).lines(&test_util::collect(&reader));
+
+        assert_eq!(lines, vec!["requests{method=GET} 3".to_string()]);
+    }
+
+    #[test]
+    fn compact_renders_exponential_histograms() {
+        let cfg = MetricsConfig {
+            aggregation_selector: Some(AggregationSelector::new(|kind| {
+                (kind == InstrumentKind::Histogram).then_some(
+                    Aggregation::Base2ExponentialHistogram {
+                        max_size: 160,
+                        max_scale: 20,
+                        record_min_max: true,
+                    },
+                )
+            })),
+            ..MetricsConfig::default()
+        };
+        let (provider, reader) = test_util::provider(&cfg);
+        let histogram = provider.meter("test").f64_histogram("latency").build();
+        histogram.record(1.0, &[]);
+        histogram.record(3.0, &[]);
+
+        let lines = CompactExporter::new(false).lines(&test_util::collect(&reader));
+
+        assert_eq!(
+            lines,
+            vec!["latency{} count=2 sum=4 min=1 max=3".to_string()]
+        );
+    }
+
+    #[test]
+    fn compact_appends_the_resource() {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        let counter = provider.meter("test").u64_counter("requests").build();
+        counter.add(1, &[]);
+
+        let mut metrics = test_util::collect(&reader);
+        metrics.resource = Resource::builder_empty()
+            .with_attribute(KeyValue::new("service.name", "svc"))
+            .build();
+        let lines = CompactExporter::new(true).lines(&metrics);
+
+        assert_eq!(lines, vec!["requests{} 1 [service.name=svc]".to_string()]);
+    }
+
+    #[test]
+    fn compact_export_fails_after_shutdown() {
+        let exporter = CompactExporter::new(false);
+        exporter.shutdown().unwrap();
+
+        let mut metrics = ResourceMetrics {
+            resource: Resource::builder_empty().build(),
+            scope_metrics: Vec::new(),
+        };
+        let result = test_util::block_on(exporter.export(&mut metrics));
+
+        assert!(matches!(result, Err(OTelSdkError::AlreadyShutdown)));
+    }
+}
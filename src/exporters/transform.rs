@@ -0,0 +1,1076 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Export Transforms
+//!
+//! Provides an exporter wrapper that rewrites the collected data before it is exported.
+//!
+//! Some changes cannot be expressed with views, which only select, rename and re-aggregate
+//! streams. The wrapper applies them to the collected `ResourceMetrics` right before the
+//! underlying exporter serializes them, so they affect every exporter in the same way.
+//!
+//! ## Global Attributes
+//!
+//! The `global_attributes` of the [`MetricsConfig`] are added to every data point. Unlike
+//! resource attributes, which describe the entity producing the metrics and are sent once per
+//! export, these are metric attributes: query layers that only filter on data point attributes
+//! can use them. An attribute recorded with the same key on a data point takes precedence.
+//!
+//! ## External Points
+//!
+//! The points of the `external_producer` are added under the `external` instrumentation scope
+//! before the other transforms run, so global attributes and rounding apply to them as well.
+//! Counters are exported as cumulative monotonic sums starting when the exporter was created.
+//! The StatsD exporter sends every sum as an increment, so it only suits external gauges.
+//!
+//! ## Per-Exporter Resource
+//!
+//! The resource is shared by every reader of a provider. When an exporter is listed in
+//! `MetricsConfig::resource_keys`, only the listed resource attributes are passed to it, so
+//! for example the Prometheus mirror of an OTLP install can expose a minimal `target_info`
+//! while the collector still receives the full resource. The other way around, the attributes
+//! listed in `MetricsConfig::resource_exclude` for an exporter are removed from its resource,
+//! for example `library.language` from the Prometheus `target_info` labels.
+//!
+//! ## Reduced Resource
+//!
+//! The resource is static, yet sent again with every export. On constrained links,
+//! `full_resource_every` makes the OTLP exporter send the full resource only on the first
+//! export and then once every that many exports; the other exports only carry the attributes
+//! identifying the service (`service.name`, `service.namespace` and `service.instance.id`).
+//! Each endpoint counts its own exports.
+//!
+//! Collectors do not remember the resource of previous exports. Until the next full export, the
+//! data of the reduced exports is stored without the other resource attributes, and if the
+//! export carrying the full resource is lost, no data point has them for a whole period. The
+//! option is therefore disabled by default. It is ignored when `collector_compat` is set.
+//!
+//! ## Rounding
+//!
+//! When `round_decimals` is set, the floating-point values of sums and gauges are rounded to
+//! that number of decimals, which cuts the churn noisy gauges cause in storage backends
+//! charging by value precision. Histograms and integer values are exported unchanged.
+//!
+//! ## Redaction
+//!
+//! The values of the data point attributes listed in `redacted_attributes` are replaced before
+//! export, masked with `[redacted]` or hashed depending on `redaction`, so values such as user
+//! emails used as attributes never leave the process. Redaction runs after the global
+//! attributes are added and applies to every exporter, external points included. The resource
+//! is not redacted.
+//!
+//! Masking leaves the series differing only by a redacted value with the same attributes,
+//! which backends reject or overwrite. Their data points are merged into one: sums are added,
+//! histogram buckets merged and gauges keep the last value.
+//!
+//! ## Histogram Min and Max
+//!
+//! With `histogram_min_max` set to `false`, the minimum and maximum are removed from the data
+//! points of explicit bucket histograms. They are dropped at export rather than through a view,
+//! so the histograms keep the boundaries their instruments declared.
+//!
+//! ## Gauges On Change
+//!
+//! When `gauges_on_change` is set, a gauge series is only exported when its value changed
+//! since the previous export, which saves the volume of observable gauges that rarely change.
+//! Every series is exported the first time it is seen. Observable counters produce sums that
+//! cannot be told apart from the ones of synchronous counters, so sums are always exported.
+//! Scrapes must contain every series, so the Prometheus exporter is never affected.
+
+use crate::config::{
+    ExporterKind, ExternalKind, ExternalPoint, ExternalProducer, MetricsConfig, Redaction,
+};
+use opentelemetry::{InstrumentationScope, Key, KeyValue, Value};
+use opentelemetry_sdk::{
+    Resource,
+    error::OTelSdkResult,
+    metrics::{
+        Temporality,
+        data::{
+            Aggregation, ExponentialBucket, ExponentialHistogram, ExponentialHistogramDataPoint,
+            Gauge, GaugeDataPoint, Histogram, HistogramDataPoint, Metric, ResourceMetrics,
+            ScopeMetrics, Sum, SumDataPoint,
+        },
+        exporter::PushMetricExporter,
+    },
+};
+use std::{
+    collections::{BTreeMap, HashMap, hash_map::Entry},
+    ops::AddAssign,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::SystemTime,
+};
+
+/// Resource attributes kept by the exports that do not carry the full resource.
+const IDENTITY_KEYS: [&str; 3] = ["service.name", "service.namespace", "service.instance.id"];
+
+/// Number of decimals beyond which rounding leaves every value unchanged.
+const MAX_DECIMALS: u32 = 15;
+
+/// Value replacing the masked attribute values.
+const REDACTED: &str = "[redacted]";
+
+/// Offset basis of the 64-bit FNV-1a hash.
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Prime of the 64-bit FNV-1a hash.
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// # TransformExporter
+///
+/// Wraps an exporter and applies the configured transforms to every export.
+#[derive(Debug)]
+pub(crate) struct TransformExporter<E> {
+    inner: E,
+    transform: Transform,
+}
+
+impl<E> TransformExporter<E> {
+    /// Wraps `inner`, applying the transforms described by the configuration.
+    pub(crate) fn new(inner: E, cfg: &MetricsConfig) -> Self {
+        Self {
+            inner,
+            transform: Transform::new(cfg),
+        }
+    }
+
+    /// Wraps `inner`, also applying the transforms specific to the exporter `kind`.
+    pub(crate) fn for_exporter(inner: E, cfg: &MetricsConfig, kind: ExporterKind) -> Self {
+        Self {
+            inner,
+            transform: Transform::for_exporter(cfg, kind),
+        }
+    }
+}
+
+/// # Transform
+///
+/// The transforms described by the configuration, for the readers that collect on demand and
+/// have no exporter to wrap.
+#[derive(Debug, Clone)]
+pub(crate) struct Transform {
+    global_attributes: Vec<KeyValue>,
+    round_decimals: Option<u32>,
+    histogram_min_max: bool,
+    redacted: Vec<Key>,
+    redaction: Redaction,
+    resource_keys: Option<Vec<String>>,
+    resource_exclude: Vec<String>,
+    last_gauges: Option<Arc<Mutex<HashMap<String, u64>>>>,
+    full_resource_every: Option<(u32, Arc<AtomicU64>)>,
+    external_producer: Option<(ExternalProducer, SystemTime)>,
+}
+
+impl Transform {
+    pub(crate) fn new(cfg: &MetricsConfig) -> Self {
+        let global_attributes = cfg
+            .global_attributes
+            .iter()
+            .map(|(key, value)| KeyValue::new(key.clone(), value.clone()))
+            .collect();
+
+        Self {
+            global_attributes,
+            round_decimals: cfg.round_decimals,
+            histogram_min_max: cfg.histogram_min_max,
+            redacted: cfg
+                .redacted_attributes
+                .iter()
+                .map(|key| Key::new(key.clone()))
+                .collect(),
+            redaction: cfg.redaction,
+            resource_keys: None,
+            resource_exclude: Vec::new(),
+            last_gauges: cfg
+                .gauges_on_change
+                .then(|| Arc::new(Mutex::new(HashMap::new()))),
+            full_resource_every: None,
+            external_producer: cfg
+                .external_producer
+                .clone()
+                .map(|producer| (producer, SystemTime::now())),
+        }
+    }
+
+    /// The transforms of the configuration, plus the ones specific to the exporter `kind`.
+    pub(crate) fn for_exporter(cfg: &MetricsConfig, kind: ExporterKind) -> Self {
+        let transform = Self::new(cfg);
+        Self {
+            resource_keys: cfg.resource_keys.get(&kind).cloned(),
+            resource_exclude: cfg.resource_exclude.get(&kind).cloned().unwrap_or_default(),
+            last_gauges: transform
+                .last_gauges
+                .filter(|_| kind != ExporterKind::Prometheus),
+            full_resource_every: (kind == ExporterKind::Otlp
+                && cfg.full_resource_every > 1
+                && !cfg.collector_compat)
+                .then(|| (cfg.full_resource_every, Arc::new(AtomicU64::new(0)))),
+            ..transform
+        }
+    }
+
+    /// Applies the transforms to the collected metrics.
+    pub(crate) fn apply(&self, metrics: &mut ResourceMetrics) {
+        if let Some((producer, start_time)) = &self.external_producer {
+            let points = producer.produce();
+            if !points.is_empty() {
+                metrics
+                    .scope_metrics
+                    .push(external_metrics(points, *start_time));
+            }
+        }
+
+        if self.resource_keys.is_some() || !self.resource_exclude.is_empty() {
+            let attributes = metrics
+                .resource
+                .iter()
+                .filter(|(k, _)| {
+                    self.resource_keys
+                        .as_ref()
+                        .is_none_or(|keys| keys.iter().any(|key| key == k.as_str()))
+                })
+                .filter(|(k, _)| !self.resource_exclude.iter().any(|key| key == k.as_str()))
+                .map(|(k, v)| KeyValue::new(k.clone(), v.clone()));
+            metrics.resource = Resource::builder_empty()
+                .with_attributes(attributes)
+                .build();
+        }
+
+        if let Some((every, exports)) = &self.full_resource_every {
+            if exports.fetch_add(1, Ordering::Relaxed) % u64::from(*every) != 0 {
+                let attributes = metrics
+                    .resource
+                    .iter()
+                    .filter(|(k, _)| IDENTITY_KEYS.contains(&k.as_str()))
+                    .map(|(k, v)| KeyValue::new(k.clone(), v.clone()));
+                metrics.resource = Resource::builder_empty()
+                    .with_attributes(attributes)
+                    .build();
+            }
+        }
+
+        if let Some(last_gauges) = &self.last_gauges {
+            let mut last_gauges = last_gauges.lock().unwrap_or_else(|e| e.into_inner());
+            for scope_metrics in &mut metrics.scope_metrics {
+                let scope = scope_metrics.scope.name().to_string();
+                scope_metrics.metrics.retain_mut(|metric| {
+                    let prefix = format!("{scope}/{}", metric.name);
+                    retain_changed(metric.data.as_mut(), &prefix, &mut last_gauges)
+                });
+            }
+        }
+
+        if self.global_attributes.is_empty()
+            && self.round_decimals.is_none()
+            && self.redacted.is_empty()
+            && self.histogram_min_max
+        {
+            return;
+        }
+
+        for scope_metrics in &mut metrics.scope_metrics {
+            for metric in &mut scope_metrics.metrics {
+                if !self.global_attributes.is_empty() {
+                    for_each_attributes(metric.data.as_mut(), |attributes| {
+                        for attribute in &self.global_attributes {
+                            if !attributes.iter().any(|kv| kv.key == attribute.key) {
+                                attributes.push(attribute.clone());
+                            }
+                        }
+                    });
+                }
+
+                if !self.redacted.is_empty() {
+                    for_each_attributes(metric.data.as_mut(), |attributes| {
+                        for attribute in attributes.iter_mut() {
+                            if self.redacted.contains(&attribute.key) {
+                                attribute.value = redact(&attribute.value, self.redaction);
+                            }
+                        }
+                    });
+
+                    if self.redaction == Redaction::Mask {
+                        merge_duplicates(metric.data.as_mut());
+                    }
+                }
+
+                if let Some(decimals) = self.round_decimals {
+                    round_values(metric.data.as_mut(), decimals);
+                }
+
+                if !self.histogram_min_max {
+                    clear_min_max(metric.data.as_mut());
+                }
+            }
+        }
+    }
+}
+
+impl<E: PushMetricExporter> PushMetricExporter for TransformExporter<E> {
+    async fn export(&self, metrics: &mut ResourceMetrics) -> OTelSdkResult {
+        self.transform.apply(metrics);
+        self.inner.export(metrics).await
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        self.inner.shutdown()
+    }
+
+    fn temporality(&self) -> Temporality {
+        self.inner.temporality()
+    }
+}
+
+/// Builds the metrics of the `external` scope from the points of the external producer.
+fn external_metrics(points: Vec<ExternalPoint>, start_time: SystemTime) -> ScopeMetrics {
+    let now = SystemTime::now();
+    let mut metrics: Vec<(String, ExternalKind, Vec<(Vec<KeyValue>, f64)>)> = Vec::new();
+
+    for point in points {
+        let values = (point.attributes, point.value);
+        match metrics
+            .iter_mut()
+            .find(|(name, kind, _)| *name == point.name && *kind == point.kind)
+        {
+            Some((_, _, existing)) => existing.push(values),
+            None => metrics.push((point.name, point.kind, vec![values])),
+        }
+    }
+
+    let metrics = metrics
+        .into_iter()
+        .map(|(name, kind, values)| {
+            let data: Box<dyn Aggregation> = match kind {
+                ExternalKind::Counter => Box::new(Sum {
+                    data_points: values
+                        .into_iter()
+                        .map(|(attributes, value)| SumDataPoint {
+                            attributes,
+                            value,
+                            exemplars: Vec::new(),
+                        })
+                        .collect(),
+                    start_time,
+                    time: now,
+                    temporality: Temporality::Cumulative,
+                    is_monotonic: true,
+                }),
+                ExternalKind::Gauge => Box::new(Gauge {
+                    data_points: values
+                        .into_iter()
+                        .map(|(attributes, value)| GaugeDataPoint {
+                            attributes,
+                            value,
+                            exemplars: Vec::new(),
+                        })
+                        .collect(),
+                    start_time: None,
+                    time: now,
+                }),
+            };
+
+            Metric {
+                name: name.into(),
+                description: "".into(),
+                unit: "".into(),
+                data,
+            }
+        })
+        .collect();
+
+    ScopeMetrics {
+        scope: InstrumentationScope::builder("external").build(),
+        metrics,
+    }
+}
+
+/// Calls `f` with the attributes of every data point of a metric.
+pub(crate) fn for_each_attributes(
+    data: &mut dyn Aggregation,
+    mut f: impl FnMut(&mut Vec<KeyValue>),
+) {
+    let data = data.as_mut();
+
+    macro_rules! visit {
+        ($($ty:ty),*) => {
+            $(
+                if let Some(agg) = data.downcast_mut::<$ty>() {
+                    agg.data_points.iter_mut().for_each(|p| f(&mut p.attributes));
+                    return;
+                }
+            )*
+        };
+    }
+
+    visit!(
+        Sum<u64>,
+        Sum<i64>,
+        Sum<f64>,
+        Gauge<u64>,
+        Gauge<i64>,
+        Gauge<f64>,
+        Histogram<u64>,
+        Histogram<f64>,
+        ExponentialHistogram<u64>,
+        ExponentialHistogram<f64>
+    );
+}
+
+/// Returns the redacted replacement of an attribute value.
+fn redact(value: &Value, redaction: Redaction) -> Value {
+    match redaction {
+        Redaction::Mask => Value::from(REDACTED),
+        Redaction::Hash => {
+            let hash = value.as_str().bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+            });
+            Value::from(format!("{hash:016x}"))
+        }
+    }
+}
+
+/// Merges the data points left with the same attributes by masking, so every series is
+/// exported once.
+fn merge_duplicates(data: &mut dyn Aggregation) {
+    let data = data.as_mut();
+
+    macro_rules! merge {
+        ($($ty:ty => $merge:expr),*) => {
+            $(
+                if let Some(agg) = data.downcast_mut::<$ty>() {
+                    merge_points(&mut agg.data_points, |p| series_key(&p.attributes), $merge);
+                    return;
+                }
+            )*
+        };
+    }
+
+    merge!(
+        Sum<u64> => merge_sum,
+        Sum<i64> => merge_sum,
+        Sum<f64> => merge_sum,
+        Gauge<u64> => merge_gauge,
+        Gauge<i64> => merge_gauge,
+        Gauge<f64> => merge_gauge,
+        Histogram<u64> => merge_histogram,
+        Histogram<f64> => merge_histogram,
+        ExponentialHistogram<u64> => merge_exponential,
+        ExponentialHistogram<f64> => merge_exponential
+    );
+}
+
+/// Merges the points sharing the same key into the first of them, keeping their order.
+fn merge_points<P>(
+    points: &mut Vec<P>,
+    key: impl Fn(&P) -> String,
+    mut merge: impl FnMut(&mut P, P),
+) {
+    let mut merged: Vec<P> = Vec::with_capacity(points.len());
+    let mut index = HashMap::new();
+
+    for point in points.drain(..) {
+        match index.entry(key(&point)) {
+            Entry::Occupied(entry) => merge(&mut merged[*entry.get()], point),
+            Entry::Vacant(entry) => {
+                entry.insert(merged.len());
+                merged.push(point);
+            }
+        }
+    }
+
+    *points = merged;
+}
+
+fn merge_sum<T: AddAssign>(into: &mut SumDataPoint<T>, from: SumDataPoint<T>) {
+    into.value += from.value;
+    into.exemplars.extend(from.exemplars);
+}
+
+fn merge_gauge<T>(into: &mut GaugeDataPoint<T>, from: GaugeDataPoint<T>) {
+    *into = from;
+}
+
+fn merge_histogram<T: Copy + PartialOrd + AddAssign>(
+    into: &mut HistogramDataPoint<T>,
+    from: HistogramDataPoint<T>,
+) {
+    // the points of a metric share the bounds of its aggregation
+    for (count, other) in into.bucket_counts.iter_mut().zip(from.bucket_counts) {
+        *count += other;
+    }
+    into.count += from.count;
+    into.sum += from.sum;
+    into.min = extremum(into.min, from.min, |a, b| b < a);
+    into.max = extremum(into.max, from.max, |a, b| b > a);
+    into.exemplars.extend(from.exemplars);
+}
+
+fn merge_exponential<T: Copy + PartialOrd + AddAssign>(
+    into: &mut ExponentialHistogramDataPoint<T>,
+    from: ExponentialHistogramDataPoint<T>,
+) {
+    // the buckets are brought to the coarser scale of both points before being added
+    let scale = into.scale.min(from.scale);
+    let (into_shift, from_shift) = ((into.scale - scale) as u32, (from.scale - scale) as u32);
+    merge_buckets(
+        &mut into.positive_bucket,
+        into_shift,
+        &from.positive_bucket,
+        from_shift,
+    );
+    merge_buckets(
+        &mut into.negative_bucket,
+        into_shift,
+        &from.negative_bucket,
+        from_shift,
+    );
+    into.scale = scale;
+    into.count += from.count;
+    into.zero_count += from.zero_count;
+    into.zero_threshold = into.zero_threshold.max(from.zero_threshold);
+    into.sum += from.sum;
+    into.min = extremum(into.min, from.min, |a, b| b < a);
+    into.max = extremum(into.max, from.max, |a, b| b > a);
+    into.exemplars.extend(from.exemplars);
+}
+
+/// Adds the counts of `from` to `into`, each downscaled by its shift.
+fn merge_buckets(
+    into: &mut ExponentialBucket,
+    into_shift: u32,
+    from: &ExponentialBucket,
+    from_shift: u32,
+) {
+    let mut counts = BTreeMap::new();
+    for (bucket, shift) in [(&*into, into_shift), (from, from_shift)] {
+        for (i, count) in bucket.counts.iter().enumerate() {
+            *counts
+                .entry((bucket.offset + i as i32) >> shift)
+                .or_insert(0) += count;
+        }
+    }
+
+    let (Some(&first), Some(&last)) = (counts.keys().next(), counts.keys().next_back()) else {
+        return;
+    };
+    into.offset = first;
+    into.counts = (first..=last)
+        .map(|index| counts.get(&index).copied().unwrap_or(0))
+        .collect();
+}
+
+/// Returns the value of `a` and `b` for which `replaces(a, b)` picks `b` over `a`.
+fn extremum<T: Copy>(a: Option<T>, b: Option<T>, replaces: impl Fn(T, T) -> bool) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) if replaces(a, b) => Some(b),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Rounds the floating-point values of sums and gauges to `decimals` decimals.
+fn round_values(data: &mut dyn Aggregation, decimals: u32) {
+    // an f64 carries at most 15 significant decimals, rounding further is a no-op
+    if decimals > MAX_DECIMALS {
+        return;
+    }
+
+    let factor = 10f64.powi(decimals as i32);
+    let round = |value: f64| {
+        // the scaled value of a large magnitude overflows, and it has no decimals to round anyway
+        if (value.abs() * factor).is_finite() {
+            (value * factor).round() / factor
+        } else {
+            value
+        }
+    };
+
+    let data = data.as_mut();
+    if let Some(sum) = data.downcast_mut::<Sum<f64>>() {
+        sum.data_points
+            .iter_mut()
+            .for_each(|p| p.value = round(p.value));
+    } else if let Some(gauge) = data.downcast_mut::<Gauge<f64>>() {
+        gauge
+            .data_points
+            .iter_mut()
+            .for_each(|p| p.value = round(p.value));
+    }
+}
+
+/// Removes the minimum and maximum from the data points of explicit bucket histograms.
+fn clear_min_max(data: &mut dyn Aggregation) {
+    let data = data.as_mut();
+    if let Some(hist) = data.downcast_mut::<Histogram<u64>>() {
+        hist.data_points
+            .iter_mut()
+            .for_each(|p| (p.min, p.max) = (None, None));
+    } else if let Some(hist) = data.downcast_mut::<Histogram<i64>>() {
+        hist.data_points
+            .iter_mut()
+            .for_each(|p| (p.min, p.max) = (None, None));
+    } else if let Some(hist) = data.downcast_mut::<Histogram<f64>>() {
+        hist.data_points
+            .iter_mut()
+            .for_each(|p| (p.min, p.max) = (None, None));
+    }
+}
+
+/// Drops the gauge data points whose value did not change since the previous export.
+///
+/// # Returns
+///
+/// Whether the metric still has data to export
+fn retain_changed(
+    data: &mut dyn Aggregation,
+    prefix: &str,
+    last_values: &mut HashMap<String, u64>,
+) -> bool {
+    let data = data.as_mut();
+    if let Some(gauge) = data.downcast_mut::<Gauge<u64>>() {
+        retain_changed_points(&mut gauge.data_points, prefix, last_values, |v| *v)
+    } else if let Some(gauge) = data.downcast_mut::<Gauge<i64>>() {
+        retain_changed_points(&mut gauge.data_points, prefix, last_values, |v| *v as u64)
+    } else if let Some(gauge) = data.downcast_mut::<Gauge<f64>>() {
+        retain_changed_points(&mut gauge.data_points, prefix, last_values, |v| v.to_bits())
+    } else {
+        true
+    }
+}
+
+/// Drops the data points whose value, as given by `bits`, is the one last seen for the series.
+fn retain_changed_points<T>(
+    points: &mut Vec<GaugeDataPoint<T>>,
+    prefix: &str,
+    last_values: &mut HashMap<String, u64>,
+    bits: impl Fn(&T) -> u64,
+) -> bool {
+    points.retain(|point| {
+        let series = format!("{prefix}{{{}}}", series_key(&point.attributes));
+        let value = bits(&point.value);
+        last_values.insert(series, value) != Some(value)
+    });
+    !points.is_empty()
+}
+
+/// Returns a key identifying the series of the attributes, whatever their order.
+fn series_key(attributes: &[KeyValue]) -> String {
+    let mut attributes: Vec<String> = attributes
+        .iter()
+        .map(|kv| format!("{}={}", kv.key, kv.value))
+        .collect();
+    attributes.sort();
+    attributes.join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use opentelemetry::metrics::MeterProvider;
+
+    /// Records on a fresh provider and returns the collected metrics.
+    fn collect(record: impl FnOnce(&opentelemetry::metrics::Meter)) -> ResourceMetrics {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        record(&provider.meter("test"));
+        test_util::collect(&reader)
+    }
+
+    /// Returns the attributes and value of every point of a `u64` sum, sorted by attributes.
+    fn sum_points(metrics: &ResourceMetrics, name: &str) -> Vec<(Vec<KeyValue>, u64)> {
+        let metric = test_util::metric(metrics, name).expect("metric");
+        let sum = metric
+            .data
+            .as_any()
+            .downcast_ref::<Sum<u64>>()
+            .expect("u64 sum");
+        let mut points: Vec<_> = sum
+            .data_points
+            .iter()
+            .map(|p| {
+                let mut attributes = p.attributes.clone();
+                attributes.sort_by(|a, b| a.key.cmp(&b.key));
+                (attributes, p.value)
+            })
+            .collect();
+        points.sort_by_key(|(attributes, _)| format!("{attributes:?}"));
+        points
+    }
+
+    #[test]
+    fn global_attributes_are_added_to_every_point() {
+        let cfg = MetricsConfig {
+            global_attributes: BTreeMap::from([
+                ("region".to_string(), "eu".to_string()),
+                ("team".to_string(), "payments".to_string()),
+            ]),
+            ..MetricsConfig::default()
+        };
+        let mut metrics = collect(|meter| {
+            meter
+                .u64_counter("requests")
+                .build()
+                .add(1, &[KeyValue::new("region", "us")]);
+        });
+
+        Transform::new(&cfg).apply(&mut metrics);
+
+        assert_eq!(
+            sum_points(&metrics, "requests"),
+            vec![(
+                vec![
+                    KeyValue::new("region", "us"),
+                    KeyValue::new("team", "payments")
+                ],
+                1
+            )]
+        );
+    }
+
+    #[test]
+    fn round_decimals_rounds_float_sums_and_gauges() {
+        let cfg = MetricsConfig {
+            round_decimals: Some(2),
+            ..MetricsConfig::default()
+        };
+        let mut metrics = collect(|meter| {
+            meter.f64_counter("cpu").build().add(1.23456, &[]);
+            meter.f64_gauge("load").build().record(0.987, &[]);
+            meter.u64_counter("requests").build().add(7, &[]);
+        });
+
+        Transform::new(&cfg).apply(&mut metrics);
+
+        let cpu = test_util::metric(&metrics, "cpu").expect("sum");
+        let cpu = cpu.data.as_any().downcast_ref::<Sum<f64>>().unwrap();
+        assert_eq!(cpu.data_points[0].value, 1.23);
+        let load = test_util::metric(&metrics, "load").expect("gauge");
+        let load = load.data.as_any().downcast_ref::<Gauge<f64>>().unwrap();
+        assert_eq!(load.data_points[0].value, 0.99);
+        assert_eq!(sum_points(&metrics, "requests"), vec![(Vec::new(), 7)]);
+    }
+
+    #[test]
+    fn round_decimals_keeps_values_too_large_to_scale() {
+        let cfg = MetricsConfig {
+            round_decimals: Some(10),
+            ..MetricsConfig::default()
+        };
+        let mut metrics = collect(|meter| {
+            meter.f64_gauge("load").build().record(1e300, &[]);
+        });
+
+        Transform::new(&cfg).apply(&mut metrics);
+
+        let load = test_util::metric(&metrics, "load").expect("gauge");
+        let load = load.data.as_any().downcast_ref::<Gauge<f64>>().unwrap();
+        assert_eq!(load.data_points[0].value, 1e300);
+    }
+
+    #[test]
+    fn histogram_min_max_is_removed_when_disabled() {
+        for (enabled, expected) in [(true, Some(4.0)), (false, None)] {
+            let cfg = MetricsConfig {
+                histogram_min_max: enabled,
+                ..MetricsConfig::default()
+            };
+            let mut metrics = collect(|meter| {
+                meter.f64_histogram("latency").build().record(4.0, &[]);
+            });
+
+            Transform::new(&cfg).apply(&mut metrics);
+
+            let latency = test_util::metric(&metrics, "latency").expect("histogram");
+            let hist = latency
+                .data
+                .as_any()
+                .downcast_ref::<Histogram<f64>>()
+                .unwrap();
+            assert_eq!(hist.data_points[0].max, expected);
+            assert_eq!(hist.data_points[0].min, expected);
+            assert_eq!(hist.data_points[0].count, 1);
+        }
+    }
+
+    #[test]
+    fn resource_keys_and_exclusions_apply_per_exporter() {
+        let cfg = MetricsConfig {
+            service_name: "checkout".to_string(),
+            resource_keys: BTreeMap::from([(
+                ExporterKind::Prometheus,
+                vec!["service.name".to_string(), "environment".to_string()],
+            )]),
+            resource_exclude: BTreeMap::from([(
+                ExporterKind::Prometheus,
+                vec!["environment".to_string()],
+            )]),
+            ..MetricsConfig::default()
+        };
+        let (_provider, reader) = test_util::provider(&cfg);
+        let mut prometheus = test_util::collect(&reader);
+        let mut otlp = test_util::collect(&reader);
+
+        Transform::for_exporter(&cfg, ExporterKind::Prometheus).apply(&mut prometheus);
+        Transform::for_exporter(&cfg, ExporterKind::Otlp).apply(&mut otlp);
+
+        let keys = prometheus
+            .resource
+            .iter()
+            .map(|(k, _)| k.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["service.name".to_string()]);
+        assert!(
+            otlp.resource
+                .get(&Key::from_static_str("environment"))
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn gauges_on_change_skips_unchanged_series() {
+        let cfg = MetricsConfig {
+            gauges_on_change: true,
+            ..MetricsConfig::default()
+        };
+        let transform = Transform::new(&cfg);
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        let meter = provider.meter("test");
+        let queue = meter.u64_gauge("queue").build();
+        meter.u64_counter("requests").build().add(1, &[]);
+        let collect = || {
+            let mut metrics = test_util::collect(&reader);
+            transform.apply(&mut metrics);
+            metrics
+        };
+
+        queue.record(3, &[]);
+        let first = collect();
+        let unchanged = collect();
+        queue.record(4, &[]);
+        let changed = collect();
+
+        assert!(test_util::metric(&first, "queue").is_some());
+        assert!(test_util::metric(&unchanged, "queue").is_none());
+        assert!(test_util::metric(&unchanged, "requests").is_some());
+        assert!(test_util::metric(&changed, "queue").is_some());
+    }
+
+    #[test]
+    fn full_resource_is_sent_once_every_n_exports() {
+        let cfg = MetricsConfig {
+            service_name: "checkout".to_string(),
+            environment: "production".to_string(),
+            full_resource_every: 3,
+            ..MetricsConfig::default()
+        };
+        let (_provider, reader) = test_util::provider(&cfg);
+        let transform = Transform::for_exporter(&cfg, ExporterKind::Otlp);
+        let full = |_| {
+            let mut metrics = test_util::collect(&reader);
+            transform.apply(&mut metrics);
+            assert_eq!(
+                metrics.resource.get(&Key::from_static_str("service.name")),
+                Some("checkout".into())
+            );
+            metrics
+                .resource
+                .get(&Key::from_static_str("environment"))
+                .is_some()
+        };
+
+        let exports = (0..4).map(full).collect::<Vec<_>>();
+
+        assert_eq!(exports, vec![true, false, false, true]);
+    }
+
+    #[test]
+    fn full_resource_every_only_applies_to_otlp() {
+        let cfg = MetricsConfig {
+            environment: "production".to_string(),
+            full_resource_every: 3,
+            ..MetricsConfig::default()
+        };
+        let (_provider, reader) = test_util::provider(&cfg);
+        let transform = Transform::for_exporter(&cfg, ExporterKind::Statsd);
+
+        for _ in 0..2 {
+            let mut metrics = test_util::collect(&reader);
+            transform.apply(&mut metrics);
+            assert!(
+                metrics
+                    .resource
+                    .get(&Key::from_static_str("environment"))
+                    .is_some()
+            );
+        }
+    }
+
+    #[test]
+    fn external_points_are_merged_into_the_export() {
+        let cfg = MetricsConfig {
+            global_attributes: BTreeMap::from([("region".to_string(), "eu".to_string())]),
+            external_producer: Some(ExternalProducer::new(|| {
+                vec![
+                    ExternalPoint {
+                        name: "legacy.jobs".to_string(),
+                        attributes: vec![KeyValue::new("queue", "a")],
+                        value: 3.0,
+                        kind: ExternalKind::Counter,
+                    },
+                    ExternalPoint {
+                        name: "legacy.jobs".to_string(),
+                        attributes: vec![KeyValue::new("queue", "b")],
+                        value: 1.0,
+                        kind: ExternalKind::Counter,
+                    },
+                    ExternalPoint {
+                        name: "legacy.temperature".to_string(),
+                        attributes: Vec::new(),
+                        value: 21.5,
+                        kind: ExternalKind::Gauge,
+                    },
+                ]
+            })),
+            ..MetricsConfig::default()
+        };
+        let mut metrics = test_util::empty_metrics();
+
+        Transform::new(&cfg).apply(&mut metrics);
+
+        assert_eq!(metrics.scope_metrics[0].scope.name(), "external");
+        let jobs = test_util::metric(&metrics, "legacy.jobs")
+            .and_then(|metric| metric.data.as_any().downcast_ref::<Sum<f64>>())
+            .expect("f64 sum");
+        assert!(jobs.is_monotonic);
+        assert_eq!(jobs.data_points.len(), 2);
+        assert!(
+            jobs.data_points
+                .iter()
+                .all(|p| p.attributes.contains(&KeyValue::new("region", "eu")))
+        );
+        let temperature = test_util::metric(&metrics, "legacy.temperature")
+            .and_then(|metric| metric.data.as_any().downcast_ref::<Gauge<f64>>())
+            .expect("f64 gauge");
+        assert_eq!(temperature.data_points[0].value, 21.5);
+    }
+
+    #[test]
+    fn collector_compat_sends_the_full_resource_every_time() {
+        let cfg = MetricsConfig {
+            environment: "production".to_string(),
+            full_resource_every: 3,
+            collector_compat: true,
+            ..MetricsConfig::default()
+        };
+        let (_provider, reader) = test_util::provider(&cfg);
+        let transform = Transform::for_exporter(&cfg, ExporterKind::Otlp);
+
+        for _ in 0..2 {
+            let mut metrics = test_util::collect(&reader);
+            transform.apply(&mut metrics);
+            assert!(
+                metrics
+                    .resource
+                    .get(&Key::from_static_str("environment"))
+                    .is_some()
+            );
+        }
+    }
+
+    #[test]
+    fn masked_series_are_merged() {
+        let cfg = MetricsConfig {
+            redacted_attributes: vec!["user".to_string()],
+            ..MetricsConfig::default()
+        };
+        let mut metrics = collect(|meter| {
+            let requests = meter.u64_counter("requests").build();
+            requests.add(2, &[KeyValue::new("user", "a@example.com")]);
+            requests.add(3, &[KeyValue::new("user", "b@example.com")]);
+            requests.add(
+                1,
+                &[
+                    KeyValue::new("user", "a@example.com"),
+                    KeyValue::new("method", "GET"),
+                ],
+            );
+            let latency = meter.f64_histogram("latency").build();
+            latency.record(1.0, &[KeyValue::new("user", "a@example.com")]);
+            latency.record(30.0, &[KeyValue::new("user", "b@example.com")]);
+        });
+
+        Transform::new(&cfg).apply(&mut metrics);
+
+        assert_eq!(
+            sum_points(&metrics, "requests"),
+            vec![
+                (
+                    vec![
+                        KeyValue::new("method", "GET"),
+                        KeyValue::new("user", REDACTED)
+                    ],
+                    1
+                ),
+                (vec![KeyValue::new("user", REDACTED)], 5),
+            ]
+        );
+        let latency = test_util::metric(&metrics, "latency")
+            .and_then(|metric| metric.data.as_any().downcast_ref::<Histogram<f64>>())
+            .expect("f64 histogram");
+        let point = &latency.data_points[..];
+        assert_eq!(point.len(), 1);
+        assert_eq!(point[0].count, 2);
+        assert_eq!(point[0].sum, 31.0);
+        assert_eq!((point[0].min, point[0].max), (Some(1.0), Some(30.0)));
+        assert_eq!(point[0].bucket_counts.iter().sum::<u64>(), 2);
+    }
+
+    #[test]
+    fn hashed_series_stay_apart() {
+        let cfg = MetricsConfig {
+            redacted_attributes: vec!["user".to_string()],
+            redaction: Redaction::Hash,
+            ..MetricsConfig::default()
+        };
+        let mut metrics = collect(|meter| {
+            let requests = meter.u64_counter("requests").build();
+            requests.add(2, &[KeyValue::new("user", "a@example.com")]);
+            requests.add(3, &[KeyValue::new("user", "b@example.com")]);
+        });
+
+        Transform::new(&cfg).apply(&mut metrics);
+
+        let points = sum_points(&metrics, "requests");
+        assert_eq!(points.len(), 2);
+        assert!(
+            points
+                .iter()
+                .all(|(attributes, _)| attributes[0].value.as_str().len() == 16)
+        );
+    }
+
+    #[test]
+    fn exponential_buckets_are_merged_at_the_coarser_scale() {
+        let mut into = ExponentialBucket {
+            offset: 4,
+            counts: vec![1, 2],
+        };
+        let from = ExponentialBucket {
+            offset: -1,
+            counts: vec![3],
+        };
+
+        // into is one scale finer, its buckets 4 and 5 both map to bucket 2
+        merge_buckets(&mut into, 1, &from, 0);
+
+        assert_eq!(into.offset, -1);
+        assert_eq!(into.counts, vec![3, 0, 0, 3]);
+    }
+}
@@ -0,0 +1,519 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Metrics Views
+//!
+//! Provides the views registered on the meter provider by the exporters.
+//!
+//! Views let the crate change how instruments are exported without touching the
+//! instrumentation code. They are built from the [`MetricsConfig`] used at install time and
+//! registered on the `MeterProviderBuilder` before the provider is built.
+//!
+//! ## Histogram Boundaries
+//!
+//! The SDK only applies the boundaries an instrument declares with `with_boundaries` when no
+//! view matches it, and does not pass them to the views. The view therefore only matches the
+//! instruments it changes: the ones it renames, re-aggregates, drops or exports under an
+//! alias. Every other instrument keeps the SDK default view and its declared boundaries. An
+//! instrument the view does change uses the boundaries of its aggregation, the SDK default ones
+//! unless the `aggregation_selector` chooses others. `histogram_min_max` is applied at export
+//! by the transforms, not by a view, so it never affects the boundaries.
+//!
+//! ## Gauges
+//!
+//! Gauges and observable gauges always use the last value aggregation of the SDK, so the
+//! exported value is the last observation of the collection window whatever the temporality.
+//! The `aggregation_selector` is not consulted for them.
+//!
+//! ## Exponential Histograms
+//!
+//! Backends differ in the resolution of the exponential histograms they accept, some rejecting
+//! scales above 8. `exponential_max_size` and `exponential_max_scale` override the maximum
+//! number of buckets and the maximum scale of every exponential histogram aggregation chosen by
+//! the `aggregation_selector`. The scale is clamped to the range the SDK accepts, -10 to 20.
+//!
+//! Older collectors reject exponential histograms altogether. With `collector_compat`, every
+//! exponential histogram chosen by the `aggregation_selector` is exported as an explicit bucket
+//! histogram with the default boundaries instead, keeping its min and max setting.
+//!
+//! ## Disabled Instruments
+//!
+//! Instrument classes listed in `disabled_instruments` are dropped: they keep accepting
+//! recordings but produce no data points. This cuts the cost of expensive classes such as
+//! histograms without touching the instrumentation code.
+//!
+//! ## Instrument Catalog
+//!
+//! When an `instrument_catalog` is configured, the view adds every instrument it sees to the
+//! catalog. The SDK runs the views when an instrument is created, so the catalog lists exactly
+//! the instruments the application registered.
+//!
+//! ## Aliases
+//!
+//! Every instrument listed in `metric_aliases` is also exported under each of its aliases, with
+//! the same description, unit and aggregation, which lets dashboards move from the old name to
+//! the new one during a rename. Instruments are matched by the name they were created with, and
+//! the aliases are exported as written, without the `name_transform` hook.
+//!
+//! ## Usage
+//!
+//! Views are used internally by exporters. Applications configure them through the fields of
+//! [`MetricsConfig`] rather than interacting with this module directly.
+
+use crate::config::MetricsConfig;
+use opentelemetry_sdk::metrics::{
+    Aggregation, Instrument, InstrumentKind, MeterProviderBuilder, Stream,
+};
+use std::sync::Arc;
+
+/// The bucket boundaries the SDK uses for histograms without an explicit aggregation, given
+/// to the explicit bucket histograms replacing exponential ones.
+const DEFAULT_HISTOGRAM_BOUNDARIES: [f64; 15] = [
+    0.0, 5.0, 10.0, 25.0, 50.0, 75.0, 100.0, 250.0, 500.0, 750.0, 1000.0, 2500.0, 5000.0, 7500.0,
+    10000.0,
+];
+
+/// The lowest scale of an exponential histogram accepted by the SDK.
+const EXPONENTIAL_MIN_SCALE: i8 = -10;
+
+/// The highest scale of an exponential histogram accepted by the SDK.
+const EXPONENTIAL_MAX_SCALE: i8 = 20;
+
+/// Registers the views derived from the configuration on the provider builder.
+///
+/// The SDK exports one stream per matching view, so every option is applied by a single
+/// composed view, and each alias adds a view matching only the aliased instrument. The composed
+/// view does not match the instruments it leaves unchanged, and when no view related option is
+/// configured the builder is returned unchanged, so the SDK default view applies.
+///
+/// # Parameters
+///
+/// * `builder` - The meter provider builder to register the views on
+/// * `cfg` - The metrics configuration
+///
+/// # Returns
+///
+/// The builder with the configured views registered
+pub(crate) fn register(builder: MeterProviderBuilder, cfg: &MetricsConfig) -> MeterProviderBuilder {
+    if cfg.name_transform.is_none()
+        && cfg.aggregation_selector.is_none()
+        && cfg.disabled_instruments.is_empty()
+        && cfg.instrument_catalog.is_none()
+        && cfg.metric_aliases.is_empty()
+        && cfg.exponential_max_size.is_none()
+        && cfg.exponential_max_scale.is_none()
+        && !cfg.collector_compat
+    {
+        return builder;
+    }
+
+    let transform = cfg.name_transform.clone();
+    let selector = cfg.aggregation_selector.clone();
+    let disabled = cfg.disabled_instruments.clone();
+    let catalog = cfg.instrument_catalog.clone();
+    let max_size = cfg.exponential_max_size;
+    let max_scale = cfg.exponential_max_scale;
+    let collector_compat = cfg.collector_compat;
+    let aliased: Vec<String> = cfg.metric_aliases.keys().cloned().collect();
+
+    // the stream of the instrument, and whether it differs from the SDK default one
+    let stream = Arc::new(move |inst: &Instrument| {
+        let name = match &transform {
+            Some(transform) => transform.apply(&inst.name).into(),
+            None => inst.name.clone(),
+        };
+        let mut changed = name != inst.name;
+
+        let mut stream = Stream::new()
+            .name(name)
+            .description(inst.description.clone())
+            .unit(inst.unit.clone());
+
+        let is_disabled = inst
+            .kind
+            .is_some_and(|kind| disabled.iter().any(|class| class.matches(kind)));
+        if is_disabled {
+            return (stream.aggregation(Aggregation::Drop), true);
+        }
+
+        let aggregation = selector
+            .as_ref()
+            .zip(inst.kind)
+            .filter(|(_, kind)| !is_gauge(*kind))
+            .and_then(|(selector, kind)| selector.select(kind));
+        if let Some(aggregation) = aggregation {
+            let aggregation = if collector_compat {
+                without_exponential(aggregation)
+            } else {
+                limit_exponential(aggregation, max_size, max_scale)
+            };
+            stream = stream.aggregation(aggregation);
+            changed = true;
+        }
+
+        (stream, changed)
+    });
+
+    let mut builder = builder.with_view({
+        let stream = stream.clone();
+        move |inst: &Instrument| {
+            if let Some(catalog) = &catalog {
+                catalog.register(inst);
+            }
+
+            // an aliased instrument matches its alias views, which would hide the default one
+            let (stream, changed) = stream(inst);
+            (changed || aliased.iter().any(|name| inst.name == name.as_str())).then_some(stream)
+        }
+    });
+
+    for (original, aliases) in &cfg.metric_aliases {
+        for alias in aliases {
+            let stream = stream.clone();
+            let original = original.clone();
+            let alias = alias.clone();
+            builder = builder.with_view(move |inst: &Instrument| {
+                (inst.name == original.as_str()).then(|| stream(inst).0.name(alias.clone()))
+            });
+        }
+    }
+
+    builder
+}
+
+/// Returns whether the instrument kind is a gauge, which always keeps the last value
+/// aggregation.
+fn is_gauge(kind: InstrumentKind) -> bool {
+    matches!(
+        kind,
+        InstrumentKind::Gauge | InstrumentKind::ObservableGauge
+    )
+}
+
+/// Overrides the maximum size and scale of an exponential histogram aggregation, when
+/// configured. Other aggregations are returned unchanged.
+fn limit_exponential(
+    aggregation: Aggregation,
+    max_size: Option<u32>,
+    max_scale: Option<i8>,
+) -> Aggregation {
+    match aggregation {
+        Aggregation::Base2ExponentialHistogram {
+            max_size: size,
+            max_scale: scale,
+            record_min_max,
+        } => Aggregation::Base2ExponentialHistogram {
+            max_size: max_size.unwrap_or(size),
+            max_scale: max_scale.map_or(scale, |scale| {
+                scale.clamp(EXPONENTIAL_MIN_SCALE, EXPONENTIAL_MAX_SCALE)
+            }),
+            record_min_max,
+        },
+        aggregation => aggregation,
+    }
+}
+
+/// Replaces an exponential histogram aggregation with an explicit bucket histogram using the
+/// default boundaries. Other aggregations are returned unchanged.
+fn without_exponential(aggregation: Aggregation) -> Aggregation {
+    match aggregation {
+        Aggregation::Base2ExponentialHistogram { record_min_max, .. } => {
+            Aggregation::ExplicitBucketHistogram {
+                boundaries: DEFAULT_HISTOGRAM_BOUNDARIES.to_vec(),
+                record_min_max,
+            }
+        }
+        aggregation => aggregation,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{
+            AggregationSelector, InstrumentCatalog, InstrumentClass, InstrumentInfo, NameTransform,
+        },
+        exporters::transform::Transform,
+        test_util,
+    };
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry_sdk::metrics::data::{ExponentialHistogram, Gauge, Histogram, Sum};
+
+    #[test]
+    fn name_transform_renames_instruments() {
+        let cfg = MetricsConfig {
+            name_transform: Some(NameTransform::new(|name| format!("app_{name}"))),
+            ..MetricsConfig::default()
+        };
+        let (provider, reader) = test_util::provider(&cfg);
+        provider
+            .meter("test")
+            .u64_counter("requests")
+            .build()
+            .add(1, &[]);
+
+        let rm = test_util::collect(&reader);
+
+        assert!(test_util::metric(&rm, "app_requests").is_some());
+        assert!(test_util::metric(&rm, "requests").is_none());
+    }
+
+    #[test]
+    fn aggregation_selector_picks_the_aggregation_per_kind() {
+        let cfg = MetricsConfig {
+            aggregation_selector: Some(AggregationSelector::new(|kind| match kind {
+                InstrumentKind::Histogram => Some(Aggregation::ExplicitBucketHistogram {
+                    boundaries: vec![1.0, 2.0],
+                    record_min_max: false,
+                }),
+                _ => Some(Aggregation::Drop),
+            })),
+            ..MetricsConfig::default()
+        };
+        let (provider, reader) = test_util::provider(&cfg);
+        let meter = provider.meter("test");
+        meter.f64_histogram("latency").build().record(1.5, &[]);
+        meter.u64_counter("requests").build().add(1, &[]);
+        meter.u64_gauge("queue").build().record(4, &[]);
+
+        let rm = test_util::collect(&reader);
+
+        let latency = test_util::metric(&rm, "latency").expect("histogram");
+        let hist = latency
+            .data
+            .as_any()
+            .downcast_ref::<Histogram<f64>>()
+            .unwrap();
+        assert_eq!(hist.data_points[0].bounds, vec![1.0, 2.0]);
+        assert_eq!(hist.data_points[0].min, None);
+        assert!(test_util::metric(&rm, "requests").is_none());
+        // gauges keep the last value aggregation whatever the selector returns
+        let queue = test_util::metric(&rm, "queue").expect("gauge");
+        assert!(queue.data.as_any().is::<Gauge<u64>>());
+    }
+
+    #[test]
+    fn declared_boundaries_are_kept() {
+        let cfg = MetricsConfig {
+            histogram_min_max: false,
+            disabled_instruments: vec![InstrumentClass::Counter],
+            ..MetricsConfig::default()
+        };
+        let (provider, reader) = test_util::provider(&cfg);
+        provider
+            .meter("test")
+            .f64_histogram("latency")
+            .with_boundaries(vec![1.0, 2.0])
+            .build()
+            .record(1.5, &[]);
+
+        let mut rm = test_util::collect(&reader);
+        Transform::new(&cfg).apply(&mut rm);
+
+        let latency = test_util::metric(&rm, "latency").expect("histogram");
+        let hist = latency
+            .data
+            .as_any()
+            .downcast_ref::<Histogram<f64>>()
+            .unwrap();
+        assert_eq!(hist.data_points[0].bounds, vec![1.0, 2.0]);
+        assert_eq!(hist.data_points[0].max, None);
+    }
+
+    #[test]
+    fn disabled_instruments_produce_no_data() {
+        let cfg = MetricsConfig {
+            disabled_instruments: vec![InstrumentClass::Histogram],
+            ..MetricsConfig::default()
+        };
+        let (provider, reader) = test_util::provider(&cfg);
+        let meter = provider.meter("test");
+        meter.f64_histogram("latency").build().record(1.5, &[]);
+        meter.u64_counter("requests").build().add(1, &[]);
+
+        let rm = test_util::collect(&reader);
+
+        assert!(test_util::metric(&rm, "latency").is_none());
+        assert!(test_util::metric(&rm, "requests").is_some());
+    }
+
+    #[test]
+    fn instrument_classes_cover_observable_kinds() {
+        assert!(InstrumentClass::Counter.matches(InstrumentKind::ObservableCounter));
+        assert!(InstrumentClass::Gauge.matches(InstrumentKind::ObservableGauge));
+        assert!(!InstrumentClass::Counter.matches(InstrumentKind::UpDownCounter));
+    }
+
+    #[test]
+    fn catalog_lists_the_registered_instruments() {
+        let catalog = InstrumentCatalog::default();
+        let cfg = MetricsConfig {
+            instrument_catalog: Some(catalog.clone()),
+            ..MetricsConfig::default()
+        };
+        let (provider, _reader) = test_util::provider(&cfg);
+        let meter = provider.meter("http");
+        meter
+            .u64_counter("requests")
+            .with_unit("{request}")
+            .with_description("Handled requests")
+            .build();
+        meter.f64_histogram("latency").build();
+        meter.u64_counter("requests").build();
+
+        let entries = catalog.entries();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "latency");
+        assert_eq!(entries[0].kind, Some(InstrumentKind::Histogram));
+        assert_eq!(
+            entries[1],
+            InstrumentInfo {
+                scope: "http".to_string(),
+                name: "requests".to_string(),
+                kind: Some(InstrumentKind::Counter),
+                unit: "{request}".to_string(),
+                description: "Handled requests".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn gauges_keep_the_last_value_whatever_the_selector() {
+        let cfg = MetricsConfig {
+            aggregation_selector: Some(AggregationSelector::new(|_| Some(Aggregation::Sum))),
+            ..MetricsConfig::default()
+        };
+        let (provider, reader) = test_util::provider(&cfg);
+        let queue = provider.meter("test").u64_gauge("queue").build();
+        queue.record(5, &[]);
+        queue.record(3, &[]);
+
+        let rm = test_util::collect(&reader);
+
+        let queue = test_util::metric(&rm, "queue").expect("gauge");
+        let gauge = queue.data.as_any().downcast_ref::<Gauge<u64>>().unwrap();
+        assert_eq!(gauge.data_points[0].value, 3);
+    }
+
+    #[test]
+    fn aliases_export_the_instrument_under_both_names() {
+        let cfg = MetricsConfig {
+            metric_aliases: [("requests".to_string(), vec!["http.requests".to_string()])].into(),
+            ..MetricsConfig::default()
+        };
+        let (provider, reader) = test_util::provider(&cfg);
+        let meter = provider.meter("test");
+        meter.u64_counter("requests").build().add(3, &[]);
+        meter.u64_counter("errors").build().add(1, &[]);
+
+        let rm = test_util::collect(&reader);
+        let sum = |name| {
+            test_util::metric(&rm, name)
+                .and_then(|metric| metric.data.as_any().downcast_ref::<Sum<u64>>())
+                .map(|sum| sum.data_points[0].value)
+        };
+
+        assert_eq!(sum("requests"), Some(3));
+        assert_eq!(sum("http.requests"), Some(3));
+        assert_eq!(sum("errors"), Some(1));
+        assert_eq!(
+            rm.scope_metrics
+                .iter()
+                .map(|scope| scope.metrics.len())
+                .sum::<usize>(),
+            3
+        );
+    }
+
+    fn exponential_selector() -> AggregationSelector {
+        AggregationSelector::new(|kind| match kind {
+            InstrumentKind::Histogram => Some(Aggregation::Base2ExponentialHistogram {
+                max_size: 160,
+                max_scale: 20,
+                record_min_max: true,
+            }),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn exponential_max_size_bounds_the_buckets() {
+        let cfg = MetricsConfig {
+            aggregation_selector: Some(exponential_selector()),
+            exponential_max_size: Some(4),
+            ..MetricsConfig::default()
+        };
+        let (provider, reader) = test_util::provider(&cfg);
+        let latency = provider.meter("test").f64_histogram("latency").build();
+        for value in 1..=100 {
+            latency.record(f64::from(value), &[]);
+        }
+
+        let rm = test_util::collect(&reader);
+
+        let hist = test_util::metric(&rm, "latency")
+            .and_then(|metric| {
+                metric
+                    .data
+                    .as_any()
+                    .downcast_ref::<ExponentialHistogram<f64>>()
+            })
+            .expect("exponential histogram");
+        let point = &hist.data_points[0];
+        assert!(point.positive_bucket.counts.len() <= 4);
+        assert_eq!(point.count, 100);
+    }
+
+    #[test]
+    fn exponential_max_scale_is_clamped_to_the_sdk_range() {
+        let scale = |max_scale| {
+            let aggregation = Aggregation::Base2ExponentialHistogram {
+                max_size: 160,
+                max_scale: 20,
+                record_min_max: true,
+            };
+            match limit_exponential(aggregation, None, max_scale) {
+                Aggregation::Base2ExponentialHistogram { max_scale, .. } => max_scale,
+                _ => unreachable!("the aggregation stays exponential"),
+            }
+        };
+
+        assert_eq!(scale(Some(50)), EXPONENTIAL_MAX_SCALE);
+        assert_eq!(scale(Some(-50)), EXPONENTIAL_MIN_SCALE);
+        assert_eq!(scale(Some(5)), 5);
+        assert_eq!(scale(None), 20);
+    }
+
+    #[test]
+    fn collector_compat_replaces_exponential_histograms() {
+        let cfg = MetricsConfig {
+            aggregation_selector: Some(exponential_selector()),
+            exponential_max_size: Some(4),
+            collector_compat: true,
+            ..MetricsConfig::default()
+        };
+        let (provider, reader) = test_util::provider(&cfg);
+        provider
+            .meter("test")
+            .f64_histogram("latency")
+            .build()
+            .record(12.0, &[]);
+
+        let rm = test_util::collect(&reader);
+
+        let latency = test_util::metric(&rm, "latency").expect("histogram");
+        let hist = latency
+            .data
+            .as_any()
+            .downcast_ref::<Histogram<f64>>()
+            .expect("explicit bucket histogram");
+        assert_eq!(
+            hist.data_points[0].bounds,
+            DEFAULT_HISTOGRAM_BOUNDARIES.to_vec()
+        );
+        assert_eq!(hist.data_points[0].min, Some(12.0));
+    }
+}
@@ -0,0 +1,55 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Feature Matrix
+//!
+//! Resolves the exporter selected by the enabled features, and checks at compile time that the
+//! selection is consistent.
+//!
+//! Every exporter feature can be combined with the others: when several are enabled, the one
+//! with the highest precedence is installed unless the configuration requests another one. The
+//! precedence is OTLP, Prometheus, stdout and StatsD, and the no-op exporter is selected when
+//! none of them is enabled. The assertions below turn a combination the selection does not
+//! handle into a build error with a clear message, instead of an exporter that is silently
+//! never reached.
+
+use crate::config::ExporterKind;
+
+/// The exporter installed when the configuration does not request one.
+pub(crate) const DEFAULT_EXPORTER: ExporterKind = default_exporter();
+
+const fn default_exporter() -> ExporterKind {
+    if cfg!(feature = "otlp") {
+        ExporterKind::Otlp
+    } else if cfg!(feature = "prometheus") {
+        ExporterKind::Prometheus
+    } else if cfg!(feature = "stdout") {
+        ExporterKind::Stdout
+    } else if cfg!(feature = "statsd") {
+        ExporterKind::Statsd
+    } else {
+        ExporterKind::None
+    }
+}
+
+const _: () = assert!(
+    DEFAULT_EXPORTER.is_compiled(),
+    "the default metric exporter must be compiled in"
+);
+
+const _: () = assert!(
+    ExporterKind::None.is_compiled(),
+    "the no-op metric exporter must always be available"
+);
+
+const _: () = assert!(
+    matches!(DEFAULT_EXPORTER, ExporterKind::None)
+        == cfg!(not(any(
+            feature = "otlp",
+            feature = "prometheus",
+            feature = "stdout",
+            feature = "statsd"
+        ))),
+    "an enabled exporter feature must be selectable as the default exporter"
+);
@@ -17,12 +17,14 @@
 //!
 //! - **Multiple Exporters**: Support for various metric export formats:
 //!   - **OTLP**: Export metrics using OpenTelemetry Protocol over gRPC (requires `otlp` feature)
-//!   - **Prometheus**: Expose metrics in Prometheus format via HTTP endpoint (requires `prometheus` feature)
+//!   - **Prometheus**: Render metrics in Prometheus format for an HTTP scrape endpoint (requires `prometheus` feature)
+//!   - **StatsD**: Send metrics to a StatsD server over UDP (requires `statsd` feature)
 //!   - **Stdout**: Write metrics to standard output for development (requires `stdout` feature)
 //! - **Smart Temporality Selection**: Automatically selects optimal temporality strategy based on the metric type
 //! - **Resource Attribution**: Automatically adds service name, namespace, environment and other attributes
 //! - **Unified Interface**: Common API across all exporters
 //! - **Ruskit Integration**: Seamless integration with Ruskit's configuration system
+//! - **Early Startup Buffering**: Metrics recorded before installation can be buffered and replayed
 //! - **Comprehensive Error Handling**: Well-defined error types for better debugging
 //!
 //! ## Example
@@ -51,11 +53,60 @@
 //!
 //! ## Feature Flags
 //!
+//! - `custom-push`: Enable the exporter posting application-serialized metrics over HTTP
 //! - `otlp`: Enable OpenTelemetry Protocol (OTLP) exporter over gRPC
+//! - `prometheus`: Enable the Prometheus exporter, rendering the text format for scraping
+//! - `self-metrics`: Enable the export duration histogram of the OTLP exporter
+//! - `statsd`: Enable the StatsD exporter over UDP, with DogStatsD tags
 //! - `stdout`: Enable standard output exporter (useful for development)
+//! - `stdout-pipe`: Enable writing OTLP JSON lines to a named pipe instead of standard output
+//! - `test-support`: Enable the in-memory `TestHarness` for testing instrumented code
+//! - `tracing-bridge`: Enable the `tracing` layer that turns event fields into metrics
 //!
 //! If no export features are enabled, a no-op implementation will be used.
+//!
+//! ## Logging
+//!
+//! The crate logs through `tracing` under the `metrics::exporter` target, so its output can be
+//! filtered independently, for example with `EnvFilter::new("info,metrics::exporter=warn")`.
+//! Routine lifecycle events such as installation are logged at `debug`, degraded exports at
+//! `warn` and failures at `error`.
 
+/// Target of every log event emitted by the crate.
+pub(crate) const LOG_TARGET: &str = "metrics::exporter";
+
+pub mod baggage;
+pub mod bound;
+pub mod buffered;
+mod builtin;
+pub mod cardinality;
+pub mod config;
+pub mod deferred;
 pub mod errors;
 pub mod exporters;
+mod features;
+pub mod non_finite;
+pub mod observable;
 pub mod provider;
+pub mod scope;
+pub mod timer;
+pub mod up_down;
+pub mod validation;
+
+#[cfg(feature = "test-support")]
+pub mod testing;
+
+#[cfg(test)]
+mod test_util;
+
+#[cfg(feature = "tracing-bridge")]
+pub mod tracing_bridge;
+
+pub use bound::{bind, inc};
+pub use deferred::defer_until_installed;
+pub use observable::{observable_counter, observable_gauge, observable_up_down_counter};
+pub use provider::init;
+pub use scope::meter_with_attrs;
+pub use timer::{Timer, timer, timer_ms};
+pub use up_down::{observable_up_down_counter_with, up_down_counter};
+pub use validation::validate_instruments;
@@ -0,0 +1,91 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Non-Finite Values
+//!
+//! Applies the configured [`NonFinitePolicy`] to the values recorded by the helper instruments.
+//!
+//! The OpenTelemetry API records whatever it is given, so a division bug can put `NaN` into a
+//! histogram and poison every export of that series. The helpers of this crate
+//! ([`bind`](crate::bind), [`BufferedRecorder`](crate::buffered::BufferedRecorder),
+//! [`observable_gauge`](crate::observable_gauge) and the tracing bridge) pass every value
+//! through [`MeasurementValue::guard`] first. Instruments used directly are not affected.
+//!
+//! The policy is set from `MetricsConfig::non_finite` when the provider is installed; until
+//! then values pass through unchanged. Integer values are always finite and are never checked.
+
+use crate::{LOG_TARGET, config::NonFinitePolicy};
+use std::sync::RwLock;
+use tracing::warn;
+
+static POLICY: RwLock<NonFinitePolicy> = RwLock::new(NonFinitePolicy::Pass);
+
+/// Sets the policy applied to non-finite values.
+pub(crate) fn configure(policy: NonFinitePolicy) {
+    *POLICY.write().unwrap_or_else(|e| e.into_inner()) = policy;
+}
+
+/// # MeasurementValue
+///
+/// A value type the instruments record, checked against the configured [`NonFinitePolicy`].
+pub trait MeasurementValue: Sized {
+    /// Applies the policy to the value.
+    ///
+    /// # Returns
+    ///
+    /// The value to record, or `None` if the recording must be skipped
+    fn guard(self) -> Option<Self>;
+}
+
+impl MeasurementValue for u64 {
+    fn guard(self) -> Option<Self> {
+        Some(self)
+    }
+}
+
+impl MeasurementValue for i64 {
+    fn guard(self) -> Option<Self> {
+        Some(self)
+    }
+}
+
+impl MeasurementValue for f64 {
+    fn guard(self) -> Option<Self> {
+        if self.is_finite() {
+            return Some(self);
+        }
+
+        match *POLICY.read().unwrap_or_else(|e| e.into_inner()) {
+            NonFinitePolicy::Pass => Some(self),
+            NonFinitePolicy::Zero => Some(0.0),
+            NonFinitePolicy::Reject => {
+                warn!(target: LOG_TARGET, value = self, "non-finite measurement rejected");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn policy_applies_to_non_finite_floats_only() {
+        let _lock = test_util::global_lock();
+
+        configure(NonFinitePolicy::Reject);
+        let rejected = (f64::NAN.guard(), f64::INFINITY.guard(), 1.5.guard());
+        configure(NonFinitePolicy::Zero);
+        let zeroed = f64::NEG_INFINITY.guard();
+        configure(NonFinitePolicy::Pass);
+        let passed = f64::INFINITY.guard();
+
+        assert_eq!(rejected, (None, None, Some(1.5)));
+        assert_eq!(zeroed, Some(0.0));
+        assert_eq!(passed, Some(f64::INFINITY));
+        assert_eq!(u64::MAX.guard(), Some(u64::MAX));
+    }
+}
@@ -0,0 +1,185 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Observable Instruments
+//!
+//! Provides helpers creating observable (asynchronous) instruments from a single callback.
+//!
+//! Observable instruments report a value polled at collection time, which suits values such as
+//! queue depths or pool sizes. The OpenTelemetry API requires building the instrument with an
+//! observer callback; these helpers reduce it to a function returning the current value.
+//!
+//! ## Callback Lifetime
+//!
+//! The callback is owned by the meter provider, not by the returned handle: it keeps being
+//! called on every collection until the provider is shut down, even if the handle is dropped.
+//! Keeping the handle is therefore optional.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use metrics::observable_gauge;
+//! use opentelemetry::global;
+//! use std::sync::{
+//!     Arc,
+//!     atomic::{AtomicUsize, Ordering},
+//! };
+//!
+//! let queue_depth = Arc::new(AtomicUsize::new(0));
+//! let depth = queue_depth.clone();
+//!
+//! observable_gauge(&global::meter("jobs"), "jobs.queue.depth", move || {
+//!     depth.load(Ordering::Relaxed) as f64
+//! });
+//! ```
+
+use crate::non_finite::MeasurementValue;
+use opentelemetry::metrics::{
+    AsyncInstrumentBuilder, Meter, ObservableCounter, ObservableGauge, ObservableUpDownCounter,
+};
+use std::borrow::Cow;
+
+/// Creates an observable gauge reporting the value returned by `callback` on every collection.
+///
+/// # Parameters
+///
+/// * `meter` - The meter owning the instrument
+/// * `name` - The name of the instrument
+/// * `callback` - Returns the current value. Non-finite values are handled according to the
+///   configured [`NonFinitePolicy`](crate::config::NonFinitePolicy)
+///
+/// # Returns
+///
+/// A handle to the instrument, which may be dropped without unregistering the callback
+pub fn observable_gauge<F>(
+    meter: &Meter,
+    name: impl Into<Cow<'static, str>>,
+    callback: F,
+) -> ObservableGauge<f64>
+where
+    F: Fn() -> f64 + Send + Sync + 'static,
+{
+    meter
+        .f64_observable_gauge(name)
+        .with_callback(move |observer| {
+            if let Some(value) = callback().guard() {
+                observer.observe(value, &[])
+            }
+        })
+        .build()
+}
+
+/// Creates an observable counter reporting the monotonic total returned by `callback` on
+/// every collection.
+///
+/// # Parameters
+///
+/// * `meter` - The meter owning the instrument
+/// * `name` - The name of the instrument
+/// * `callback` - Returns the current total, which must never decrease
+///
+/// # Returns
+///
+/// A handle to the instrument, which may be dropped without unregistering the callback
+pub fn observable_counter<F>(
+    meter: &Meter,
+    name: impl Into<Cow<'static, str>>,
+    callback: F,
+) -> ObservableCounter<u64>
+where
+    F: Fn() -> u64 + Send + Sync + 'static,
+{
+    meter
+        .u64_observable_counter(name)
+        .with_callback(move |observer| observer.observe(callback(), &[]))
+        .build()
+}
+
+/// Creates an observable up-down counter reporting the total returned by `callback` on every
+/// collection.
+///
+/// # Parameters
+///
+/// * `meter` - The meter owning the instrument
+/// * `name` - The name of the instrument
+/// * `callback` - Returns the current total, which may increase or decrease
+///
+/// # Returns
+///
+/// A handle to the instrument, which may be dropped without unregistering the callback
+pub fn observable_up_down_counter<F>(
+    meter: &Meter,
+    name: impl Into<Cow<'static, str>>,
+    callback: F,
+) -> ObservableUpDownCounter<i64>
+where
+    F: Fn() -> i64 + Send + Sync + 'static,
+{
+    up_down_counter_builder(meter, name, callback).build()
+}
+
+/// Returns the builder of [`observable_up_down_counter`], for the helpers declaring more than
+/// the name.
+pub(crate) fn up_down_counter_builder<F>(
+    meter: &Meter,
+    name: impl Into<Cow<'static, str>>,
+    callback: F,
+) -> AsyncInstrumentBuilder<'_, ObservableUpDownCounter<i64>, i64>
+where
+    F: Fn() -> i64 + Send + Sync + 'static,
+{
+    meter
+        .i64_observable_up_down_counter(name)
+        .with_callback(move |observer| observer.observe(callback(), &[]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::MetricsConfig, test_util};
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry_sdk::metrics::data::{Gauge, ResourceMetrics, Sum};
+    use std::sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    };
+
+    #[test]
+    fn callbacks_are_observed_on_every_collection() {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        let meter = provider.meter("test");
+        let total = Arc::new(AtomicU64::new(3));
+        let _counter = observable_counter(&meter, "jobs.done", {
+            let total = total.clone();
+            move || total.load(Ordering::SeqCst)
+        });
+        let _gauge = observable_gauge(&meter, "queue.fill", || 0.5);
+        let _up_down = observable_up_down_counter(&meter, "workers.delta", || -2);
+
+        let first = test_util::collect(&reader);
+        total.store(7, Ordering::SeqCst);
+        let second = test_util::collect(&reader);
+
+        let done = |rm: &ResourceMetrics| {
+            let metric = test_util::metric(rm, "jobs.done").expect("counter");
+            metric
+                .data
+                .as_any()
+                .downcast_ref::<Sum<u64>>()
+                .unwrap()
+                .data_points[0]
+                .value
+        };
+        assert_eq!(done(&first), 3);
+        assert_eq!(done(&second), 7);
+
+        let fill = test_util::metric(&second, "queue.fill").expect("gauge");
+        let fill = fill.data.as_any().downcast_ref::<Gauge<f64>>().unwrap();
+        assert_eq!(fill.data_points[0].value, 0.5);
+
+        let delta = test_util::metric(&second, "workers.delta").expect("up-down counter");
+        let delta = delta.data.as_any().downcast_ref::<Sum<i64>>().unwrap();
+        assert_eq!(delta.data_points[0].value, -2);
+    }
+}
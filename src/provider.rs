@@ -10,27 +10,71 @@
 //! based on application configuration. It handles feature detection and
 //! initializes the appropriate exporter based on the available features.
 //!
-//! The provider automatically selects the appropriate exporter in the following priority:
+//! The exporter can be selected at runtime with the `exporter` field of the configuration
+//! (`METRIC_EXPORTER` for the ambient configuration), among the exporters compiled in. This
+//! allows a single binary built with every exporter to be deployed across environments.
+//!
+//! When no exporter is configured, the provider automatically selects the appropriate exporter
+//! in the following priority, implemented by `default_exporter` in the `features` module:
 //!
 //! 1. OTLP exporter (when the `otlp` feature is enabled)
-//! 2. Stdout exporter (when the `stdout` feature is enabled)
-//! 3. No-op exporter (when neither of the above features are enabled)
+//! 2. Prometheus exporter (when the `prometheus` feature is enabled)
+//! 3. Stdout exporter (when the `stdout` feature is enabled)
+//! 4. StatsD exporter (when the `statsd` feature is enabled)
+//! 5. No-op exporter (when none of the above features are enabled)
 //!
 //! This design allows applications to switch between exporters by simply changing feature flags
 //! without modifying application code.
+//!
+//! Metrics recorded before the installation are lost unless buffered with
+//! [`defer_until_installed`](crate::defer_until_installed).
+
+#[cfg(feature = "otlp")]
+use crate::config::Detector;
+use crate::{
+    LOG_TARGET, baggage, builtin,
+    config::{EffectiveConfig, EffectiveEndpoint, ExporterKind, MetricsConfig},
+    errors::MetricsError,
+    exporters, features, non_finite,
+};
+#[cfg(feature = "otlp")]
+use opentelemetry::KeyValue;
+use opentelemetry::{
+    InstrumentationScope,
+    metrics::{Meter, MeterProvider},
+};
+#[cfg(feature = "otlp")]
+use opentelemetry_sdk::{Resource, resource::ResourceDetector};
+use opentelemetry_sdk::{
+    error::{OTelSdkError, OTelSdkResult},
+    metrics::SdkMeterProvider,
+};
+#[cfg(feature = "otlp")]
+use std::env;
+use std::{iter, ops::Deref, panic, path::Path, sync::mpsc, thread, time::Duration};
+use tokio::{task, time};
+use tracing::{debug, error, warn};
 
-use crate::{errors::MetricsError, exporters};
-use opentelemetry_sdk::metrics::SdkMeterProvider;
-use tracing::info;
+/// OTLP gRPC port of the Datadog agent.
+#[cfg(feature = "otlp")]
+const DATADOG_OTLP_PORT: u16 = 4317;
+
+/// Maximum duration of the final flush and shutdown in [`run_until_shutdown`] and
+/// [`ShutdownGuard`].
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Initialize and install the metrics provider based on available features.
 ///
-/// This function sets up the appropriate metrics exporter based on the features enabled
-/// during compilation. It automatically configures the following in order of precedence:
+/// This function sets up the metrics exporter requested by the configuration. When none is
+/// requested, it selects the exporter based on the features enabled during compilation, in
+/// the following order of precedence, implemented by `default_exporter` in the `features`
+/// module:
 ///
 /// 1. OTLP exporter (when the `otlp` feature is enabled)
-/// 2. Stdout exporter (when the `stdout` feature is enabled)
-/// 3. No-op exporter (when neither of the above features are enabled)
+/// 2. Prometheus exporter (when the `prometheus` feature is enabled)
+/// 3. Stdout exporter (when the `stdout` feature is enabled)
+/// 4. StatsD exporter (when the `statsd` feature is enabled)
+/// 5. No-op exporter (when none of the above features are enabled)
 ///
 /// The function also configures resource attributes for the metrics including service name,
 /// namespace, environment, and library language.
@@ -38,6 +82,8 @@ use tracing::info;
 /// # Returns
 ///
 /// * `Ok(SdkMeterProvider)` - The configured meter provider that can be used to create meters
+/// * `Err(MetricsError::InvalidFeaturesError)` - If the configuration requests an exporter whose
+///   feature was not enabled at compile time
 /// * `Err(MetricsError)` - If an error occurred during metrics initialization
 ///
 /// # Examples
@@ -72,24 +118,1026 @@ use tracing::info;
 /// // With stdout feature:
 /// // cargo build --features stdout
 ///
+/// // With StatsD feature:
+/// // cargo build --features statsd
+///
 /// // With no specific feature (uses no-op):
 /// // cargo build
 /// ```
 pub fn install() -> Result<SdkMeterProvider, MetricsError> {
-    info!("metrics::install configure metrics...");
+    debug!(target: LOG_TARGET, "metrics::install configure metrics...");
+
+    install_with_config(&MetricsConfig::load())
+}
+
+/// Initialize and install the metrics provider, and return a meter for the given scope.
+///
+/// This function reduces the most common setup to a single call: it behaves like [`install`]
+/// and creates a meter from the installed provider. The provider stays installed as the global
+/// provider; use [`install`] when the provider itself is needed, for example to shut it down.
+///
+/// # Parameters
+///
+/// * `scope` - The name of the instrumentation scope of the meter
+///
+/// # Returns
+///
+/// * `Ok(Meter)` - A meter of the installed provider
+/// * `Err(MetricsError)` - If an error occurred during metrics initialization
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// let meter = metrics::init("my_component").unwrap();
+/// meter.u64_counter("requests").build().add(1, &[]);
+/// ```
+pub fn init(scope: &str) -> Result<Meter, MetricsError> {
+    let provider = install()?;
+
+    Ok(provider.meter_with_scope(InstrumentationScope::builder(scope.to_string()).build()))
+}
+
+/// Initialize and install the metrics provider from a TOML or YAML configuration file.
+///
+/// This function behaves like [`install`], but reads the metrics configuration from the
+/// given file instead of the ambient Ruskit configuration system. This is useful for
+/// standalone tools that don't use the full Ruskit setup.
+///
+/// See [`MetricsConfig`] for the supported fields.
+///
+/// # Parameters
+///
+/// * `path` - Path of a `.toml`, `.yaml` or `.yml` configuration file
+///
+/// # Returns
+///
+/// * `Ok(SdkMeterProvider)` - The configured meter provider
+/// * `Err(MetricsError::InvalidConfigError)` - If the file could not be read or parsed
+/// * `Err(MetricsError)` - If an error occurred during metrics initialization
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use metrics::provider;
+///
+/// let provider = provider::install_from_file("metrics.toml").unwrap();
+/// ```
+pub fn install_from_file(path: impl AsRef<Path>) -> Result<SdkMeterProvider, MetricsError> {
+    debug!(target: LOG_TARGET, "metrics::install_from_file configure metrics...");
+
+    let cfg = MetricsConfig::from_file(path)?;
+
+    install_with_config(&cfg)
+}
+
+/// Initialize and install the metrics provider, reporting a `build_info` gauge.
+///
+/// This function behaves like [`install`] with an explicit configuration, and additionally
+/// registers an observable gauge named `build_info` that always reports `1`. Its attributes
+/// identify the running build, so dashboards can join other metrics on the deployed version:
+///
+/// * `version` - The given version
+/// * `commit` - The given commit
+/// * `rustc` - The output of `rustc -V` for the compiler that built this crate, or `unknown`
+///   when the build script could not run it
+///
+/// # Parameters
+///
+/// * `cfg` - The metrics configuration
+/// * `version` - The version of the application, typically `env!("CARGO_PKG_VERSION")`
+/// * `commit` - The commit the application was built from, typically passed as a build-time
+///   environment variable
+///
+/// # Returns
+///
+/// * `Ok(SdkMeterProvider)` - The configured meter provider
+/// * `Err(MetricsError)` - If an error occurred during metrics initialization
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use metrics::{config::MetricsConfig, provider};
+///
+/// let provider = provider::install_with_build_info(
+///     &MetricsConfig::load(),
+///     env!("CARGO_PKG_VERSION"),
+///     option_env!("GIT_COMMIT").unwrap_or("unknown"),
+/// )
+/// .unwrap();
+/// ```
+pub fn install_with_build_info(
+    cfg: &MetricsConfig,
+    version: &str,
+    commit: &str,
+) -> Result<SdkMeterProvider, MetricsError> {
+    debug!(target: LOG_TARGET, "metrics::install_with_build_info configure metrics...");
+
+    let meter = install_with_config(cfg)?;
+    builtin::register_build_info(&meter, version, commit);
+
+    Ok(meter)
+}
+
+/// Initialize and install an OTLP provider tuned for the Datadog agent.
+///
+/// The Datadog agent ingests OTLP, but expects its own conventions. This function installs the
+/// OTLP exporter from the given configuration with the following adjustments:
+///
+/// * The exporter uses cumulative temporality, the temporality the agent converts most reliably
+/// * The environment is reported as `deployment.environment`, which the agent maps to its `env`
+///   tag. `service.name` is already mapped to `service` by the agent
+/// * The `DD_VERSION` environment variable, when set, is reported as `service.version`, which
+///   the agent maps to its `version` tag
+/// * When the `DD_AGENT_HOST` environment variable is set, metrics are sent to the OTLP gRPC
+///   port of that host, `4317`
+///
+/// # Parameters
+///
+/// * `cfg` - The metrics configuration
+///
+/// # Returns
+///
+/// * `Ok(SdkMeterProvider)` - The configured meter provider
+/// * `Err(MetricsError)` - If an error occurred during metrics initialization
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use metrics::{config::MetricsConfig, provider};
+///
+/// let provider = provider::install_datadog(&MetricsConfig::load()).unwrap();
+/// ```
+#[cfg(feature = "otlp")]
+pub fn install_datadog(cfg: &MetricsConfig) -> Result<SdkMeterProvider, MetricsError> {
+    debug!(target: LOG_TARGET, "metrics::install_datadog configure metrics...");
+
+    let mut cfg = cfg.clone();
+    cfg.exporter = Some(ExporterKind::Otlp);
+    cfg.environment_key = "deployment.environment".to_string();
+    cfg.detectors.push(Detector::new(DatadogVersionDetector));
+
+    if let Ok(host) = env::var("DD_AGENT_HOST") {
+        cfg.endpoint = format!("http://{host}:{DATADOG_OTLP_PORT}");
+    }
+
+    install_with_config(&cfg)
+}
+
+/// Resolves the settings the install functions would apply for the given configuration.
+///
+/// The resolution is the one performed at install, including the feature-selected default
+/// exporter and the resource detectors, but nothing is installed and no exporter is created.
+/// This lets operators confirm what is actually in effect.
+///
+/// # Parameters
+///
+/// * `cfg` - The metrics configuration
+///
+/// # Returns
+///
+/// The resolved [`EffectiveConfig`]
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use metrics::{config::MetricsConfig, provider};
+///
+/// let effective = provider::effective_config(&MetricsConfig::load());
+/// println!("{}", serde_yaml::to_string(&effective).unwrap());
+/// ```
+pub fn effective_config(cfg: &MetricsConfig) -> EffectiveConfig {
+    let exporter = cfg.exporter.unwrap_or(features::DEFAULT_EXPORTER);
+
+    let endpoints = match exporter {
+        ExporterKind::Otlp => iter::once(&cfg.endpoint)
+            .chain(&cfg.secondary_endpoints)
+            .map(|endpoint| EffectiveEndpoint {
+                address: endpoint.clone(),
+                tls: cfg.uses_tls(endpoint),
+            })
+            .collect(),
+        ExporterKind::Statsd => vec![EffectiveEndpoint {
+            address: cfg.statsd_address.clone(),
+            tls: false,
+        }],
+        _ => Vec::new(),
+    };
+
+    let temporality = match exporter {
+        ExporterKind::Statsd => Some("delta"),
+        ExporterKind::None => None,
+        _ => Some("cumulative"),
+    };
+
+    let compression = match exporter {
+        ExporterKind::Otlp => Some("gzip".to_string()),
+        _ => None,
+    };
+
+    EffectiveConfig {
+        exporter,
+        endpoints,
+        interval: cfg.exporter_interval,
+        timeout: cfg.exporter_timeout,
+        temporality: temporality.map(str::to_string),
+        compression,
+        resource: exporters::resource::build(cfg)
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+    }
+}
+
+/// Waits for a shutdown signal, then flushes and shuts down the provider.
+///
+/// This packages the graceful shutdown of a service: once `signal` completes, the pending
+/// metrics are flushed and the provider is shut down on a blocking thread, bounded by a
+/// timeout of 10 seconds so a collector that stopped answering cannot hold the process.
+///
+/// # Parameters
+///
+/// * `provider` - The installed meter provider
+/// * `signal` - A future completing when the service must stop, such as
+///   `tokio::signal::ctrl_c()`
+///
+/// # Returns
+///
+/// * `Ok(())` - The final flush succeeded
+/// * `Err(OTelSdkError::Timeout)` - The flush and shutdown did not complete in time
+/// * `Err(OTelSdkError)` - The final flush failed
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use metrics::provider;
+/// use tokio::{runtime::Builder, sync::oneshot};
+///
+/// let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+/// runtime.block_on(async {
+///     let provider = provider::install().unwrap();
+///     let (stop, stopped) = oneshot::channel::<()>();
+///
+///     // ... start the service, which sends on `stop` when it must stop ...
+///     # let _ = stop.send(());
+///
+///     provider::run_until_shutdown(provider, stopped)
+///         .await
+///         .unwrap();
+/// });
+/// ```
+pub async fn run_until_shutdown<F>(provider: SdkMeterProvider, signal: F) -> OTelSdkResult
+where
+    F: Future,
+{
+    let _ = signal.await;
+
+    debug!(target: LOG_TARGET, "metrics::shutdown signal received, flushing metrics");
+
+    let flush = task::spawn_blocking(move || {
+        let flushed = provider.force_flush();
+        let _ = provider.shutdown();
+        flushed
+    });
+
+    match time::timeout(SHUTDOWN_TIMEOUT, flush).await {
+        Ok(Ok(flushed)) => flushed,
+        Ok(Err(err)) => {
+            error!(target: LOG_TARGET, error = %err, "failure to flush metrics on shutdown");
+            Err(OTelSdkError::InternalFailure(err.to_string()))
+        }
+        Err(_) => {
+            error!(
+                target: LOG_TARGET,
+                timeout = ?SHUTDOWN_TIMEOUT,
+                "metrics flush on shutdown timed out"
+            );
+            Err(OTelSdkError::Timeout(SHUTDOWN_TIMEOUT))
+        }
+    }
+}
+
+/// Flushes the provider when the process panics, before the previous panic hook runs.
+///
+/// For crash diagnostics, the metric state at the time of the panic is exported by the
+/// configured exporters, so with the stdout exporter, or `mirror_stdout`, it lands in the crash
+/// logs next to the panic message. The hook installed before, the default one printing the
+/// message unless replaced, is chained and still runs afterwards.
+///
+/// Every panic flushes, including panics caught with `catch_unwind` and panics of threads the
+/// process survives. The flush blocks the panicking thread, bounded by the export timeout.
+///
+/// # Parameters
+///
+/// * `provider` - The installed meter provider
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use metrics::provider;
+///
+/// let provider = provider::install().unwrap();
+/// provider::install_panic_flush(&provider);
+/// ```
+pub fn install_panic_flush(provider: &SdkMeterProvider) {
+    let provider = provider.clone();
+    let previous = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        match provider.force_flush() {
+            Ok(()) => debug!(target: LOG_TARGET, "metrics flushed on panic"),
+            Err(err) => {
+                error!(target: LOG_TARGET, error = %err, "failure to flush metrics on panic")
+            }
+        }
+
+        previous(info);
+    }));
+
+    debug!(target: LOG_TARGET, "metrics panic flush installed");
+}
+
+/// # FlushOnDrop
+///
+/// Wraps a meter provider and flushes it when dropped.
+///
+/// Short-lived jobs and tests easily forget to flush before exiting, losing the metrics
+/// recorded since the last periodic export. Holding the provider in a `FlushOnDrop` flushes
+/// them when the scope ends, including on early returns. The guard dereferences to the
+/// provider, so meters can be created from it directly.
+///
+/// Dropping the guard blocks the current thread until the exporters complete the flush, which
+/// is bounded by the export timeout.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use metrics::provider::{self, FlushOnDrop};
+/// use opentelemetry::metrics::MeterProvider;
+///
+/// let provider = FlushOnDrop::new(provider::install().unwrap());
+/// provider.meter("job").u64_counter("job.runs").build().add(1, &[]);
+/// // flushed here
+/// ```
+#[derive(Debug)]
+pub struct FlushOnDrop {
+    provider: SdkMeterProvider,
+}
+
+impl FlushOnDrop {
+    /// Creates a guard flushing `provider` when dropped.
+    pub fn new(provider: SdkMeterProvider) -> Self {
+        Self { provider }
+    }
+}
+
+impl Deref for FlushOnDrop {
+    type Target = SdkMeterProvider;
+
+    fn deref(&self) -> &Self::Target {
+        &self.provider
+    }
+}
+
+impl Drop for FlushOnDrop {
+    fn drop(&mut self) {
+        if let Err(err) = self.provider.force_flush() {
+            warn!(target: LOG_TARGET, error = %err, "failure to flush metrics on drop");
+        }
+    }
+}
+
+/// Initialize and install the metrics provider from an explicit configuration.
+///
+/// This function behaves like [`install`], but takes a fully constructed [`MetricsConfig`]
+/// instead of reading the ambient Ruskit configuration, which decouples the installation from
+/// the global configuration loader for dependency injection and tests.
+///
+/// The requested exporter is checked against the compiled-in features, or the `fallback` chain
+/// is tried when configured. An empty service name is reported as `unknown_service`, with a
+/// warning, unless `OTEL_SERVICE_NAME` is set. The baggage allowlist used by
+/// [`with_baggage`](crate::baggage::with_baggage) and the policy for non-finite values are set,
+/// the `process_uptime_seconds` gauge is registered when enabled, and metrics buffered by
+/// [`defer_until_installed`](crate::defer_until_installed) are replayed onto the new provider.
+///
+/// # Parameters
+///
+/// * `cfg` - The metrics configuration
+///
+/// # Returns
+///
+/// * `Ok(SdkMeterProvider)` - The configured meter provider
+/// * `Err(MetricsError::InvalidFeaturesError)` - If the configuration requests an exporter whose
+///   feature was not enabled at compile time
+/// * `Err(MetricsError)` - If an error occurred during metrics initialization
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use metrics::{
+///     config::{ExporterKind, MetricsConfig},
+///     provider,
+/// };
+/// use std::time::Duration;
+///
+/// let cfg = MetricsConfig {
+///     exporter: Some(ExporterKind::Otlp),
+///     service_name: "my-service".to_string(),
+///     endpoint: "http://collector:4317".to_string(),
+///     exporter_interval: Duration::from_secs(10),
+///     exporter_timeout: Duration::from_secs(5),
+///     ..MetricsConfig::default()
+/// };
+///
+/// let provider = provider::install_with_config(&cfg).unwrap();
+/// ```
+pub fn install_with_config(cfg: &MetricsConfig) -> Result<SdkMeterProvider, MetricsError> {
+    let meter = if cfg.fallback.is_empty() {
+        check_features(cfg)?;
+        install_exporter(cfg)?
+    } else {
+        install_fallback(cfg)?
+    };
+    baggage::configure(&cfg.baggage_keys);
+    non_finite::configure(cfg.non_finite);
+
+    if cfg.service_name.is_empty() {
+        warn!(
+            target: LOG_TARGET,
+            "metrics service name is empty, falling back to OTEL_SERVICE_NAME or unknown_service"
+        );
+    }
+
+    if cfg.with_uptime {
+        builtin::register_uptime(&meter);
+    }
+
+    Ok(meter)
+}
+
+/// Initialize and install the metrics provider, along with a guard shutting it down.
+///
+/// Behaves like [`install_with_config`], and also returns a [`ShutdownGuard`]. Holding the
+/// guard in `main` flushes and shuts down the provider when `main` returns, however the
+/// threads using the provider are organized.
+///
+/// # Parameters
+///
+/// * `cfg` - The metrics configuration
+///
+/// # Returns
+///
+/// * `Ok((SdkMeterProvider, ShutdownGuard))` - The configured meter provider and its guard
+/// * `Err(MetricsError)` - If an error occurred during metrics initialization
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use metrics::{config::MetricsConfig, provider};
+///
+/// let (provider, _guard) = provider::install_guarded(&MetricsConfig::load()).unwrap();
+///
+/// // ... run the service ...
+///
+/// // flushed and shut down here
+/// ```
+pub fn install_guarded(
+    cfg: &MetricsConfig,
+) -> Result<(SdkMeterProvider, ShutdownGuard), MetricsError> {
+    let provider = install_with_config(cfg)?;
+    let guard = ShutdownGuard {
+        provider: provider.clone(),
+    };
+
+    Ok((provider, guard))
+}
+
+/// # ShutdownGuard
+///
+/// Flushes and shuts down a meter provider when dropped.
+///
+/// Unlike [`FlushOnDrop`], the provider is shut down after the flush, so the exporters release
+/// their connections and no export runs afterwards. Dropping the guard blocks the current
+/// thread for at most 10 seconds, so a collector that stopped answering cannot hold the
+/// process. Returned by [`install_guarded`].
+#[derive(Debug)]
+pub struct ShutdownGuard {
+    provider: SdkMeterProvider,
+}
+
+impl Drop for ShutdownGuard {
+    fn drop(&mut self) {
+        let provider = self.provider.clone();
+        let (done, finished) = mpsc::channel();
+
+        let spawned = thread::Builder::new()
+            .name("metrics-shutdown".to_string())
+            .spawn(move || {
+                let flushed = provider.force_flush();
+                let _ = provider.shutdown();
+                let _ = done.send(flushed);
+            });
+        if let Err(err) = spawned {
+            error!(target: LOG_TARGET, error = %err, "failure to start the metrics shutdown");
+            return;
+        }
+
+        match finished.recv_timeout(SHUTDOWN_TIMEOUT) {
+            Ok(Ok(())) => debug!(target: LOG_TARGET, "metrics flushed and shut down"),
+            Ok(Err(err)) => {
+                error!(target: LOG_TARGET, error = %err, "failure to flush metrics on shutdown")
+            }
+            Err(_) => error!(
+                target: LOG_TARGET,
+                timeout = ?SHUTDOWN_TIMEOUT,
+                "metrics flush on shutdown timed out"
+            ),
+        }
+    }
+}
+
+/// Reports the `DD_VERSION` environment variable as the `service.version` resource attribute.
+#[cfg(feature = "otlp")]
+#[derive(Debug)]
+struct DatadogVersionDetector;
+
+#[cfg(feature = "otlp")]
+impl ResourceDetector for DatadogVersionDetector {
+    fn detect(&self) -> Resource {
+        let mut builder = Resource::builder_empty();
+
+        if let Ok(version) = env::var("DD_VERSION") {
+            builder = builder.with_attribute(KeyValue::new("service.version", version));
+        }
+
+        builder.build()
+    }
+}
+
+/// Fails when the exporter requested by the configuration was not compiled in.
+///
+/// Without this check, a binary built without the requested exporter would silently fall back
+/// to another exporter, or to the no-op provider.
+fn check_features(cfg: &MetricsConfig) -> Result<(), MetricsError> {
+    let Some(kind) = cfg.exporter else {
+        return Ok(());
+    };
+
+    if kind.is_compiled() {
+        return Ok(());
+    }
+
+    error!(
+        target: LOG_TARGET,
+        exporter = ?kind,
+        feature = kind.feature().unwrap_or_default(),
+        "requested metric exporter requires a feature that was not enabled at compile time"
+    );
+    Err(MetricsError::InvalidFeaturesError)
+}
+
+/// Installs the first exporter of the `fallback` chain that is compiled in and installs
+/// successfully.
+///
+/// The OTLP exporter connects lazily, so within the chain it is installed with
+/// `require_connection` set: an unreachable collector moves on to the next exporter.
+fn install_fallback(cfg: &MetricsConfig) -> Result<SdkMeterProvider, MetricsError> {
+    for kind in &cfg.fallback {
+        if !kind.is_compiled() {
+            warn!(
+                target: LOG_TARGET,
+                exporter = ?kind,
+                feature = kind.feature().unwrap_or_default(),
+                "fallback exporter not compiled in, trying the next one"
+            );
+            continue;
+        }
+
+        let mut attempt = cfg.clone();
+        attempt.exporter = Some(*kind);
+        attempt.require_connection = true;
+
+        match install_exporter(&attempt) {
+            Ok(provider) => {
+                debug!(target: LOG_TARGET, exporter = ?kind, "fallback exporter selected");
+                return Ok(provider);
+            }
+            Err(err) => warn!(
+                target: LOG_TARGET,
+                exporter = ?kind,
+                error = %err,
+                "fallback exporter failed to install, trying the next one"
+            ),
+        }
+    }
+
+    error!(
+        target: LOG_TARGET,
+        fallback = ?cfg.fallback,
+        "no exporter of the fallback chain could be installed"
+    );
+    Err(MetricsError::ExporterProviderError)
+}
+
+/// Installs the exporter requested by the configuration, or the feature-selected one.
+fn install_exporter(cfg: &MetricsConfig) -> Result<SdkMeterProvider, MetricsError> {
+    match cfg.exporter.unwrap_or(features::DEFAULT_EXPORTER) {
+        #[cfg(feature = "otlp")]
+        ExporterKind::Otlp => exporters::otlp_grpc::install_with_config(cfg),
+        #[cfg(feature = "prometheus")]
+        ExporterKind::Prometheus => exporters::prom::install_with_config(cfg),
+        #[cfg(feature = "stdout")]
+        ExporterKind::Stdout => exporters::stdout::install_with_config(cfg),
+        #[cfg(feature = "statsd")]
+        ExporterKind::Statsd => exporters::statsd::install_with_config(cfg),
+        ExporterKind::CustomPush => {
+            error!(
+                target: LOG_TARGET,
+                "the custom push exporter is installed with `custom_push::install`, not from the configuration"
+            );
+            Err(MetricsError::InvalidConfigError(
+                "the custom push exporter can not be requested from the configuration".to_string(),
+            ))
+        }
+        ExporterKind::None => exporters::noop::install_with_config(cfg),
+        // exporters that were not compiled in are rejected by `check_features`, the arm is
+        // unreachable when every exporter feature is enabled
+        #[allow(unreachable_patterns)]
+        _ => Err(MetricsError::InvalidFeaturesError),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, TempFile};
+
+    #[test]
+    fn install_from_file_installs_the_configured_exporter() {
+        let _lock = test_util::global_lock();
+        let file = TempFile::new("metrics.toml", "exporter = \"none\"\n");
+
+        let provider = install_from_file(file.path()).unwrap();
+
+        assert!(provider.shutdown().is_ok());
+    }
+
+    #[cfg(not(feature = "prometheus"))]
+    #[test]
+    fn install_from_file_applies_the_file() {
+        let _lock = test_util::global_lock();
+        let file = TempFile::new("metrics.yaml", "exporter: prometheus\n");
+
+        assert_eq!(
+            install_from_file(file.path()).unwrap_err(),
+            MetricsError::InvalidFeaturesError
+        );
+    }
+
+    #[test]
+    fn install_from_file_rejects_an_unreadable_file() {
+        let _lock = test_util::global_lock();
+        let file = TempFile::new("metrics.toml", "exporter = 42\n");
+
+        assert!(matches!(
+            install_from_file(file.path()),
+            Err(MetricsError::InvalidConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn install_rejects_an_exporter_that_was_not_compiled_in() {
+        let _lock = test_util::global_lock();
+        let missing = [
+            ExporterKind::Otlp,
+            ExporterKind::Prometheus,
+            ExporterKind::Statsd,
+            ExporterKind::Stdout,
+        ]
+        .into_iter()
+        .find(|kind| !kind.is_compiled());
+        // every exporter is compiled in with all features enabled
+        let Some(kind) = missing else {
+            return;
+        };
+
+        let cfg = MetricsConfig {
+            exporter: Some(kind),
+            ..MetricsConfig::default()
+        };
+
+        assert_eq!(
+            install_with_config(&cfg).unwrap_err(),
+            MetricsError::InvalidFeaturesError
+        );
+    }
+
+    #[test]
+    fn install_rejects_the_custom_push_exporter() {
+        let _lock = test_util::global_lock();
+        let cfg = MetricsConfig {
+            exporter: Some(ExporterKind::CustomPush),
+            ..MetricsConfig::default()
+        };
+
+        let err = install_with_config(&cfg).unwrap_err();
+        if ExporterKind::CustomPush.is_compiled() {
+            assert!(matches!(err, MetricsError::InvalidConfigError(_)));
+        } else {
+            assert_eq!(err, MetricsError::InvalidFeaturesError);
+        }
+    }
+
+    #[test]
+    fn install_uses_the_exporter_selected_at_runtime() {
+        let _lock = test_util::global_lock();
+        let cfg = MetricsConfig {
+            exporter: Some(ExporterKind::None),
+            ..MetricsConfig::default()
+        };
+
+        // the no-op provider has no reader, so flushing has nothing to do
+        let provider = install_with_config(&cfg).unwrap();
+
+        assert!(provider.force_flush().is_ok());
+    }
+
+    #[test]
+    fn run_until_shutdown_flushes_and_shuts_down_on_signal() {
+        let (provider, _reader) = test_util::provider(&MetricsConfig::default());
+        let handle = provider.clone();
+        let (stop, signal) = tokio::sync::oneshot::channel::<()>();
+
+        let result = test_util::block_on(async move {
+            let shutdown = tokio::spawn(run_until_shutdown(provider, signal));
+            task::yield_now().await;
+            assert!(!shutdown.is_finished());
+
+            stop.send(()).unwrap();
+            shutdown.await.unwrap()
+        });
+
+        assert!(result.is_ok());
+        assert!(matches!(
+            handle.shutdown(),
+            Err(OTelSdkError::AlreadyShutdown)
+        ));
+    }
+
+    #[cfg(feature = "otlp")]
+    #[test]
+    fn datadog_version_is_reported_as_service_version() {
+        let _lock = test_util::global_lock();
+        // SAFETY: the tests touching the environment hold the global lock
+        unsafe { env::set_var("DD_VERSION", "1.2.3") };
+
+        let resource = DatadogVersionDetector.detect();
+
+        unsafe { env::remove_var("DD_VERSION") };
+        assert_eq!(
+            resource.get(&opentelemetry::Key::from_static_str("service.version")),
+            Some("1.2.3".into())
+        );
+        assert_eq!(DatadogVersionDetector.detect().len(), 0);
+    }
+
+    #[test]
+    fn flush_on_drop_exports_the_pending_metrics() {
+        let exporter = test_util::MockExporter::default();
+        let cfg = MetricsConfig {
+            exporter_interval: Duration::from_secs(3600),
+            ..MetricsConfig::default()
+        };
+        let reader = crate::exporters::reader::periodic(exporter.clone(), &cfg);
+        let provider = FlushOnDrop::new(SdkMeterProvider::builder().with_reader(reader).build());
+        provider
+            .meter("test")
+            .u64_counter("requests")
+            .build()
+            .add(1, &[]);
+
+        drop(provider);
+
+        assert_eq!(exporter.names().last(), Some(&vec!["requests".to_string()]));
+    }
+
+    #[test]
+    fn init_installs_the_requested_exporter_and_returns_a_meter() {
+        let _lock = test_util::global_lock();
+        // SAFETY: the tests touching the environment hold the global lock
+        unsafe { std::env::set_var("METRIC_EXPORTER", "none") };
+
+        let meter = init("component");
+
+        unsafe { std::env::remove_var("METRIC_EXPORTER") };
+        let counter = meter.unwrap().u64_counter("requests").build();
+        counter.add(1, &[]);
+    }
+
+    #[test]
+    fn effective_config_resolves_the_otlp_settings() {
+        let cfg = MetricsConfig {
+            exporter: Some(ExporterKind::Otlp),
+            service_name: "checkout".to_string(),
+            endpoint: "https://collector-a:4317".to_string(),
+            secondary_endpoints: vec!["http://collector-b:4317".to_string()],
+            ..MetricsConfig::default()
+        };
+
+        let effective = effective_config(&cfg);
+
+        assert_eq!(
+            effective.endpoints,
+            vec![
+                EffectiveEndpoint {
+                    address: "https://collector-a:4317".to_string(),
+                    tls: true,
+                },
+                EffectiveEndpoint {
+                    address: "http://collector-b:4317".to_string(),
+                    tls: false,
+                },
+            ]
+        );
+        assert_eq!(effective.temporality.as_deref(), Some("cumulative"));
+        assert_eq!(effective.compression.as_deref(), Some("gzip"));
+        assert_eq!(
+            effective.resource.get("service.name").map(String::as_str),
+            Some("checkout")
+        );
+    }
+
+    #[test]
+    fn effective_config_reports_delta_for_statsd() {
+        let cfg = MetricsConfig {
+            exporter: Some(ExporterKind::Statsd),
+            ..MetricsConfig::default()
+        };
+
+        let effective = effective_config(&cfg);
+
+        assert_eq!(effective.temporality.as_deref(), Some("delta"));
+        assert_eq!(effective.endpoints[0].address, cfg.statsd_address);
+        assert_eq!(effective.compression, None);
+    }
+
+    /// Returns an endpoint on a port nothing listens on.
+    fn closed_endpoint() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+
+    #[test]
+    fn fallback_moves_on_to_the_next_exporter() {
+        let _lock = test_util::global_lock();
+        let cfg = MetricsConfig {
+            endpoint: closed_endpoint(),
+            exporter_timeout: Duration::from_secs(1),
+            fallback: vec![ExporterKind::Otlp, ExporterKind::None],
+            ..MetricsConfig::default()
+        };
+
+        let logs = test_util::capture_logs(|| {
+            let provider = test_util::block_on(async { install_with_config(&cfg) }).unwrap();
+            assert!(provider.force_flush().is_ok());
+        });
+
+        assert!(
+            logs.iter()
+                .any(|log| log.message.contains("trying the next one"))
+        );
+    }
 
     #[cfg(feature = "otlp")]
-    {
-        let meter = exporters::otlp_grpc::install()?;
-        Ok(meter)
+    #[test]
+    fn fallback_fails_when_no_exporter_installs() {
+        let _lock = test_util::global_lock();
+        let cfg = MetricsConfig {
+            endpoint: closed_endpoint(),
+            exporter_timeout: Duration::from_secs(1),
+            fallback: vec![ExporterKind::Otlp],
+            ..MetricsConfig::default()
+        };
+
+        let result = test_util::block_on(async { install_with_config(&cfg) });
+
+        assert_eq!(result.unwrap_err(), MetricsError::ExporterProviderError);
+    }
+
+    #[cfg(feature = "statsd")]
+    #[test]
+    fn install_with_config_uses_the_injected_configuration() {
+        let _lock = test_util::global_lock();
+        let server = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let cfg = MetricsConfig {
+            exporter: Some(ExporterKind::Statsd),
+            statsd_address: server.local_addr().unwrap().to_string(),
+            exporter_interval: Duration::from_secs(3600),
+            ..MetricsConfig::default()
+        };
+
+        let provider = install_with_config(&cfg).unwrap();
+        provider
+            .meter("test")
+            .u64_counter("requests")
+            .build()
+            .add(1, &[]);
+        provider.force_flush().unwrap();
+
+        let mut buf = [0; 1024];
+        let len = server.recv(&mut buf).unwrap();
+        assert_eq!(String::from_utf8_lossy(&buf[..len]), "requests:1|c");
+        let _ = provider.shutdown();
+    }
+
+    #[test]
+    fn install_warns_about_an_empty_service_name() {
+        let _lock = test_util::global_lock();
+        let cfg = MetricsConfig {
+            exporter: Some(ExporterKind::None),
+            ..MetricsConfig::default()
+        };
+
+        let logs = test_util::capture_logs(|| {
+            install_with_config(&cfg).unwrap();
+        });
+
+        assert!(
+            logs.iter()
+                .any(|log| log.message.contains("service name is empty"))
+        );
     }
 
-    #[cfg(feature = "stdout")]
-    {
-        let meter = exporters::stdout::install()?;
-        Ok(meter)
+    #[test]
+    fn panic_flush_exports_before_the_previous_hook() {
+        let _lock = test_util::global_lock();
+        let exporter = test_util::MockExporter::default();
+        let cfg = MetricsConfig {
+            exporter_interval: Duration::from_secs(3600),
+            ..MetricsConfig::default()
+        };
+        let reader = crate::exporters::reader::periodic(exporter.clone(), &cfg);
+        let provider = SdkMeterProvider::builder().with_reader(reader).build();
+        provider
+            .meter("test")
+            .u64_counter("requests")
+            .build()
+            .add(1, &[]);
+
+        install_panic_flush(&provider);
+        let panicked = panic::catch_unwind(|| panic!("stage failed"));
+        // restores the default hook for the other tests
+        let _ = panic::take_hook();
+
+        assert!(panicked.is_err());
+        assert_eq!(exporter.names().last(), Some(&vec!["requests".to_string()]));
+        let _ = provider.shutdown();
     }
 
-    #[cfg(not(any(feature = "stdout", feature = "otlp")))]
-    return exporters::noop::install();
+    #[test]
+    fn shutdown_guard_shuts_the_provider_down_when_dropped() {
+        let _lock = test_util::global_lock();
+        let cfg = MetricsConfig {
+            exporter: Some(ExporterKind::None),
+            ..MetricsConfig::default()
+        };
+
+        let (provider, guard) = install_guarded(&cfg).unwrap();
+        drop(guard);
+
+        assert!(matches!(
+            provider.shutdown(),
+            Err(OTelSdkError::AlreadyShutdown)
+        ));
+    }
+
+    #[cfg(feature = "statsd")]
+    #[test]
+    fn shutdown_guard_flushes_the_pending_metrics() {
+        let _lock = test_util::global_lock();
+        let server = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let cfg = MetricsConfig {
+            exporter: Some(ExporterKind::Statsd),
+            statsd_address: server.local_addr().unwrap().to_string(),
+            exporter_interval: Duration::from_secs(3600),
+            ..MetricsConfig::default()
+        };
+
+        let (provider, guard) = install_guarded(&cfg).unwrap();
+        provider
+            .meter("test")
+            .u64_counter("jobs")
+            .build()
+            .add(4, &[]);
+        drop(guard);
+
+        let mut buf = [0; 1024];
+        let len = server.recv(&mut buf).unwrap();
+        assert_eq!(String::from_utf8_lossy(&buf[..len]), "jobs:4|c");
+    }
 }
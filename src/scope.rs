@@ -0,0 +1,81 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Instrumentation Scopes
+//!
+//! Provides helpers creating meters with a fully described instrumentation scope.
+//!
+//! Every meter belongs to an instrumentation scope, exported alongside its metrics. Besides a
+//! name and a version, the scope can carry attributes, for example `scope.team`, which lets
+//! backends attribute the metrics of a multi-team repository to their owners.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use metrics::meter_with_attrs;
+//! use opentelemetry::KeyValue;
+//!
+//! let meter = meter_with_attrs(
+//!     "payments",
+//!     env!("CARGO_PKG_VERSION"),
+//!     [KeyValue::new("scope.team", "checkout")],
+//! );
+//! let counter = meter.u64_counter("payments.processed").build();
+//! ```
+
+use opentelemetry::{InstrumentationScope, KeyValue, global, metrics::Meter};
+use std::borrow::Cow;
+
+/// Creates a meter of the global provider whose scope carries the given version and attributes.
+///
+/// # Parameters
+///
+/// * `name` - The name of the instrumentation scope
+/// * `version` - The version of the instrumentation scope
+/// * `attrs` - The attributes of the instrumentation scope
+///
+/// # Returns
+///
+/// A meter whose instruments export under the described scope
+pub fn meter_with_attrs(
+    name: impl Into<Cow<'static, str>>,
+    version: impl Into<Cow<'static, str>>,
+    attrs: impl IntoIterator<Item = KeyValue>,
+) -> Meter {
+    let scope = InstrumentationScope::builder(name)
+        .with_version(version)
+        .with_attributes(attrs)
+        .build();
+
+    global::meter_with_scope(scope)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::MetricsConfig, test_util};
+
+    #[test]
+    fn meter_scope_carries_version_and_attributes() {
+        let _lock = test_util::global_lock();
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        global::set_meter_provider(provider);
+
+        let meter = meter_with_attrs(
+            "payments",
+            "1.2.0",
+            [KeyValue::new("scope.team", "checkout")],
+        );
+        meter.u64_counter("payments.processed").build().add(1, &[]);
+
+        let rm = test_util::collect(&reader);
+        let scope = &rm.scope_metrics[0].scope;
+        assert_eq!(scope.name(), "payments");
+        assert_eq!(scope.version(), Some("1.2.0"));
+        assert_eq!(
+            scope.attributes().cloned().collect::<Vec<_>>(),
+            vec![KeyValue::new("scope.team", "checkout")]
+        );
+    }
+}
@@ -0,0 +1,403 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Test Utilities
+//!
+//! Helpers shared by the unit tests of the crate.
+//!
+//! The tests build providers from a [`MetricsConfig`] with a [`SharedReader`] registered on
+//! them, so they can collect on demand and inspect the data points the exporters would see.
+//! Tests touching process wide state, such as the global meter provider, hold
+//! [`global_lock`] so they do not interleave.
+
+// some helpers are only used by the tests of feature gated modules
+#![allow(dead_code)]
+
+use crate::{
+    config::MetricsConfig,
+    exporters::{self, reader::SharedReader},
+};
+#[cfg(feature = "otlp")]
+use opentelemetry_proto::tonic::collector::metrics::v1::{
+    ExportMetricsServiceRequest, ExportMetricsServiceResponse,
+    metrics_service_server::{MetricsService, MetricsServiceServer},
+};
+use opentelemetry_sdk::{
+    Resource,
+    error::{OTelSdkError, OTelSdkResult},
+    metrics::{
+        SdkMeterProvider, Temporality,
+        data::{Metric, ResourceMetrics},
+        exporter::PushMetricExporter,
+        reader::MetricReader,
+    },
+};
+use std::{
+    env, fmt, fs,
+    future::Future,
+    path::PathBuf,
+    process,
+    sync::{
+        Arc, Mutex, MutexGuard,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+};
+#[cfg(feature = "otlp")]
+use tonic::{
+    codec::CompressionEncoding,
+    transport::{Server, server::TcpIncoming},
+};
+use tracing::{
+    Event, Metadata, Subscriber,
+    field::Field,
+    span::{Attributes, Id, Record},
+};
+
+static GLOBAL: Mutex<()> = Mutex::new(());
+
+static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Builds a provider from the configuration with a manual reader registered on it.
+pub(crate) fn provider(cfg: &MetricsConfig) -> (SdkMeterProvider, SharedReader) {
+    let reader = SharedReader::new();
+    let provider = exporters::provider_builder(cfg)
+        .with_reader(reader.clone())
+        .build();
+    (provider, reader)
+}
+
+/// Collects the metrics currently held by the reader.
+pub(crate) fn collect(reader: &SharedReader) -> ResourceMetrics {
+    let mut rm = empty_metrics();
+    reader.collect(&mut rm).expect("collect");
+    rm
+}
+
+/// Returns the metric with the given name, across every scope.
+pub(crate) fn metric<'a>(rm: &'a ResourceMetrics, name: &str) -> Option<&'a Metric> {
+    rm.scope_metrics
+        .iter()
+        .flat_map(|scope| scope.metrics.iter())
+        .find(|metric| metric.name == name)
+}
+
+/// Runs a future to completion on a single threaded runtime.
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("runtime")
+        .block_on(future)
+}
+
+/// Serializes the tests touching the global meter provider and the other process wide state.
+pub(crate) fn global_lock() -> MutexGuard<'static, ()> {
+    GLOBAL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// # TempFile
+///
+/// A file in the temporary directory, removed when dropped.
+pub(crate) struct TempFile(PathBuf);
+
+impl TempFile {
+    /// Writes `content` to a new file whose name ends with `suffix`, such as `metrics.toml`.
+    pub(crate) fn new(suffix: &str, content: &str) -> Self {
+        let id = TEMP_FILES.fetch_add(1, Ordering::Relaxed);
+        let path = env::temp_dir().join(format!("metrics-test-{}-{id}-{suffix}", process::id()));
+        fs::write(&path, content).expect("write temp file");
+        Self(path)
+    }
+
+    pub(crate) fn path(&self) -> &PathBuf {
+        &self.0
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// # MockExporter
+///
+/// An exporter counting its exports and the metrics it receives, optionally failing them, for
+/// the tests of the exporter wrappers.
+///
+/// Clones share their state, so a test keeps one clone while the wrapper owns the other.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MockExporter(Arc<MockState>);
+
+#[derive(Debug, Default)]
+struct MockState {
+    failing: AtomicBool,
+    exports: AtomicUsize,
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+    names: Mutex<Vec<Vec<String>>>,
+}
+
+impl MockExporter {
+    /// Makes the following exports fail, or succeed again.
+    pub(crate) fn set_failing(&self, failing: bool) {
+        self.0.failing.store(failing, Ordering::SeqCst);
+    }
+
+    /// Returns the number of exports that reached the exporter.
+    pub(crate) fn exports(&self) -> usize {
+        self.0.exports.load(Ordering::SeqCst)
+    }
+
+    /// Returns the highest number of exports observed in flight at the same time.
+    pub(crate) fn max_in_flight(&self) -> usize {
+        self.0.max_in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Returns the metric names of every export, in order.
+    pub(crate) fn names(&self) -> Vec<Vec<String>> {
+        self.0
+            .names
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+impl PushMetricExporter for MockExporter {
+    async fn export(&self, metrics: &mut ResourceMetrics) -> OTelSdkResult {
+        let state = &self.0;
+        let in_flight = state.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        state.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+
+        // lets the other exports polled by the test start before this one completes
+        tokio::task::yield_now().await;
+
+        state.in_flight.fetch_sub(1, Ordering::SeqCst);
+        state.exports.fetch_add(1, Ordering::SeqCst);
+        let names = metrics
+            .scope_metrics
+            .iter()
+            .flat_map(|scope| scope.metrics.iter())
+            .map(|metric| metric.name.to_string())
+            .collect();
+        state
+            .names
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(names);
+
+        if state.failing.load(Ordering::SeqCst) {
+            Err(OTelSdkError::InternalFailure("mock failure".to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        Ok(())
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        Ok(())
+    }
+
+    fn temporality(&self) -> Temporality {
+        Temporality::Cumulative
+    }
+}
+
+/// Returns an empty batch, for exporter tests that do not look at the data.
+pub(crate) fn empty_metrics() -> ResourceMetrics {
+    ResourceMetrics {
+        resource: Resource::builder_empty().build(),
+        scope_metrics: Vec::new(),
+    }
+}
+
+/// # LogEvent
+///
+/// A `tracing` event captured by [`capture_logs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LogEvent {
+    pub(crate) target: String,
+    pub(crate) message: String,
+}
+
+/// Runs `f` and returns the `tracing` events it emitted on the current thread.
+pub(crate) fn capture_logs(f: impl FnOnce()) -> Vec<LogEvent> {
+    let capture = LogCapture::default();
+    tracing::subscriber::with_default(capture.clone(), f);
+    capture.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+#[derive(Clone, Default)]
+struct LogCapture(Arc<Mutex<Vec<LogEvent>>>);
+
+impl Subscriber for LogCapture {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _span: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut message = String::new();
+        event.record(&mut |field: &Field, value: &dyn fmt::Debug| {
+            if field.name() == "message" {
+                message = format!("{value:?}");
+            }
+        });
+
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(LogEvent {
+                target: event.metadata().target().to_string(),
+                message,
+            });
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+/// # MockCollector
+///
+/// An OTLP gRPC collector served in process on its own runtime, recording the export requests
+/// it receives with their metadata.
+///
+/// The exporters connect lazily and spawn their channel on the runtime current at install, so
+/// the tests install them within [`MockCollector::enter`]: the runtime of the collector keeps
+/// running until the collector is dropped.
+#[cfg(feature = "otlp")]
+pub(crate) struct MockCollector {
+    endpoint: String,
+    runtime: tokio::runtime::Handle,
+    received: Arc<Mutex<Vec<Received>>>,
+    shutdown: Option<tokio::sync::oneshot::Sender<()>>,
+}
+
+/// An export request received by a [`MockCollector`].
+#[cfg(feature = "otlp")]
+#[derive(Debug, Clone)]
+pub(crate) struct Received {
+    pub(crate) metadata: tonic::metadata::MetadataMap,
+    pub(crate) request: ExportMetricsServiceRequest,
+}
+
+#[cfg(feature = "otlp")]
+impl MockCollector {
+    /// Serves a collector on a free loopback port.
+    pub(crate) fn start() -> Self {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind collector");
+        listener
+            .set_nonblocking(true)
+            .expect("nonblocking listener");
+        let endpoint = format!(
+            "http://{}",
+            listener.local_addr().expect("collector address")
+        );
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let service = MetricsServiceServer::new(Recorder(received.clone()))
+            .accept_compressed(CompressionEncoding::Gzip);
+        let (shutdown, stopped) = tokio::sync::oneshot::channel::<()>();
+        let (runtime_tx, runtime_rx) = std::sync::mpsc::channel();
+
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("runtime");
+            runtime_tx
+                .send(runtime.handle().clone())
+                .expect("runtime handle");
+            runtime.block_on(async move {
+                let listener = tokio::net::TcpListener::from_std(listener).expect("listener");
+                let incoming = TcpIncoming::from_listener(listener, true, None).expect("incoming");
+                let _ = Server::builder()
+                    .add_service(service)
+                    .serve_with_incoming_shutdown(incoming, async {
+                        let _ = stopped.await;
+                    })
+                    .await;
+            });
+        });
+
+        Self {
+            endpoint,
+            runtime: runtime_rx.recv().expect("runtime handle"),
+            received,
+            shutdown: Some(shutdown),
+        }
+    }
+
+    /// The endpoint to configure the exporter with.
+    pub(crate) fn endpoint(&self) -> String {
+        self.endpoint.clone()
+    }
+
+    /// Enters the runtime of the collector, to install the exporters within.
+    pub(crate) fn enter(&self) -> tokio::runtime::EnterGuard<'_> {
+        self.runtime.enter()
+    }
+
+    /// The requests received so far, in order.
+    pub(crate) fn received(&self) -> Vec<Received> {
+        self.received
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// The names of the metrics of every request received so far.
+    pub(crate) fn metric_names(&self) -> Vec<String> {
+        self.received()
+            .iter()
+            .flat_map(|received| &received.request.resource_metrics)
+            .flat_map(|resource| &resource.scope_metrics)
+            .flat_map(|scope| &scope.metrics)
+            .map(|metric| metric.name.clone())
+            .collect()
+    }
+}
+
+#[cfg(feature = "otlp")]
+impl Drop for MockCollector {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+/// The metrics service of a [`MockCollector`], recording every request.
+#[cfg(feature = "otlp")]
+struct Recorder(Arc<Mutex<Vec<Received>>>);
+
+#[cfg(feature = "otlp")]
+#[tonic::async_trait]
+impl MetricsService for Recorder {
+    async fn export(
+        &self,
+        request: tonic::Request<ExportMetricsServiceRequest>,
+    ) -> Result<tonic::Response<ExportMetricsServiceResponse>, tonic::Status> {
+        let metadata = request.metadata().clone();
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Received {
+                metadata,
+                request: request.into_inner(),
+            });
+        Ok(tonic::Response::new(ExportMetricsServiceResponse::default()))
+    }
+}
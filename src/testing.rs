@@ -0,0 +1,428 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Test Support
+//!
+//! Provides a deterministic harness for testing instrumented code.
+//!
+//! This module is conditionally compiled when the "test-support" feature is enabled. The
+//! [`TestHarness`] builds a meter provider exporting to the SDK in-memory exporter, without
+//! network, async runtime or sleeps. Collection is synchronous: every call to
+//! [`TestHarness::collect_and_snapshot`] returns the current value of every series.
+//!
+//! The harness does not replace the global meter provider, so tests using it can run in
+//! parallel. Code under test should receive the harness meter or provider explicitly.
+//!
+//! ## Isolation
+//!
+//! Tests sharing a harness can call [`TestHarness::reset`] between cases. Sums and histograms
+//! are then reported relative to the reset, and series that did not change since are omitted,
+//! so each case starts clean. Gauges keep reporting their last value, since a gauge recorded
+//! again with the same value cannot be told apart from one that was not recorded.
+//!
+//! ## Example
+//!
+//! ```
+//! use metrics::testing::{PointValue, TestHarness};
+//! use opentelemetry::KeyValue;
+//!
+//! let harness = TestHarness::new();
+//! let counter = harness.meter("orders").u64_counter("orders.created").build();
+//!
+//! counter.add(2, &[KeyValue::new("region", "eu")]);
+//!
+//! let points = harness.collect_and_snapshot();
+//! assert_eq!(points.len(), 1);
+//! assert_eq!(points[0].name, "orders.created");
+//! assert_eq!(points[0].value, PointValue::Number(2.0));
+//! ```
+
+use crate::{
+    config::MetricsConfig,
+    exporters::{self, transform::TransformExporter},
+};
+use opentelemetry::{
+    KeyValue,
+    metrics::{Meter, MeterProvider},
+};
+use opentelemetry_sdk::metrics::{
+    InMemoryMetricExporter, PeriodicReader, SdkMeterProvider,
+    data::{Gauge, Histogram, Metric, ResourceMetrics, Sum},
+};
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+/// Interval of the periodic reader, long enough to never trigger during a test.
+const READER_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// # PointValue
+///
+/// The value of a collected data point.
+///
+/// ## Variants
+///
+/// * `Number` - The value of a sum or gauge point, converted to `f64`
+/// * `Histogram` - The count and sum of a histogram point
+#[derive(Debug, Clone, PartialEq)]
+pub enum PointValue {
+    Number(f64),
+    Histogram { count: u64, sum: f64 },
+}
+
+/// # MetricPoint
+///
+/// A single collected data point.
+///
+/// ## Fields
+///
+/// * `scope` - Name of the meter that recorded the point
+/// * `name` - Name of the metric, after views are applied
+/// * `attributes` - Attributes of the series
+/// * `value` - Value of the point
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricPoint {
+    pub scope: String,
+    pub name: String,
+    pub attributes: Vec<KeyValue>,
+    pub value: PointValue,
+}
+
+/// # TestHarness
+///
+/// A meter provider backed by the in-memory exporter, collected on demand.
+#[derive(Debug)]
+pub struct TestHarness {
+    provider: SdkMeterProvider,
+    exporter: InMemoryMetricExporter,
+    baseline: Mutex<HashMap<SeriesKey, PointValue>>,
+}
+
+/// Identifies a series by scope, metric name and attributes.
+type SeriesKey = (String, String, String);
+
+impl Default for TestHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestHarness {
+    /// Creates a harness with the default configuration.
+    pub fn new() -> Self {
+        Self::with_config(&MetricsConfig::default())
+    }
+
+    /// Creates a harness applying the views, transforms and resource of the given
+    /// configuration, so the exported names and attributes match the ones of an installed
+    /// provider.
+    ///
+    /// # Parameters
+    ///
+    /// * `cfg` - The metrics configuration
+    pub fn with_config(cfg: &MetricsConfig) -> Self {
+        let exporter = InMemoryMetricExporter::default();
+        let reader = PeriodicReader::builder(TransformExporter::new(exporter.clone(), cfg))
+            .with_interval(READER_INTERVAL)
+            .build();
+
+        let provider = exporters::provider_builder(cfg).with_reader(reader).build();
+
+        Self {
+            provider,
+            exporter,
+            baseline: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the meter provider of the harness.
+    pub fn provider(&self) -> &SdkMeterProvider {
+        &self.provider
+    }
+
+    /// Returns a meter of the harness provider.
+    pub fn meter(&self, name: &'static str) -> Meter {
+        self.provider.meter(name)
+    }
+
+    /// Collects the recorded metrics and returns every data point.
+    ///
+    /// Collection uses cumulative temporality, so each call returns the totals since the
+    /// harness was created, or since the last call to [`TestHarness::reset`].
+    pub fn collect_and_snapshot(&self) -> Vec<MetricPoint> {
+        let baseline = self.baseline.lock().unwrap_or_else(|e| e.into_inner());
+
+        self.collect()
+            .into_iter()
+            .filter_map(|(point, cumulative)| {
+                if !cumulative {
+                    return Some(point);
+                }
+                match baseline.get(&series_key(&point)) {
+                    Some(base) => relative_to(point, base),
+                    None => Some(point),
+                }
+            })
+            .collect()
+    }
+
+    /// Clears the data recorded so far.
+    ///
+    /// Later snapshots report sums and histograms relative to this call, and omit the series
+    /// that did not change since. Gauges are not affected.
+    pub fn reset(&self) {
+        let baseline = self
+            .collect()
+            .into_iter()
+            .filter(|(_, cumulative)| *cumulative)
+            .map(|(point, _)| (series_key(&point), point.value))
+            .collect();
+
+        *self.baseline.lock().unwrap_or_else(|e| e.into_inner()) = baseline;
+    }
+
+    /// Collects every data point, flagged with whether its value accumulates over time.
+    fn collect(&self) -> Vec<(MetricPoint, bool)> {
+        self.exporter.reset();
+        let _ = self.provider.force_flush();
+
+        let Some(rm) = self
+            .exporter
+            .get_finished_metrics()
+            .ok()
+            .and_then(|mut rms| rms.pop())
+        else {
+            return Vec::new();
+        };
+
+        snapshot(&rm)
+    }
+}
+
+impl Drop for TestHarness {
+    fn drop(&mut self) {
+        let _ = self.provider.shutdown();
+    }
+}
+
+fn snapshot(rm: &ResourceMetrics) -> Vec<(MetricPoint, bool)> {
+    let mut points = Vec::new();
+
+    for scope_metrics in &rm.scope_metrics {
+        let scope = scope_metrics.scope.name();
+        for metric in &scope_metrics.metrics {
+            let cumulative = !is_gauge(metric);
+            for (attributes, value) in metric_points(metric) {
+                let point = MetricPoint {
+                    scope: scope.to_string(),
+                    name: metric.name.to_string(),
+                    attributes,
+                    value,
+                };
+                points.push((point, cumulative));
+            }
+        }
+    }
+
+    points
+}
+
+fn is_gauge(metric: &Metric) -> bool {
+    let data = metric.data.as_any();
+    data.is::<Gauge<u64>>() || data.is::<Gauge<i64>>() || data.is::<Gauge<f64>>()
+}
+
+fn series_key(point: &MetricPoint) -> SeriesKey {
+    (
+        point.scope.clone(),
+        point.name.clone(),
+        format!("{:?}", point.attributes),
+    )
+}
+
+/// Returns the point relative to its baseline, or `None` when it did not change.
+fn relative_to(mut point: MetricPoint, base: &PointValue) -> Option<MetricPoint> {
+    if point.value == *base {
+        return None;
+    }
+
+    point.value = match (point.value, base) {
+        (PointValue::Number(value), PointValue::Number(base)) => PointValue::Number(value - base),
+        (
+            PointValue::Histogram { count, sum },
+            PointValue::Histogram {
+                count: base_count,
+                sum: base_sum,
+            },
+        ) => PointValue::Histogram {
+            count: count - base_count,
+            sum: sum - base_sum,
+        },
+        (value, _) => value,
+    };
+
+    Some(point)
+}
+
+fn metric_points(metric: &Metric) -> Vec<(Vec<KeyValue>, PointValue)> {
+    let data = metric.data.as_any();
+
+    if let Some(sum) = data.downcast_ref::<Sum<u64>>() {
+        sum_points(sum, |v| v as f64)
+    } else if let Some(sum) = data.downcast_ref::<Sum<i64>>() {
+        sum_points(sum, |v| v as f64)
+    } else if let Some(sum) = data.downcast_ref::<Sum<f64>>() {
+        sum_points(sum, |v| v)
+    } else if let Some(gauge) = data.downcast_ref::<Gauge<u64>>() {
+        gauge_points(gauge, |v| v as f64)
+    } else if let Some(gauge) = data.downcast_ref::<Gauge<i64>>() {
+        gauge_points(gauge, |v| v as f64)
+    } else if let Some(gauge) = data.downcast_ref::<Gauge<f64>>() {
+        gauge_points(gauge, |v| v)
+    } else if let Some(histogram) = data.downcast_ref::<Histogram<u64>>() {
+        histogram_points(histogram, |v| v as f64)
+    } else if let Some(histogram) = data.downcast_ref::<Histogram<f64>>() {
+        histogram_points(histogram, |v| v)
+    } else {
+        Vec::new()
+    }
+}
+
+fn sum_points<T: Copy>(sum: &Sum<T>, to_f64: fn(T) -> f64) -> Vec<(Vec<KeyValue>, PointValue)> {
+    sum.data_points
+        .iter()
+        .map(|p| (p.attributes.clone(), PointValue::Number(to_f64(p.value))))
+        .collect()
+}
+
+fn gauge_points<T: Copy>(
+    gauge: &Gauge<T>,
+    to_f64: fn(T) -> f64,
+) -> Vec<(Vec<KeyValue>, PointValue)> {
+    gauge
+        .data_points
+        .iter()
+        .map(|p| (p.attributes.clone(), PointValue::Number(to_f64(p.value))))
+        .collect()
+}
+
+fn histogram_points<T: Copy>(
+    histogram: &Histogram<T>,
+    to_f64: fn(T) -> f64,
+) -> Vec<(Vec<KeyValue>, PointValue)> {
+    histogram
+        .data_points
+        .iter()
+        .map(|p| {
+            let value = PointValue::Histogram {
+                count: p.count,
+                sum: to_f64(p.sum),
+            };
+            (p.attributes.clone(), value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NameTransform;
+
+    #[test]
+    fn snapshot_returns_every_point() {
+        let harness = TestHarness::new();
+        let meter = harness.meter("orders");
+        meter
+            .u64_counter("orders.created")
+            .build()
+            .add(2, &[KeyValue::new("region", "eu")]);
+        let latency = meter.f64_histogram("orders.latency").build();
+        latency.record(1.0, &[]);
+        latency.record(2.5, &[]);
+
+        let mut points = harness.collect_and_snapshot();
+        points.sort_by(|a, b| a.name.cmp(&b.name));
+
+        assert_eq!(
+            points,
+            vec![
+                MetricPoint {
+                    scope: "orders".to_string(),
+                    name: "orders.created".to_string(),
+                    attributes: vec![KeyValue::new("region", "eu")],
+                    value: PointValue::Number(2.0),
+                },
+                MetricPoint {
+                    scope: "orders".to_string(),
+                    name: "orders.latency".to_string(),
+                    attributes: Vec::new(),
+                    value: PointValue::Histogram { count: 2, sum: 3.5 },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn snapshots_are_cumulative_and_repeatable() {
+        let harness = TestHarness::new();
+        let counter = harness
+            .meter("orders")
+            .u64_counter("orders.created")
+            .build();
+
+        counter.add(1, &[]);
+        assert_eq!(
+            harness.collect_and_snapshot()[0].value,
+            PointValue::Number(1.0)
+        );
+        counter.add(2, &[]);
+        assert_eq!(
+            harness.collect_and_snapshot()[0].value,
+            PointValue::Number(3.0)
+        );
+    }
+
+    #[test]
+    fn harness_applies_the_configuration() {
+        let cfg = MetricsConfig {
+            name_transform: Some(NameTransform::new(|name| name.replace('.', "_"))),
+            ..MetricsConfig::default()
+        };
+        let harness = TestHarness::with_config(&cfg);
+        harness
+            .meter("orders")
+            .u64_counter("orders.created")
+            .build()
+            .add(1, &[]);
+
+        assert_eq!(harness.collect_and_snapshot()[0].name, "orders_created");
+    }
+
+    #[test]
+    fn reset_clears_sums_but_not_gauges() {
+        let harness = TestHarness::new();
+        let meter = harness.meter("orders");
+        let counter = meter.u64_counter("orders.created").build();
+        let other = meter.u64_counter("orders.cancelled").build();
+        let queue = meter.u64_gauge("orders.queue").build();
+        counter.add(5, &[]);
+        other.add(1, &[]);
+        queue.record(7, &[]);
+
+        harness.reset();
+        counter.add(2, &[]);
+
+        let mut points = harness.collect_and_snapshot();
+        points.sort_by(|a, b| a.name.cmp(&b.name));
+        let values = points
+            .into_iter()
+            .map(|p| (p.name, p.value))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                ("orders.created".to_string(), PointValue::Number(2.0)),
+                ("orders.queue".to_string(), PointValue::Number(7.0)),
+            ]
+        );
+    }
+}
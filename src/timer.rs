@@ -0,0 +1,160 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Timers
+//!
+//! Provides an RAII helper recording the duration of a scope to a histogram.
+//!
+//! The [`Timer`] returned by [`timer`] starts measuring when created and records the elapsed
+//! time when dropped, so the duration is recorded on every exit path, including early returns
+//! and `?`.
+//!
+//! ## Units
+//!
+//! Durations are recorded in seconds, the unit required by the OpenTelemetry semantic
+//! conventions, and histograms fed by [`timer`] should declare the `s` unit. Teams whose
+//! dashboards expect milliseconds can use [`timer_ms`] instead, with histograms declaring the
+//! `ms` unit. Mixing both units on the same histogram produces meaningless data.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use opentelemetry::{KeyValue, global};
+//!
+//! let histogram = global::meter("db")
+//!     .f64_histogram("db.query.duration")
+//!     .with_unit("s")
+//!     .build();
+//!
+//! {
+//!     let _t = metrics::timer(&histogram, &[KeyValue::new("db.operation", "select")]);
+//!     // ... run the query ...
+//! } // recorded here
+//! ```
+
+use opentelemetry::{KeyValue, metrics::Histogram};
+use std::time::Instant;
+
+/// # Timer
+///
+/// Records the time elapsed since its creation to a histogram when dropped.
+#[derive(Debug)]
+#[must_use = "the duration is recorded when the timer is dropped"]
+pub struct Timer {
+    histogram: Histogram<f64>,
+    attributes: Vec<KeyValue>,
+    start: Instant,
+    unit: TimeUnit,
+}
+
+/// The unit of the durations recorded by a [`Timer`].
+#[derive(Debug, Clone, Copy)]
+enum TimeUnit {
+    Seconds,
+    Milliseconds,
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        let elapsed = match self.unit {
+            TimeUnit::Seconds => elapsed.as_secs_f64(),
+            TimeUnit::Milliseconds => elapsed.as_secs_f64() * 1_000.0,
+        };
+        self.histogram.record(elapsed, &self.attributes);
+    }
+}
+
+/// Starts a timer recording the elapsed seconds to `histogram` when dropped.
+///
+/// # Parameters
+///
+/// * `histogram` - The histogram receiving the duration
+/// * `attrs` - The attributes of the recorded duration
+///
+/// # Returns
+///
+/// The running [`Timer`]
+pub fn timer(histogram: &Histogram<f64>, attrs: &[KeyValue]) -> Timer {
+    start(histogram, attrs, TimeUnit::Seconds)
+}
+
+/// Starts a timer recording the elapsed milliseconds to `histogram` when dropped.
+///
+/// Prefer [`timer`] unless the histogram is already established in milliseconds.
+///
+/// # Parameters
+///
+/// * `histogram` - The histogram receiving the duration
+/// * `attrs` - The attributes of the recorded duration
+///
+/// # Returns
+///
+/// The running [`Timer`]
+pub fn timer_ms(histogram: &Histogram<f64>, attrs: &[KeyValue]) -> Timer {
+    start(histogram, attrs, TimeUnit::Milliseconds)
+}
+
+fn start(histogram: &Histogram<f64>, attrs: &[KeyValue], unit: TimeUnit) -> Timer {
+    Timer {
+        histogram: histogram.clone(),
+        attributes: attrs.to_vec(),
+        start: Instant::now(),
+        unit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::MetricsConfig, test_util};
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry_sdk::metrics::data;
+    use std::{thread, time::Duration};
+
+    #[test]
+    fn timer_records_the_scope_duration_when_dropped() {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        let histogram = provider.meter("test").f64_histogram("duration").build();
+
+        {
+            let _t = timer(&histogram, &[KeyValue::new("op", "select")]);
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let rm = test_util::collect(&reader);
+        let duration = test_util::metric(&rm, "duration").expect("histogram");
+        let hist = duration
+            .data
+            .as_any()
+            .downcast_ref::<data::Histogram<f64>>()
+            .unwrap();
+        assert_eq!(hist.data_points[0].count, 1);
+        assert!(hist.data_points[0].sum >= 0.01);
+        assert_eq!(
+            hist.data_points[0].attributes,
+            vec![KeyValue::new("op", "select")]
+        );
+    }
+
+    #[test]
+    fn timer_ms_records_milliseconds() {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        let histogram = provider.meter("test").f64_histogram("duration").build();
+
+        {
+            let _t = timer_ms(&histogram, &[]);
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let rm = test_util::collect(&reader);
+        let duration = test_util::metric(&rm, "duration").expect("histogram");
+        let hist = duration
+            .data
+            .as_any()
+            .downcast_ref::<data::Histogram<f64>>()
+            .unwrap();
+        assert!(hist.data_points[0].sum >= 10.0);
+    }
+}
@@ -0,0 +1,407 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Tracing Bridge
+//!
+//! Provides a `tracing_subscriber` layer that records metrics from `tracing` events.
+//!
+//! This module is conditionally compiled when the "tracing-bridge" feature is enabled. It lets
+//! applications drive metrics with the same macros they already use for logging, instead of
+//! holding OpenTelemetry instruments.
+//!
+//! ## Field Prefixes
+//!
+//! The prefix of an event field selects the instrument it updates. The rest of the field name
+//! is the instrument name:
+//!
+//! | Prefix | Instrument | Value types |
+//! |--------|------------|-------------|
+//! | `monotonic_counter.` | Counter | `u64`, `f64` |
+//! | `counter.` | UpDownCounter | `i64`, `f64` |
+//! | `histogram.` | Histogram | `u64`, `f64` |
+//!
+//! Every other field of the event (strings, booleans and numbers) is attached as an attribute
+//! to the recorded measurements. Events without a prefixed field are ignored.
+//!
+//! OpenTelemetry integer attributes and up-down counters are signed, so `u64` values above
+//! `i64::MAX` can not be represented. Such a `counter.` field or attribute is skipped with a
+//! warning instead of wrapping around to a negative value. Likewise, a negative `i64` on a
+//! `monotonic_counter.` or `histogram.` field is skipped with a warning instead of being
+//! recorded as zero.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use metrics::{provider, tracing_bridge::MetricsLayer};
+//! use tracing_subscriber::layer::SubscriberExt;
+//!
+//! let provider = provider::install().unwrap();
+//! let subscriber = tracing_subscriber::registry().with(MetricsLayer::new(&provider));
+//! tracing::subscriber::set_global_default(subscriber).unwrap();
+//!
+//! // increments the `http.requests` counter with the `http.status` attribute
+//! tracing::info!(monotonic_counter.http.requests = 1_u64, http.status = 200_i64);
+//! ```
+
+use crate::{LOG_TARGET, non_finite::MeasurementValue};
+use opentelemetry::{
+    KeyValue,
+    metrics::{Counter, Histogram, Meter, MeterProvider, UpDownCounter},
+};
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use std::{collections::HashMap, fmt, hash::Hash, sync::RwLock};
+use tracing::{
+    Event, Subscriber,
+    field::{Field, Visit},
+    warn,
+};
+use tracing_subscriber::layer::{Context, Layer};
+
+const METER_NAME: &str = "metrics.tracing_bridge";
+const MONOTONIC_COUNTER_PREFIX: &str = "monotonic_counter.";
+const COUNTER_PREFIX: &str = "counter.";
+const HISTOGRAM_PREFIX: &str = "histogram.";
+
+/// # MetricsLayer
+///
+/// A `tracing_subscriber` layer recording the prefixed fields of events as metrics.
+///
+/// Instruments are created lazily on the first event that references them, and cached for the
+/// lifetime of the layer.
+pub struct MetricsLayer {
+    meter: Meter,
+    instruments: Instruments,
+}
+
+impl MetricsLayer {
+    /// Creates a layer recording on the given provider.
+    ///
+    /// # Parameters
+    ///
+    /// * `provider` - The installed meter provider
+    pub fn new(provider: &SdkMeterProvider) -> Self {
+        Self {
+            meter: provider.meter(METER_NAME),
+            instruments: Instruments::default(),
+        }
+    }
+}
+
+impl fmt::Debug for MetricsLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MetricsLayer")
+    }
+}
+
+impl<S: Subscriber> Layer<S> for MetricsLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MetricVisitor::default();
+        event.record(&mut visitor);
+
+        for measurement in visitor.measurements {
+            self.instruments
+                .record(&self.meter, measurement, &visitor.attributes);
+        }
+    }
+}
+
+/// A measurement extracted from a prefixed event field.
+enum Measurement {
+    MonotonicU64(&'static str, u64),
+    MonotonicF64(&'static str, f64),
+    UpDownI64(&'static str, i64),
+    UpDownF64(&'static str, f64),
+    HistogramU64(&'static str, u64),
+    HistogramF64(&'static str, f64),
+}
+
+/// Collects the measurements and attributes of a single event.
+#[derive(Default)]
+struct MetricVisitor {
+    measurements: Vec<Measurement>,
+    attributes: Vec<KeyValue>,
+}
+
+impl Visit for MetricVisitor {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        let name = field.name();
+        if let Some(metric) = name.strip_prefix(MONOTONIC_COUNTER_PREFIX) {
+            self.measurements
+                .push(Measurement::MonotonicU64(metric, value));
+        } else if let Some(metric) = name.strip_prefix(COUNTER_PREFIX) {
+            if let Some(value) = signed(name, value) {
+                self.measurements
+                    .push(Measurement::UpDownI64(metric, value));
+            }
+        } else if let Some(metric) = name.strip_prefix(HISTOGRAM_PREFIX) {
+            self.measurements
+                .push(Measurement::HistogramU64(metric, value));
+        } else if let Some(value) = signed(name, value) {
+            self.attributes.push(KeyValue::new(name, value));
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        let name = field.name();
+        if let Some(metric) = name.strip_prefix(MONOTONIC_COUNTER_PREFIX) {
+            if let Some(value) = unsigned(name, value) {
+                self.measurements
+                    .push(Measurement::MonotonicU64(metric, value));
+            }
+        } else if let Some(metric) = name.strip_prefix(COUNTER_PREFIX) {
+            self.measurements
+                .push(Measurement::UpDownI64(metric, value));
+        } else if let Some(metric) = name.strip_prefix(HISTOGRAM_PREFIX) {
+            if let Some(value) = unsigned(name, value) {
+                self.measurements
+                    .push(Measurement::HistogramU64(metric, value));
+            }
+        } else {
+            self.attributes.push(KeyValue::new(name, value));
+        }
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        let name = field.name();
+        if let Some(metric) = name.strip_prefix(MONOTONIC_COUNTER_PREFIX) {
+            self.measurements
+                .push(Measurement::MonotonicF64(metric, value));
+        } else if let Some(metric) = name.strip_prefix(COUNTER_PREFIX) {
+            self.measurements
+                .push(Measurement::UpDownF64(metric, value));
+        } else if let Some(metric) = name.strip_prefix(HISTOGRAM_PREFIX) {
+            self.measurements
+                .push(Measurement::HistogramF64(metric, value));
+        } else {
+            self.attributes.push(KeyValue::new(name, value));
+        }
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.attributes.push(KeyValue::new(field.name(), value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.attributes
+            .push(KeyValue::new(field.name(), value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        // the event message is not an attribute
+        if field.name() == "message" {
+            return;
+        }
+        self.attributes
+            .push(KeyValue::new(field.name(), format!("{value:?}")));
+    }
+}
+
+/// Converts a `u64` field to the `i64` OpenTelemetry expects, or warns and returns `None` when
+/// it does not fit.
+fn signed(field: &str, value: u64) -> Option<i64> {
+    match i64::try_from(value) {
+        Ok(value) => Some(value),
+        Err(_) => {
+            warn!(
+                target: LOG_TARGET,
+                field,
+                value,
+                "tracing bridge field skipped, the value does not fit in an i64"
+            );
+            None
+        }
+    }
+}
+
+/// Converts an `i64` field of a monotonic counter or histogram to a `u64`, or warns and returns
+/// `None` when it is negative.
+fn unsigned(field: &str, value: i64) -> Option<u64> {
+    match u64::try_from(value) {
+        Ok(value) => Some(value),
+        Err(_) => {
+            warn!(
+                target: LOG_TARGET,
+                field,
+                value,
+                "tracing bridge field skipped, a negative value can not be recorded"
+            );
+            None
+        }
+    }
+}
+
+/// The instruments created by the layer, keyed by name.
+#[derive(Default)]
+struct Instruments {
+    u64_counters: RwLock<HashMap<&'static str, Counter<u64>>>,
+    f64_counters: RwLock<HashMap<&'static str, Counter<f64>>>,
+    i64_up_down_counters: RwLock<HashMap<&'static str, UpDownCounter<i64>>>,
+    f64_up_down_counters: RwLock<HashMap<&'static str, UpDownCounter<f64>>>,
+    u64_histograms: RwLock<HashMap<&'static str, Histogram<u64>>>,
+    f64_histograms: RwLock<HashMap<&'static str, Histogram<f64>>>,
+}
+
+impl Instruments {
+    fn record(&self, meter: &Meter, measurement: Measurement, attributes: &[KeyValue]) {
+        match measurement {
+            Measurement::MonotonicU64(name, value) => {
+                with_instrument(&self.u64_counters, name, |n| meter.u64_counter(n).build())
+                    .add(value, attributes)
+            }
+            Measurement::MonotonicF64(name, value) => {
+                let Some(value) = value.guard() else { return };
+                with_instrument(&self.f64_counters, name, |n| meter.f64_counter(n).build())
+                    .add(value, attributes)
+            }
+            Measurement::UpDownI64(name, value) => {
+                with_instrument(&self.i64_up_down_counters, name, |n| {
+                    meter.i64_up_down_counter(n).build()
+                })
+                .add(value, attributes)
+            }
+            Measurement::UpDownF64(name, value) => {
+                let Some(value) = value.guard() else { return };
+                with_instrument(&self.f64_up_down_counters, name, |n| {
+                    meter.f64_up_down_counter(n).build()
+                })
+                .add(value, attributes)
+            }
+            Measurement::HistogramU64(name, value) => {
+                with_instrument(&self.u64_histograms, name, |n| {
+                    meter.u64_histogram(n).build()
+                })
+                .record(value, attributes)
+            }
+            Measurement::HistogramF64(name, value) => {
+                let Some(value) = value.guard() else { return };
+                with_instrument(&self.f64_histograms, name, |n| {
+                    meter.f64_histogram(n).build()
+                })
+                .record(value, attributes)
+            }
+        }
+    }
+}
+
+/// Returns the cached instrument for `name`, creating it with `build` on first use.
+fn with_instrument<K, I>(cache: &RwLock<HashMap<K, I>>, name: K, build: impl FnOnce(K) -> I) -> I
+where
+    K: Eq + Hash + Copy,
+    I: Clone,
+{
+    if let Some(instrument) = cache.read().unwrap_or_else(|e| e.into_inner()).get(&name) {
+        return instrument.clone();
+    }
+
+    cache
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(name)
+        .or_insert_with(|| build(name))
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::MetricsConfig, test_util};
+    use opentelemetry_sdk::metrics::data::Sum;
+    use tracing::{
+        Metadata,
+        span::{Attributes, Id, Record},
+    };
+
+    /// A subscriber doing nothing but enabling every event, for the layer to sit on.
+    struct Bare;
+
+    impl Subscriber for Bare {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _span: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, _event: &Event<'_>) {}
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    fn with_layer(provider: &SdkMeterProvider, emit: impl FnOnce()) {
+        let subscriber = MetricsLayer::new(provider).with_subscriber(Bare);
+        tracing::subscriber::with_default(subscriber, emit);
+    }
+
+    #[test]
+    fn prefixed_fields_are_recorded_with_attributes() {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+
+        with_layer(&provider, || {
+            tracing::info!(
+                monotonic_counter.http.requests = 2_u64,
+                http.status = 200_i64
+            );
+            tracing::info!(counter.queue.depth = 5_u64);
+        });
+
+        let rm = test_util::collect(&reader);
+        let requests = test_util::metric(&rm, "http.requests").expect("counter");
+        let sum = requests.data.as_any().downcast_ref::<Sum<u64>>().unwrap();
+        assert_eq!(sum.data_points[0].value, 2);
+        assert_eq!(
+            sum.data_points[0].attributes,
+            vec![KeyValue::new("http.status", 200_i64)]
+        );
+
+        let depth = test_util::metric(&rm, "queue.depth").expect("up-down counter");
+        let sum = depth.data.as_any().downcast_ref::<Sum<i64>>().unwrap();
+        assert_eq!(sum.data_points[0].value, 5);
+    }
+
+    #[test]
+    fn values_above_i64_max_are_skipped() {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+
+        with_layer(&provider, || {
+            tracing::info!(counter.queue.depth = u64::MAX);
+            tracing::info!(
+                monotonic_counter.http.requests = 1_u64,
+                request.size = u64::MAX
+            );
+        });
+
+        let rm = test_util::collect(&reader);
+        assert!(test_util::metric(&rm, "queue.depth").is_none());
+
+        let requests = test_util::metric(&rm, "http.requests").expect("counter");
+        let sum = requests.data.as_any().downcast_ref::<Sum<u64>>().unwrap();
+        assert!(sum.data_points[0].attributes.is_empty());
+    }
+
+    #[test]
+    fn negative_counter_and_histogram_values_are_skipped() {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+
+        with_layer(&provider, || {
+            tracing::info!(monotonic_counter.http.requests = -1_i64);
+            tracing::info!(histogram.http.latency = -5_i64);
+            tracing::info!(monotonic_counter.http.retries = 2_i64);
+        });
+
+        let rm = test_util::collect(&reader);
+        assert!(test_util::metric(&rm, "http.requests").is_none());
+        assert!(test_util::metric(&rm, "http.latency").is_none());
+
+        let retries = test_util::metric(&rm, "http.retries").expect("counter");
+        let sum = retries.data.as_any().downcast_ref::<Sum<u64>>().unwrap();
+        assert_eq!(sum.data_points[0].value, 2);
+    }
+}
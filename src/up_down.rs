@@ -0,0 +1,152 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # UpDownCounters
+//!
+//! Provides helpers creating described up-down counters in a single call.
+//!
+//! Up-down counters track totals that go both ways, such as open connections or queued jobs.
+//! Cumulative is the default temporality of the OTLP exporter, and the one of the Prometheus and
+//! stdout exporters, so the exported value is the current total rather than the change since
+//! the previous export. The StatsD exporter
+//! sends the change as a signed gauge adjustment, which the server adds to its own total.
+//!
+//! [`up_down_counter`] creates the synchronous instrument, updated by the application, and
+//! [`observable_up_down_counter_with`] the observable one, polled at collection time. Both
+//! declare the unit and description, which the exporters propagate to the backends.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use metrics::{observable_up_down_counter_with, up_down_counter};
+//! use opentelemetry::global;
+//!
+//! let meter = global::meter("pool");
+//!
+//! let connections = up_down_counter(
+//!     &meter,
+//!     "db.client.connections.usage",
+//!     "{connection}",
+//!     "Connections currently open",
+//! );
+//! connections.add(1, &[]);
+//! connections.add(-1, &[]);
+//!
+//! observable_up_down_counter_with(
+//!     &meter,
+//!     "jobs.queue.size",
+//!     "{job}",
+//!     "Jobs waiting in the queue",
+//!     || 42,
+//! );
+//! ```
+
+use crate::observable;
+use opentelemetry::metrics::{Meter, ObservableUpDownCounter, UpDownCounter};
+use std::borrow::Cow;
+
+/// Creates an up-down counter with the given unit and description.
+///
+/// # Parameters
+///
+/// * `meter` - The meter owning the instrument
+/// * `name` - The name of the instrument
+/// * `unit` - The unit of the instrument, following the UCUM conventions
+/// * `description` - The description of the instrument
+///
+/// # Returns
+///
+/// The up-down counter
+pub fn up_down_counter(
+    meter: &Meter,
+    name: impl Into<Cow<'static, str>>,
+    unit: impl Into<Cow<'static, str>>,
+    description: impl Into<Cow<'static, str>>,
+) -> UpDownCounter<i64> {
+    meter
+        .i64_up_down_counter(name)
+        .with_unit(unit)
+        .with_description(description)
+        .build()
+}
+
+/// Creates an observable up-down counter with the given unit and description, reporting the
+/// total returned by `callback` on every collection.
+///
+/// # Parameters
+///
+/// * `meter` - The meter owning the instrument
+/// * `name` - The name of the instrument
+/// * `unit` - The unit of the instrument, following the UCUM conventions
+/// * `description` - The description of the instrument
+/// * `callback` - Returns the current total, which may increase or decrease
+///
+/// # Returns
+///
+/// A handle to the instrument, which may be dropped without unregistering the callback
+pub fn observable_up_down_counter_with<F>(
+    meter: &Meter,
+    name: impl Into<Cow<'static, str>>,
+    unit: impl Into<Cow<'static, str>>,
+    description: impl Into<Cow<'static, str>>,
+    callback: F,
+) -> ObservableUpDownCounter<i64>
+where
+    F: Fn() -> i64 + Send + Sync + 'static,
+{
+    observable::up_down_counter_builder(meter, name, callback)
+        .with_unit(unit)
+        .with_description(description)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::MetricsConfig, test_util};
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry_sdk::metrics::{Temporality, data::Sum};
+
+    #[test]
+    fn up_down_counter_reports_the_described_total() {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        let connections = up_down_counter(
+            &provider.meter("pool"),
+            "connections",
+            "{connection}",
+            "Connections currently open",
+        );
+        connections.add(3, &[]);
+        connections.add(-1, &[]);
+
+        let rm = test_util::collect(&reader);
+
+        let metric = test_util::metric(&rm, "connections").expect("metric");
+        assert_eq!(metric.unit, "{connection}");
+        assert_eq!(metric.description, "Connections currently open");
+        let sum = metric.data.as_any().downcast_ref::<Sum<i64>>().unwrap();
+        assert!(!sum.is_monotonic);
+        assert_eq!(sum.temporality, Temporality::Cumulative);
+        assert_eq!(sum.data_points[0].value, 2);
+    }
+
+    #[test]
+    fn observable_up_down_counter_reports_the_callback_total() {
+        let (provider, reader) = test_util::provider(&MetricsConfig::default());
+        let _queue = observable_up_down_counter_with(
+            &provider.meter("jobs"),
+            "queue",
+            "{job}",
+            "Jobs waiting in the queue",
+            || -4,
+        );
+
+        let rm = test_util::collect(&reader);
+
+        let metric = test_util::metric(&rm, "queue").expect("metric");
+        assert_eq!(metric.unit, "{job}");
+        let sum = metric.data.as_any().downcast_ref::<Sum<i64>>().unwrap();
+        assert_eq!(sum.data_points[0].value, -4);
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright (c) 2025, The Ruskit Authors
+// MIT License
+// All rights reserved.
+
+//! # Instrument Validation
+//!
+//! Checks the registered instruments against the OpenTelemetry and Prometheus naming rules.
+//!
+//! The SDK already rejects instruments whose own name is invalid, turning them into no-op
+//! instruments with a log line that is easy to miss. This module catches what remains:
+//!
+//! - Exported names made invalid by the `name_transform` hook
+//! - Distinct names that collide once translated to the Prometheus character set, where `.`,
+//!   `-` and `/` all become `_`, for example `http.requests` and `http_requests`
+//!
+//! Attribute keys are only known when measurements are recorded, so they are not checked.
+//!
+//! ## Strict Mode
+//!
+//! The instruments are read from `MetricsConfig::instrument_catalog`, so validation runs once
+//! the application created its instruments. With `strict_validation` enabled,
+//! [`validate_instruments`] fails with every violation, which lets a CI job reject bad
+//! instrumentation before release. Otherwise each violation is only logged as a warning.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use metrics::{
+//!     config::{InstrumentCatalog, MetricsConfig},
+//!     validate_instruments,
+//! };
+//!
+//! let cfg = MetricsConfig {
+//!     instrument_catalog: Some(InstrumentCatalog::default()),
+//!     strict_validation: true,
+//!     ..MetricsConfig::load()
+//! };
+//!
+//! // after installing with `cfg` and creating the instruments
+//! validate_instruments(&cfg).unwrap();
+//! ```
+
+use crate::{LOG_TARGET, config::MetricsConfig, errors::MetricsError};
+use std::collections::BTreeMap;
+use tracing::{error, warn};
+
+/// Maximum length of an instrument name.
+const NAME_MAX_LENGTH: usize = 255;
+
+/// Validates the instruments registered in the configured catalog.
+///
+/// # Parameters
+///
+/// * `cfg` - The configuration the provider was installed with
+///
+/// # Returns
+///
+/// * `Ok(())` - If no instrument violates the rules, or if `strict_validation` is disabled
+/// * `Err(MetricsError::InvalidConfigError)` - Listing every violation in strict mode, or if
+///   no `instrument_catalog` is configured
+pub fn validate_instruments(cfg: &MetricsConfig) -> Result<(), MetricsError> {
+    let Some(catalog) = &cfg.instrument_catalog else {
+        error!(
+            target: LOG_TARGET,
+            "instrument validation requires an instrument catalog"
+        );
+        return Err(MetricsError::InvalidConfigError(
+            "instrument validation requires an instrument_catalog".to_string(),
+        ));
+    };
+
+    let mut violations = Vec::new();
+    let mut prometheus_names: BTreeMap<String, String> = BTreeMap::new();
+
+    for entry in catalog.entries() {
+        let name = match &cfg.name_transform {
+            Some(transform) => transform.apply(&entry.name),
+            None => entry.name.clone(),
+        };
+
+        if let Some(reason) = name_violation(&name) {
+            violations.push(format!("{}/{name}: {reason}", entry.scope));
+        }
+
+        let translated = prometheus_name(&name);
+        match prometheus_names.get(&translated) {
+            Some(other) if *other != name => violations.push(format!(
+                "{}/{name}: collides with {other} as the Prometheus name {translated}",
+                entry.scope
+            )),
+            Some(_) => {}
+            None => {
+                prometheus_names.insert(translated, name);
+            }
+        }
+    }
+
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    if !cfg.strict_validation {
+        for violation in &violations {
+            warn!(target: LOG_TARGET, violation = %violation, "invalid instrument");
+        }
+        return Ok(());
+    }
+
+    let violations = violations.join("; ");
+    error!(
+        target: LOG_TARGET,
+        violations = %violations,
+        "instrument validation failed"
+    );
+    Err(MetricsError::InvalidConfigError(violations))
+}
+
+/// Returns why a name breaks the OpenTelemetry instrument name syntax, if it does.
+fn name_violation(name: &str) -> Option<&'static str> {
+    if name.is_empty() {
+        return Some("name must be non-empty");
+    }
+    if name.len() > NAME_MAX_LENGTH {
+        return Some("name must be at most 255 characters");
+    }
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Some("name must start with an ASCII letter");
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-' | '/'))
+    {
+        return Some("name must only contain ASCII letters, digits, '_', '.', '-' and '/'");
+    }
+    None
+}
+
+/// Translates a name to the Prometheus character set.
+fn prometheus_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{InstrumentCatalog, NameTransform},
+        test_util,
+    };
+    use opentelemetry::metrics::MeterProvider;
+
+    fn config(strict: bool) -> MetricsConfig {
+        MetricsConfig {
+            instrument_catalog: Some(InstrumentCatalog::default()),
+            strict_validation: strict,
+            ..MetricsConfig::default()
+        }
+    }
+
+    #[test]
+    fn strict_validation_reports_prometheus_collisions() {
+        let cfg = config(true);
+        let (provider, _reader) = test_util::provider(&cfg);
+        let meter = provider.meter("http");
+        meter.u64_counter("http.requests").build();
+        meter.u64_counter("http_requests").build();
+
+        let Err(MetricsError::InvalidConfigError(violations)) = validate_instruments(&cfg) else {
+            panic!("expected a validation error");
+        };
+
+        assert!(violations.contains("collides with"));
+    }
+
+    #[test]
+    fn strict_validation_checks_the_transformed_names() {
+        let cfg = MetricsConfig {
+            name_transform: Some(NameTransform::new(|name| format!("1{name}"))),
+            ..config(true)
+        };
+        let (provider, _reader) = test_util::provider(&cfg);
+        provider.meter("http").u64_counter("requests").build();
+
+        let Err(MetricsError::InvalidConfigError(violations)) = validate_instruments(&cfg) else {
+            panic!("expected a validation error");
+        };
+
+        assert!(violations.contains("must start with an ASCII letter"));
+    }
+
+    #[test]
+    fn lenient_validation_only_warns() {
+        let cfg = config(false);
+        let (provider, _reader) = test_util::provider(&cfg);
+        let meter = provider.meter("http");
+        meter.u64_counter("http.requests").build();
+        meter.u64_counter("http_requests").build();
+
+        let logs = test_util::capture_logs(|| assert!(validate_instruments(&cfg).is_ok()));
+
+        assert_eq!(logs.len(), 1);
+    }
+
+    #[test]
+    fn validation_requires_a_catalog() {
+        assert!(matches!(
+            validate_instruments(&MetricsConfig::default()),
+            Err(MetricsError::InvalidConfigError(_))
+        ));
+    }
+}